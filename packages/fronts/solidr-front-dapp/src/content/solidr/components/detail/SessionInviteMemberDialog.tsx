@@ -95,7 +95,7 @@ export default ({ dialogVisible, setDialogVisible }: IInviteMemberDialogProps) =
                         </>
                     )}
                     <Button onClick={() => {
-                        solidrClient.generateSessionLink(anchorWallet, new BN(sessionCurrent.session.sessionId))
+                        solidrClient.createInvitation(anchorWallet, new BN(sessionCurrent.session.sessionId))
                             .then(({ data: { token } }) => {
                                 setInvitationToken(token);
                                 generateUrl();
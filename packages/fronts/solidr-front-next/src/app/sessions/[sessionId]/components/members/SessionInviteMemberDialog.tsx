@@ -32,7 +32,7 @@ export default function InviteMemberDialog({ dialogVisible, setDialogVisible }:
 
     const onSubmit = async () => {
         if (!solidrClient || !sessionCurrent.session) return;
-        const { data: { token } } = await solidrClient.generateSessionLink(anchorWallet, new BN(sessionCurrent.session.sessionId))
+        const { data: { token } } = await solidrClient.createInvitation(anchorWallet, new BN(sessionCurrent.session.sessionId))
         setInvitationToken(token);
         generateUrl();
     };
@@ -1,22 +1,24 @@
-import { BN, Program, Wallet } from '@coral-xyz/anchor';
-import { PublicKey, SendOptions, Transaction, TransactionInstruction } from '@solana/web3.js';
+import { BN, Program, utils, Wallet } from '@coral-xyz/anchor';
+import { AccountMeta, LAMPORTS_PER_SOL, PublicKey, SendOptions, Transaction, TransactionInstruction } from '@solana/web3.js';
 import { sha256 } from 'js-sha256';
 import { bs58 } from '@coral-xyz/anchor/dist/cjs/utils/bytes';
 import * as _ from 'lodash';
 
 import { AbstractSolanaClient, ITransactionResult, ProgramInstructionWrapper } from './AbstractSolanaClient';
 import { Solidr } from '../target/types/solidr';
-import { generateSessionLinkTokenData } from './TokenHelpers';
-import { HermesClient } from '@pythnetwork/hermes-client';
+import { generateSessionLinkTokenData, hashToken } from './TokenHelpers';
 
 export type Global = {
     sessionCount: BN;
 };
 
 type InternalSessionStatus =
-    | ({ closed?: never } & { opened: Record<string, never> })
-    | ({ opened?: never } & {
+    | ({ closed?: never; settled?: never } & { opened: Record<string, never> })
+    | ({ opened?: never; settled?: never } & {
           closed: Record<string, never>;
+      })
+    | ({ opened?: never; closed?: never } & {
+          settled: Record<string, never>;
       });
 
 type InternalSession = {
@@ -28,6 +30,77 @@ type InternalSession = {
     expensesCount: number;
     refundsCount: number;
     invitationHash: number[];
+    decimals: number;
+    currency: string;
+    mint: PublicKey | null;
+    mintCurrency: string;
+    pendingAdmin: PublicKey | null;
+    approvalThreshold: number;
+    expenseApproval: InternalExpenseApproval;
+    closedAt: BN;
+    reopenWindow: BN;
+};
+
+type InternalExpenseApproval = {
+    disabled?: Record<string, never>;
+    allParticipants?: Record<string, never>;
+    quorum?: { approvals: number };
+};
+
+type InternalMemberRole = {
+    admin?: Record<string, never>;
+    treasurer?: Record<string, never>;
+    member?: Record<string, never>;
+    viewer?: Record<string, never>;
+};
+
+type InternalSessionMember = {
+    sessionId: BN;
+    name: string;
+    addr: PublicKey;
+    isAdmin: boolean;
+    role: InternalMemberRole;
+};
+
+type InternalSplitMode = {
+    equal?: Record<string, never>;
+    weighted?: Record<string, never>;
+    percentage?: Record<string, never>;
+    exact?: Record<string, never>;
+};
+
+type InternalOriginalAmount = {
+    currency: string;
+    amount: BN;
+    decimals: number;
+    exchangeRate: BN;
+};
+
+type InternalExpenseParticipant = {
+    member: PublicKey;
+    share: BN;
+};
+
+type InternalExpenseStatus = {
+    approved?: Record<string, never>;
+    pending?: Record<string, never>;
+    rejected?: Record<string, never>;
+};
+
+type InternalExpenseCategory = {
+    other?: Record<string, never>;
+    food?: Record<string, never>;
+    transport?: Record<string, never>;
+    accommodation?: Record<string, never>;
+    entertainment?: Record<string, never>;
+    shopping?: Record<string, never>;
+    utilities?: Record<string, never>;
+    health?: Record<string, never>;
+};
+
+type InternalExpenseReceipt = {
+    hash: number[];
+    uri: string;
 };
 
 type InternalExpense = {
@@ -36,8 +109,43 @@ type InternalExpense = {
     name: string;
     owner: PublicKey;
     date: BN;
-    amount: number;
-    participants: PublicKey[];
+    amount: BN;
+    participants: InternalExpenseParticipant[];
+    splitMode: InternalSplitMode;
+    original: InternalOriginalAmount | null;
+    status: InternalExpenseStatus;
+    approvals: PublicKey[];
+    dispute: InternalExpenseDispute | null;
+    category: InternalExpenseCategory;
+    tags: string[];
+    receipt: InternalExpenseReceipt | null;
+    payer: PublicKey;
+};
+
+type InternalExpenseDispute = {
+    by: PublicKey;
+    date: BN;
+    reason: string;
+};
+
+type InternalRecurringStatus = {
+    active?: Record<string, never>;
+    paused?: Record<string, never>;
+};
+
+type InternalRecurringExpense = {
+    sessionId: BN;
+    recurringId: number;
+    owner: PublicKey;
+    name: string;
+    amount: BN;
+    splitMode: InternalSplitMode;
+    participants: InternalExpenseParticipant[];
+    interval: BN;
+    nextDue: BN;
+    status: InternalRecurringStatus;
+    category: InternalExpenseCategory;
+    tags: string[];
 };
 
 type InternalRefund = {
@@ -46,15 +154,93 @@ type InternalRefund = {
     date: BN;
     from: PublicKey;
     to: PublicKey;
-    amount: number;
+    amount: BN;
     amountInLamports: BN;
+    mint: PublicKey | null;
+    tokenAmount: BN;
+    settlementIndex: number | null;
+};
+
+type InternalSettlementTransfer = {
+    from: PublicKey;
+    to: PublicKey;
+    amount: BN;
+    paid: BN;
+};
+
+type InternalSettlement = {
+    sessionId: BN;
+    date: BN;
+    planId: number;
+    transfers: InternalSettlementTransfer[];
+};
+
+type InternalMemberBalanceAccount = {
+    sessionId: BN;
+    addr: PublicKey;
+    totalPaid: BN;
+    totalOwed: BN;
+    balance: BN;
+};
+
+type InternalVault = {
+    sessionId: BN;
+    epoch: number;
+    totalDeposited: BN;
+    totalLamports: BN;
+};
+
+type InternalProposalAction = {
+    closeSession?: Record<string, never>;
+    deleteSession?: Record<string, never>;
+    deleteMember?: { addr: PublicKey };
+    deleteRefund?: { refundId: number };
+    setApprovalThreshold?: { threshold: number };
+    reopenSession?: Record<string, never>;
+    setPriceFeed?: { priceFeedId: number[] };
+};
+
+type InternalProposal = {
+    sessionId: BN;
+    proposalId: number;
+    date: BN;
+    proposer: PublicKey;
+    action: InternalProposalAction;
+    approvals: PublicKey[];
+    executed: boolean;
+};
+
+type InternalInvitation = {
+    sessionId: BN;
+    invitationId: number;
+    tokenHash: number[];
+    expiresAt: BN;
+    maxUses: number;
+    remainingUses: number;
+    revoked: boolean;
+    label: string;
+    creator: PublicKey;
+    role: InternalMemberRole | null;
+};
+
+type InternalBudgetLine = {
+    limit: BN | null;
+    spent: BN;
+    hardCap: boolean;
+};
+
+type InternalBudget = {
+    sessionId: BN;
+    total: InternalBudgetLine;
+    categories: InternalBudgetLine[];
 };
 
-type PriceData = { price: string; expo: number };
+type PriceData = { price: BN; expo: number };
 
 export enum SessionStatus {
     Opened = 'opened',
     Closed = 'closed',
+    Settled = 'settled',
 }
 
 export type Session = {
@@ -66,15 +252,71 @@ export type Session = {
     expensesCount: number;
     refundsCount: number;
     invitationHash: string;
+    // ISO-4217 code of session currency, amounts being expressed with its decimals
+    currency: string;
+    decimals: number;
+    // mint of token used for refunds, refunds are paid in SOL when missing
+    mint: PublicKey | null;
+    // ISO-4217 code of the currency session token is pegged to
+    mintCurrency: string;
+    // member proposed as new administrator, until they accept
+    pendingAdmin: PublicKey | null;
+    // number of approvals required by destructive actions, disabled when 0
+    approvalThreshold: number;
+    // participants approvals required before expenses count toward balances
+    expenseApproval: ExpenseApproval;
+    closedAt: Date | null;
+    // seconds after closing during which admin can reopen session without majority approval
+    reopenWindow: number;
 };
 
+export enum ExpenseApprovalMode {
+    Disabled = 'disabled',
+    AllParticipants = 'allParticipants',
+    Quorum = 'quorum',
+}
+
+export type ExpenseApproval = { mode: ExpenseApprovalMode.Disabled | ExpenseApprovalMode.AllParticipants } | { mode: ExpenseApprovalMode.Quorum; approvals: number };
+
+export enum MemberRole {
+    Admin = 'admin',
+    Treasurer = 'treasurer',
+    Member = 'member',
+    Viewer = 'viewer',
+}
+
 export type SessionMember = {
     sessionId: BN;
     name: string;
     addr: PublicKey;
     isAdmin: boolean;
+    role: MemberRole;
+};
+
+export enum SplitMode {
+    Equal = 'equal',
+    Weighted = 'weighted',
+    Percentage = 'percentage',
+    Exact = 'exact',
+}
+
+export type OriginalAmount = {
+    currency: string;
+    amount: number;
+    decimals: number;
+    // price of one original currency unit in session currency
+    exchangeRate: number;
 };
 
+// exchange rates are stored scaled by 10^9
+const EXCHANGE_RATE_DECIMALS = 9;
+
+export enum ExpenseStatus {
+    Approved = 'approved',
+    Pending = 'pending',
+    Rejected = 'rejected',
+}
+
 export type Expense = {
     sessionId: BN;
     expenseId: number;
@@ -83,6 +325,101 @@ export type Expense = {
     date: Date;
     amount: number;
     participants: PublicKey[];
+    splitMode: SplitMode;
+    // weights, percentages or exact amounts depending on split mode, in participants order
+    shares: number[];
+    original: OriginalAmount | null;
+    // only approved expenses count toward balances
+    status: ExpenseStatus;
+    approvals: PublicKey[];
+    // disputed expenses are frozen until owner or admin resolves dispute
+    dispute: ExpenseDispute | null;
+    category: ExpenseCategory;
+    tags: string[];
+    receipt: ExpenseReceipt | null;
+    // member who paid the expense, owner being the member who recorded it
+    payer: PublicKey;
+};
+
+export type ExpenseReceipt = {
+    // hex encoded SHA-256 digest of receipt content
+    hash: string;
+    // off-chain storage location of receipt image or PDF
+    uri: string;
+};
+
+export enum ExpenseCategory {
+    Other = 'other',
+    Food = 'food',
+    Transport = 'transport',
+    Accommodation = 'accommodation',
+    Entertainment = 'entertainment',
+    Shopping = 'shopping',
+    Utilities = 'utilities',
+    Health = 'health',
+}
+
+export type BudgetLine = {
+    limit: number | null;
+    spent: number;
+    // expenses crossing the limit fail instead of only emitting BudgetExceeded
+    hardCap: boolean;
+};
+
+export type Budget = {
+    sessionId: BN;
+    total: BudgetLine;
+    categories: Record<ExpenseCategory, BudgetLine>;
+};
+
+export type ExpenseDispute = {
+    by: PublicKey;
+    date: Date;
+    reason: string;
+};
+
+export type DisputeAmendment = {
+    amount?: number;
+    // participants to add, or whose shares are updated
+    participants?: PublicKey[];
+    shares?: number[];
+};
+
+export type ExpenseOptions = {
+    splitMode?: SplitMode;
+    // weights, percentages or exact amounts depending on split mode, in participants order
+    shares?: number[];
+    // amount as written on receipt when paid in another currency
+    original?: OriginalAmount;
+    category?: ExpenseCategory;
+    tags?: string[];
+    receipt?: ExpenseReceipt;
+    // member who paid the expense, signer by default. Only admins and treasurers can record on behalf of another member
+    payer?: PublicKey;
+    // payer doesn't take part in the expense
+    excludePayer?: boolean;
+};
+
+export enum RecurringStatus {
+    Active = 'active',
+    Paused = 'paused',
+}
+
+export type RecurringExpense = {
+    sessionId: BN;
+    recurringId: number;
+    owner: PublicKey;
+    name: string;
+    amount: number;
+    participants: PublicKey[];
+    splitMode: SplitMode;
+    shares: number[];
+    // delay between two occurrences in seconds
+    interval: number;
+    nextDue: Date;
+    status: RecurringStatus;
+    category: ExpenseCategory;
+    tags: string[];
 };
 
 export type Refund = {
@@ -93,6 +430,89 @@ export type Refund = {
     to: PublicKey;
     amount: number;
     amountInLamports: BN;
+    mint: PublicKey | null;
+    // raw amount of tokens transferred, in mint decimals
+    tokenAmount: BN;
+    // index of settlement plan transfer fulfilled by this refund
+    settlementIndex: number | null;
+};
+
+export type SettlementTransfer = {
+    from: PublicKey;
+    to: PublicKey;
+    amount: number;
+    paid: number;
+};
+
+export type Settlement = {
+    sessionId: BN;
+    date: Date;
+    // incremented each time plan is recomputed, refunds of previous plans being unlinked
+    planId: number;
+    transfers: SettlementTransfer[];
+};
+
+export type MemberBalanceAccount = {
+    sessionId: BN;
+    addr: PublicKey;
+    totalPaid: number;
+    totalOwed: number;
+    balance: number;
+};
+
+export type Vault = {
+    sessionId: BN;
+    // incremented each time vault is paid out
+    epoch: number;
+    totalDeposited: number;
+    totalLamports: BN;
+};
+
+export enum ProposalActionType {
+    CloseSession = 'closeSession',
+    DeleteSession = 'deleteSession',
+    DeleteMember = 'deleteMember',
+    DeleteRefund = 'deleteRefund',
+    SetApprovalThreshold = 'setApprovalThreshold',
+    ReopenSession = 'reopenSession',
+    SetPriceFeed = 'setPriceFeed',
+}
+
+export type ProposalAction =
+    | { type: ProposalActionType.CloseSession | ProposalActionType.DeleteSession | ProposalActionType.ReopenSession }
+    | { type: ProposalActionType.DeleteMember; addr: PublicKey }
+    | { type: ProposalActionType.DeleteRefund; refundId: number }
+    | { type: ProposalActionType.SetApprovalThreshold; threshold: number }
+    | { type: ProposalActionType.SetPriceFeed; priceFeedId: string };
+
+export type Proposal = {
+    sessionId: BN;
+    proposalId: number;
+    date: Date;
+    proposer: PublicKey;
+    action: ProposalAction;
+    approvals: PublicKey[];
+    executed: boolean;
+};
+
+export type Invitation = {
+    sessionId: BN;
+    invitationId: number;
+    tokenHash: string;
+    expiresAt: Date | null;
+    maxUses: number;
+    remainingUses: number;
+    revoked: boolean;
+    label: string;
+    creator: PublicKey;
+    role: MemberRole | null;
+};
+
+export type InvitationOptions = {
+    label?: string;
+    role?: MemberRole;
+    expiresAt?: Date;
+    maxUses?: number;
 };
 
 export type MemberBalance = {
@@ -110,6 +530,8 @@ export type MemberTransfer = {
 export type MemberRefund = {
     to: PublicKey;
     amount: number;
+    // index of settlement plan transfer fulfilled by this refund
+    settlementIndex?: number;
 };
 
 export type Balance = {
@@ -121,15 +543,20 @@ export type Balance = {
 
 export const MISSING_INVITATION_HASH = new Array(32).fill(0).toString();
 
+export const DEFAULT_CURRENCY = 'USD';
+export const DEFAULT_DECIMALS = 2;
+export const SOL_USD_PRICE_FEED_ID = '0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d';
+
+// Pyth push oracle account holding SOL/USD price updates
+export const SOL_USD_PRICE_UPDATE_ACCOUNT = new PublicKey('7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaYKvRgxASKn');
+export const DEFAULT_MAX_SLIPPAGE_BPS = 100;
+
 export class SolidrClient extends AbstractSolanaClient<Solidr> {
     public readonly globalAccountPubkey: PublicKey;
 
-    private readonly hermesConnection: HermesClient;
-
     constructor(program: Program<Solidr>, options?: SendOptions, wrapFn?: ProgramInstructionWrapper<Solidr>) {
         super(program, options, wrapFn);
         this.globalAccountPubkey = PublicKey.findProgramAddressSync([Buffer.from('global')], program.programId)[0];
-        this.hermesConnection = new HermesClient('https://hermes.pyth.network');
     }
 
     public async initGlobal(payer: Wallet) {
@@ -157,19 +584,28 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         return globalAccountPubkey;
     }
 
-    public async openSession(admin: Wallet, name: string, description: string, memberName: string): Promise<ITransactionResult<{ sessionId: string }>> {
+    public async openSession(
+        admin: Wallet,
+        name: string,
+        description: string,
+        memberName: string,
+        currency: string = DEFAULT_CURRENCY,
+        decimals: number = DEFAULT_DECIMALS,
+    ): Promise<ITransactionResult<{ sessionId: string }>> {
         return this.wrapFn(async () => {
             const sessionId = await this._getNextSessionId();
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
             const memberAccountAddress = this.findSessionMemberAccountAddress(sessionId, admin.publicKey);
+            const balanceAccountAddress = this.findBalanceAccountAddress(sessionId, admin.publicKey);
 
             const tx = await this.program.methods
-                .openSession(name, description, memberName)
+                .openSession(name, description, memberName, currency, decimals)
                 .accountsPartial({
                     global: this.globalAccountPubkey,
                     admin: admin.publicKey,
                     session: sessionAccountPubkey,
                     member: memberAccountAddress,
+                    balance: balanceAccountAddress,
                 })
                 .transaction();
 
@@ -196,6 +632,7 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                 .accountsPartial({
                     admin: admin.publicKey,
                     session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
                 })
                 .transaction();
 
@@ -208,12 +645,137 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
     public async closeSession(admin: Wallet, sessionId: BN): Promise<ITransactionResult> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const proposals = await this.listSessionProposals(sessionId);
 
             const tx = await this.program.methods
                 .closeSession()
                 .accountsPartial({
                     admin: admin.publicKey,
                     session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.CloseSession }),
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async proposeAdmin(admin: Wallet, sessionId: BN, newAdmin: PublicKey): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+
+            const tx = await this.program.methods
+                .proposeAdmin(newAdmin)
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    newAdminMember: this.findSessionMemberAccountAddress(sessionId, newAdmin),
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async acceptAdmin(signer: Wallet, sessionId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+
+            const tx = await this.program.methods
+                .acceptAdmin()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    previousAdminMember: this.findSessionMemberAccountAddress(sessionId, session.admin),
+                    newAdminMember: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async reopenSession(admin: Wallet, sessionId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const proposals = await this.listSessionProposals(sessionId);
+
+            const tx = await this.program.methods
+                .reopenSession()
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.ReopenSession }),
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async setReopenWindow(admin: Wallet, sessionId: BN, reopenWindow: number): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+
+            const tx = await this.program.methods
+                .setReopenWindow(new BN(reopenWindow))
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async setSessionMint(admin: Wallet, sessionId: BN, mint: PublicKey | null, currency: string = DEFAULT_CURRENCY): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+
+            const tx = await this.program.methods
+                .setSessionMint(currency)
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    mint,
+                    tokenProgram: mint ? await this._getTokenProgramId(mint) : null,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async setSessionPriceFeed(admin: Wallet, sessionId: BN, priceFeedId: string): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const proposals = await this.listSessionProposals(sessionId);
+
+            const tx = await this.program.methods
+                .setSessionPriceFeed([...Buffer.from(priceFeedId.replace(/^0x/, ''), 'hex')])
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.SetPriceFeed, priceFeedId }),
                 })
                 .transaction();
 
@@ -226,6 +788,9 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
     public async deleteSession(admin: Wallet, sessionId: BN): Promise<ITransactionResult> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            // each deletion requires its own approved proposal when session has an approval threshold
+            const proposals = await this.listSessionProposals(sessionId);
+            const { budgeted } = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
 
             let instructions: TransactionInstruction[] = [];
             const expenses = await this.listSessionExpenses(sessionId);
@@ -236,7 +801,9 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                         owner: admin.publicKey,
                         session: sessionAccountPubkey,
                         expense: this.findExpenseAccountAddress(sessionId, new BN(expense.expenseId)),
+                        budget: this._findBudgetAccountAddress(sessionId, budgeted),
                     })
+                    .remainingAccounts(this._balanceAccountMetas(sessionId, [expense.owner, expense.payer, ...expense.participants]))
                     .instruction();
                 instructions.push(instruction);
             }
@@ -247,19 +814,28 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                     .accountsPartial({
                         admin: admin.publicKey,
                         session: sessionAccountPubkey,
+                        signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
                         refund: this.findRefundAccountAddress(sessionId, new BN(refund.refundId)),
+                        senderBalance: this.findBalanceAccountAddress(sessionId, refund.from),
+                        receiverBalance: this.findBalanceAccountAddress(sessionId, refund.to),
+                        proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.DeleteRefund, refundId: refund.refundId }),
+                        settlement: refund.settlementIndex !== null ? this.findSettlementAccountAddress(sessionId) : null,
                     })
                     .instruction();
                 instructions.push(instruction);
             }
-            const members = await this.listSessionMembers(sessionId);
+            // signer's member and balance accounts are closed by deleteSession itself
+            const members = (await this.listSessionMembers(sessionId)).filter((m) => !m.addr.equals(admin.publicKey));
             for (const member of members) {
                 const instruction = await this.program.methods
                     .deleteSessionMember()
                     .accountsPartial({
                         admin: admin.publicKey,
                         session: sessionAccountPubkey,
+                        signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
                         member: this.findSessionMemberAccountAddress(sessionId, member.addr),
+                        balance: this.findBalanceAccountAddress(sessionId, member.addr),
+                        proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.DeleteMember, addr: member.addr }),
                     })
                     .instruction();
                 instructions.push(instruction);
@@ -269,6 +845,10 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                 .accountsPartial({
                     admin: admin.publicKey,
                     session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    signerBalance: this.findBalanceAccountAddress(sessionId, admin.publicKey),
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.DeleteSession }),
+                    vault: this.findVaultAccountAddress(sessionId),
                 })
                 .preInstructions(instructions)
                 .transaction();
@@ -279,29 +859,128 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         });
     }
 
-    public computeBalance(sessionMembers: SessionMember[], expenses: Expense[], refunds: Refund[]): Balance {
-        const members = sessionMembers.reduce(
-            (members, member) => {
-                members[member.addr.toString()] = { owner: member.addr, balance: 0, totalCost: 0 };
-                return members;
-            },
-            {} as { [key: string]: MemberBalance },
-        );
+    public async setApprovalThreshold(admin: Wallet, sessionId: BN, threshold: number): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const proposals = await this.listSessionProposals(sessionId);
 
-        let totalExpenses = 0;
-        let totalRefunds = 0;
+            const tx = await this.program.methods
+                .setApprovalThreshold(threshold)
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.SetApprovalThreshold, threshold }),
+                })
+                .transaction();
 
-        // 1. Compute total of expenses and individual costs for each member
-        for (const expense of expenses) {
-            members[expense.owner.toString()].balance += expense.amount;
-            totalExpenses += expense.amount;
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
 
-            const shareAmount = expense.amount / expense.participants.length;
-            for (const participant of expense.participants) {
+    public async createProposal(proposer: Wallet, sessionId: BN, action: ProposalAction): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const proposalAccountPubkey = this.findProposalAccountAddress(sessionId, new BN(session.proposalsCount));
+
+            const tx = await this.program.methods
+                .createProposal(this._toInternalProposalAction(action))
+                .accountsPartial({
+                    proposer: proposer.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, proposer.publicKey),
+                    proposal: proposalAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(proposer, tx, {
+                sessionAccountPubkey,
+                proposalAccountPubkey,
+            });
+        });
+    }
+
+    public async approveProposal(signer: Wallet, sessionId: BN, proposalId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const proposalAccountPubkey = this.findProposalAccountAddress(sessionId, proposalId);
+
+            const tx = await this.program.methods
+                .approveProposal()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                    proposal: proposalAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                proposalAccountPubkey,
+            });
+        });
+    }
+
+    public async getProposal(proposalAccountPubkey: PublicKey): Promise<Proposal> {
+        return this.wrapFn(async () => {
+            const proposal = await this.program.account.proposalAccount.fetch(proposalAccountPubkey);
+            return this._mapProposal(proposal);
+        });
+    }
+
+    public async listSessionProposals(sessionId: BN): Promise<Proposal[]> {
+        return this.wrapFn(async () => {
+            const proposalAccountDiscriminator = Buffer.from(sha256.digest('account:ProposalAccount')).subarray(0, 8);
+            const accounts = await this.connection.getProgramAccounts(this.program.programId, {
+                dataSlice: { offset: 0, length: 0 },
+                filters: [
+                    { memcmp: { offset: 0, bytes: bs58.encode(proposalAccountDiscriminator) } }, // Ensure it's a ProposalAccount account.
+                    { memcmp: { offset: 8, bytes: bs58.encode(sessionId.toArrayLike(Buffer, 'le', 8)) } },
+                ],
+            });
+            const proposals = await this.getPage<InternalProposal>(
+                this.program.account.proposalAccount,
+                accounts.map(({ pubkey }) => pubkey),
+            );
+            return _.sortBy(proposals.map(this._mapProposal), (proposal) => proposal.proposalId);
+        });
+    }
+
+    public findProposalAccountAddress(sessionId: BN, proposalId: BN): PublicKey {
+        const [proposalAccountPubkey] = PublicKey.findProgramAddressSync(
+            [Buffer.from('proposal'), sessionId.toArrayLike(Buffer, 'le', 8), proposalId.toArrayLike(Buffer, 'le', 2)],
+            this.program.programId,
+        );
+        return proposalAccountPubkey;
+    }
+
+    public computeBalance(sessionMembers: SessionMember[], expenses: Expense[], refunds: Refund[]): Balance {
+        const members = sessionMembers.reduce(
+            (members, member) => {
+                members[member.addr.toString()] = { owner: member.addr, balance: 0, totalCost: 0 };
+                return members;
+            },
+            {} as { [key: string]: MemberBalance },
+        );
+
+        let totalExpenses = 0;
+        let totalRefunds = 0;
+
+        // 1. Compute total of expenses and individual costs for each member
+        for (const expense of expenses) {
+            if (expense.status !== ExpenseStatus.Approved) continue;
+            members[expense.payer.toString()].balance += expense.amount;
+            totalExpenses += expense.amount;
+
+            const shareAmounts = this._splitExpense(expense);
+            expense.participants.forEach((participant, i) => {
                 const participantKey = participant.toString();
-                members[participantKey].balance -= shareAmount;
-                members[participantKey].totalCost += shareAmount;
-            }
+                members[participantKey].balance -= shareAmounts[i];
+                members[participantKey].totalCost += shareAmounts[i];
+            });
         }
 
         // 2. Apply refunds
@@ -354,9 +1033,25 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         return Math.round(amount * 100) / 100;
     };
 
-    public async generateSessionLink(
+    private _splitExpense(expense: Expense): number[] {
+        switch (expense.splitMode) {
+            case SplitMode.Exact:
+                return expense.shares;
+            case SplitMode.Percentage:
+                return expense.shares.map((share) => (expense.amount * share) / 100);
+            case SplitMode.Weighted: {
+                const totalWeight = _.sum(expense.shares);
+                return expense.shares.map((share) => (totalWeight > 0 ? (expense.amount * share) / totalWeight : 0));
+            }
+            default:
+                return expense.participants.map(() => expense.amount / expense.participants.length);
+        }
+    }
+
+    public async createInvitation(
         admin: Wallet,
         sessionId: BN,
+        options: InvitationOptions = {},
     ): Promise<
         ITransactionResult<{
             token: string;
@@ -365,25 +1060,102 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
     > {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const invitationAccountPubkey = this.findInvitationAccountAddress(sessionId, new BN(session.invitationsCount));
 
             const { token, hash } = generateSessionLinkTokenData(sessionId.toString(), admin.publicKey);
+            const expiresAt = options.expiresAt ? new BN(Math.floor(options.expiresAt.getTime() / 1000)) : new BN(0);
+
+            const tx = await this.program.methods
+                .createInvitation(options.label ?? '', [...hash], expiresAt, options.maxUses ?? 1, options.role ? this._toInternalMemberRole(options.role) : null)
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    invitation: invitationAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, { sessionAccountPubkey, invitationAccountPubkey }, { token, hash });
+        });
+    }
+
+    public async revokeInvitation(admin: Wallet, sessionId: BN, invitationId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const invitationAccountPubkey = this.findInvitationAccountAddress(sessionId, invitationId);
 
             const tx = await this.program.methods
-                .setSessionTokenHash([...hash])
+                .revokeInvitation()
                 .accountsPartial({
                     admin: admin.publicKey,
                     session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    invitation: invitationAccountPubkey,
                 })
                 .transaction();
 
-            return this.signAndSendTransaction(admin, tx, { sessionAccountPubkey }, { token, hash });
+            return this.signAndSendTransaction(admin, tx, {
+                invitationAccountPubkey,
+            });
+        });
+    }
+
+    public async getInvitation(invitationAccountPubkey: PublicKey): Promise<Invitation> {
+        return this.wrapFn(async () => {
+            const invitation = await this.program.account.invitationAccount.fetch(invitationAccountPubkey);
+            return this._mapInvitation(invitation);
+        });
+    }
+
+    public async listSessionInvitations(sessionId: BN): Promise<Invitation[]> {
+        return this.wrapFn(async () => {
+            const invitationAccountDiscriminator = Buffer.from(sha256.digest('account:InvitationAccount')).subarray(0, 8);
+            const accounts = await this.connection.getProgramAccounts(this.program.programId, {
+                dataSlice: { offset: 0, length: 0 },
+                filters: [
+                    { memcmp: { offset: 0, bytes: bs58.encode(invitationAccountDiscriminator) } }, // Ensure it's a InvitationAccount account.
+                    { memcmp: { offset: 8, bytes: bs58.encode(sessionId.toArrayLike(Buffer, 'le', 8)) } },
+                ],
+            });
+            const invitations = await this.getPage<InternalInvitation>(
+                this.program.account.invitationAccount,
+                accounts.map(({ pubkey }) => pubkey),
+            );
+            return _.sortBy(invitations.map(this._mapInvitation), (invitation) => invitation.invitationId);
+        });
+    }
+
+    private async _findInvitationAccountAddressByToken(sessionId: BN, token: string): Promise<PublicKey> {
+        const invitationAccountDiscriminator = Buffer.from(sha256.digest('account:InvitationAccount')).subarray(0, 8);
+        const accounts = await this.connection.getProgramAccounts(this.program.programId, {
+            dataSlice: { offset: 0, length: 0 },
+            filters: [
+                { memcmp: { offset: 0, bytes: bs58.encode(invitationAccountDiscriminator) } }, // Ensure it's an InvitationAccount account.
+                { memcmp: { offset: 8, bytes: bs58.encode(sessionId.toArrayLike(Buffer, 'le', 8)) } },
+                { memcmp: { offset: 8 + 8 + 2, bytes: bs58.encode(hashToken(token)) } },
+            ],
         });
+        if (accounts.length === 0) throw new Error('Invitation not found');
+        return accounts[0].pubkey;
+    }
+
+    public findInvitationAccountAddress(sessionId: BN, invitationId: BN): PublicKey {
+        const [invitationAccountPubkey] = PublicKey.findProgramAddressSync(
+            [Buffer.from('invitation'), sessionId.toArrayLike(Buffer, 'le', 8), invitationId.toArrayLike(Buffer, 'le', 2)],
+            this.program.programId,
+        );
+        return invitationAccountPubkey;
     }
 
-    public async joinSessionAsMember(payer: Wallet, sessionId: BN, name: string, tokenBase64: string): Promise<ITransactionResult> {
+    public async joinSessionAsMember(payer: Wallet, sessionId: BN, name: string, tokenBase64: string, invitationId?: BN): Promise<ITransactionResult> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
             const memberAccountAddress = this.findSessionMemberAccountAddress(sessionId, payer.publicKey);
+            // share links only carry the token, invitation is then looked up from its hash
+            const invitationAccountPubkey = invitationId
+                ? this.findInvitationAccountAddress(sessionId, invitationId)
+                : await this._findInvitationAccountAddressByToken(sessionId, tokenBase64);
 
             const tx = await this.program.methods
                 .joinSessionAsMember(name, tokenBase64)
@@ -391,6 +1163,8 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                     signer: payer.publicKey,
                     session: sessionAccountPubkey,
                     member: memberAccountAddress,
+                    balance: this.findBalanceAccountAddress(sessionId, payer.publicKey),
+                    invitation: invitationAccountPubkey,
                 })
                 .transaction();
 
@@ -411,7 +1185,9 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                 .accountsPartial({
                     admin: payer.publicKey,
                     session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, payer.publicKey),
                     member: memberAccountPubkey,
+                    balance: this.findBalanceAccountAddress(sessionId, addr),
                 })
                 .transaction();
 
@@ -445,13 +1221,17 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
             const memberAccountAddress = this.findSessionMemberAccountAddress(sessionId, addr);
+            const proposals = await this.listSessionProposals(sessionId);
 
             const tx = await this.program.methods
                 .deleteSessionMember()
                 .accountsPartial({
                     admin: payer.publicKey,
                     session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, payer.publicKey),
                     member: memberAccountAddress,
+                    balance: this.findBalanceAccountAddress(sessionId, addr),
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.DeleteMember, addr }),
                 })
                 .transaction();
 
@@ -461,6 +1241,112 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         });
     }
 
+    public async removeSessionMember(admin: Wallet, sessionId: BN, addr: PublicKey, newOwner?: PublicKey): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const memberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, addr);
+            const proposals = await this.listSessionProposals(sessionId);
+
+            // every expense involving member is re-split or reassigned, along with balances of other involved members
+            const expenses = (await this.listSessionExpenses(sessionId)).filter(
+                (expense) => expense.owner.equals(addr) || expense.payer.equals(addr) || expense.participants.some((p) => p.equals(addr)),
+            );
+            const involvedMembers = _.flatMap(expenses, (expense) => [expense.owner, expense.payer, ...expense.participants]).filter((member) => !member.equals(addr));
+
+            const tx = await this.program.methods
+                .removeSessionMember(expenses.length)
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    member: memberAccountPubkey,
+                    balance: this.findBalanceAccountAddress(sessionId, addr),
+                    newOwnerMember: newOwner ? this.findSessionMemberAccountAddress(sessionId, newOwner) : null,
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.DeleteMember, addr }),
+                })
+                .remainingAccounts([
+                    ...expenses.map((expense) => ({
+                        pubkey: this.findExpenseAccountAddress(sessionId, new BN(expense.expenseId)),
+                        isSigner: false,
+                        isWritable: true,
+                    })),
+                    ...this._balanceAccountMetas(sessionId, newOwner ? [...involvedMembers, newOwner] : involvedMembers),
+                ])
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+                memberAccountPubkey,
+            });
+        });
+    }
+
+    public async leaveSession(member: Wallet, sessionId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const memberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, member.publicKey);
+
+            const tx = await this.program.methods
+                .leaveSession()
+                .accountsPartial({
+                    signer: member.publicKey,
+                    session: sessionAccountPubkey,
+                    member: memberAccountPubkey,
+                    balance: this.findBalanceAccountAddress(sessionId, member.publicKey),
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(member, tx, {
+                sessionAccountPubkey,
+                memberAccountPubkey,
+            });
+        });
+    }
+
+    public async grantRole(admin: Wallet, sessionId: BN, addr: PublicKey, role: MemberRole): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const memberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, addr);
+
+            const tx = await this.program.methods
+                .grantRole(this._toInternalMemberRole(role))
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    member: memberAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+                memberAccountPubkey,
+            });
+        });
+    }
+
+    public async revokeRole(admin: Wallet, sessionId: BN, addr: PublicKey): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const memberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, addr);
+
+            const tx = await this.program.methods
+                .revokeRole()
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    member: memberAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+                memberAccountPubkey,
+            });
+        });
+    }
+
     public async getSession(sessionAccountPubkey: PublicKey): Promise<Session | undefined> {
         return this.wrapFn(async () => {
             try {
@@ -550,28 +1436,80 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         return sessionMemberAccountPubkey;
     }
 
-    public async addExpense(member: Wallet, sessionId: BN, name: string, amount: number, participants?: PublicKey[]): Promise<ITransactionResult> {
+    public findBalanceAccountAddress(sessionId: BN, memberPubkey: PublicKey): PublicKey {
+        const [balanceAccountPubkey] = PublicKey.findProgramAddressSync(
+            [Buffer.from('balance'), sessionId.toArrayLike(Buffer, 'le', 8), memberPubkey.toBuffer()],
+            this.program.programId,
+        );
+        return balanceAccountPubkey;
+    }
+
+    public findVaultAccountAddress(sessionId: BN): PublicKey {
+        const [vaultAccountPubkey] = PublicKey.findProgramAddressSync([Buffer.from('vault'), sessionId.toArrayLike(Buffer, 'le', 8)], this.program.programId);
+        return vaultAccountPubkey;
+    }
+
+    public async getMemberBalance(sessionId: BN, memberPubkey: PublicKey): Promise<MemberBalanceAccount> {
+        return this.wrapFn(async () => {
+            const balance = await this.program.account.balanceAccount.fetch(this.findBalanceAccountAddress(sessionId, memberPubkey));
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(sessionId));
+            return this._mapMemberBalance(balance, decimals);
+        });
+    }
+
+    public async listSessionBalances(sessionId: BN): Promise<MemberBalanceAccount[]> {
+        return this.wrapFn(async () => {
+            const balanceAccountDiscriminator = Buffer.from(sha256.digest('account:BalanceAccount')).subarray(0, 8);
+            const accounts = await this.connection.getProgramAccounts(this.program.programId, {
+                dataSlice: { offset: 0, length: 0 },
+                filters: [
+                    { memcmp: { offset: 0, bytes: bs58.encode(balanceAccountDiscriminator) } }, // Ensure it's a BalanceAccount account.
+                    { memcmp: { offset: 8, bytes: bs58.encode(sessionId.toArrayLike(Buffer, 'le', 8)) } },
+                ],
+            });
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(sessionId));
+            return (
+                await this.getPage<InternalMemberBalanceAccount>(
+                    this.program.account.balanceAccount,
+                    accounts.map(({ pubkey }) => pubkey),
+                )
+            ).map((balance) => this._mapMemberBalance(balance, decimals));
+        });
+    }
+
+    public async addExpense(member: Wallet, sessionId: BN, name: string, amount: number, participants?: PublicKey[], options?: ExpenseOptions): Promise<ITransactionResult> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
             const memberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, member.publicKey);
-            const expenseId = await this._getNextExpenseId(sessionAccountPubkey);
-            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, new BN(session.expensesCount));
+            const splitMode = options?.splitMode ?? SplitMode.Equal;
+            const payer = options?.payer ?? member.publicKey;
 
             const tx = await this.program.methods
-                .addExpense(name, amount, participants || [])
+                .addExpense(
+                    name,
+                    this._toMinorUnits(amount, session.decimals),
+                    this._toInternalSplitMode(splitMode),
+                    this._toInternalParticipants(participants || [], splitMode, options?.shares, session.decimals),
+                    options?.original ? this._toInternalOriginal(options.original) : null,
+                    this._toInternalExpenseCategory(options?.category ?? ExpenseCategory.Other),
+                    options?.tags ?? [],
+                    options?.receipt ? this._toInternalReceipt(options.receipt) : null,
+                    options?.payer ?? null,
+                    options?.excludePayer ?? false,
+                )
                 .accountsPartial({
                     owner: member.publicKey,
                     member: memberAccountPubkey,
                     session: sessionAccountPubkey,
                     expense: expenseAccountPubkey,
+                    budget: this._findBudgetAccountAddress(sessionId, session.budgeted),
                 })
-                .remainingAccounts(
-                    _.map(participants, (p) => ({
-                        pubkey: this.findSessionMemberAccountAddress(sessionId, p),
-                        isSigner: false,
-                        isWritable: false,
-                    })),
-                )
+                .remainingAccounts([
+                    ...this._memberAccountMetas(sessionId, payer.equals(member.publicKey) ? participants || [] : [...(participants || []), payer]),
+                    ...this._balanceAccountMetas(sessionId, [member.publicKey, payer, ...(participants || [])]),
+                ])
                 .transaction();
 
             return this.signAndSendTransaction(member, tx, {
@@ -582,24 +1520,54 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         });
     }
 
-    public async updateExpense(member: Wallet, sessionId: BN, expenseId: BN, name: string, amount: number, participants: PublicKey[]): Promise<ITransactionResult> {
+    public async updateExpense(
+        member: Wallet,
+        sessionId: BN,
+        expenseId: BN,
+        name: string,
+        amount: number,
+        participants: PublicKey[],
+        shares?: number[],
+        // original amount is kept when undefined, and removed when null
+        original?: OriginalAmount | null,
+        // category and tags are kept when undefined
+        classification?: Pick<ExpenseOptions, 'category' | 'tags'>,
+    ): Promise<ITransactionResult> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
             const memberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, member.publicKey);
             const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
 
+            const { decimals, budgeted } = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const expense = await this.program.account.expenseAccount.fetch(expenseAccountPubkey);
+            const oldParticipants = expense.participants.map((p) => p.member);
+            // payer is never removed from participants by an update
+            const newParticipants = _.uniqBy([...participants, expense.payer], (pk) => pk.toString());
+            const participantToAdd = _.differenceBy(participants, oldParticipants, (pk) => pk.toString());
+            const participantToRemove = _.differenceBy(oldParticipants, newParticipants, (pk) => pk.toString());
+            const balanceAccounts = this._balanceAccountMetas(sessionId, [...this._involvedMembers(expense), ...participantToAdd]);
+            const splitMode = this._mapSplitMode(expense.splitMode);
+            // when shares are given, shares of existing participants are updated as well
+            const participantsToUpsert = shares ? participants : participantToAdd;
+
             const tx = await this.program.methods
-                .updateExpense(name, amount)
+                .updateExpense(
+                    name,
+                    this._toMinorUnits(amount, decimals),
+                    participantsToUpsert.length > 0 ? this._toInternalParticipants(participantsToUpsert, splitMode, shares, decimals) : null,
+                    original === undefined ? expense.original : original && this._toInternalOriginal(original),
+                    classification?.category ? this._toInternalExpenseCategory(classification.category) : expense.category,
+                    classification?.tags ?? expense.tags,
+                )
                 .accountsPartial({
                     owner: member.publicKey,
                     session: sessionAccountPubkey,
                     expense: expenseAccountPubkey,
+                    budget: this._findBudgetAccountAddress(sessionId, budgeted),
                 })
+                .remainingAccounts([...this._memberAccountMetas(sessionId, participantToAdd), ...balanceAccounts])
                 .transaction();
 
-            const oldParticipants = (await this.getExpense(expenseAccountPubkey)).participants;
-            const newParticipants = _.uniqBy([...participants, member.publicKey], (pk) => pk.toString());
-            let participantToRemove = _.differenceBy(oldParticipants, newParticipants, (pk) => pk.toString());
             if (participantToRemove.length > 0) {
                 tx.add(
                     await this.program.methods
@@ -609,33 +1577,7 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                             expense: expenseAccountPubkey,
                             session: sessionAccountPubkey,
                         })
-                        .remainingAccounts(
-                            _.map(participantToRemove, (p) => ({
-                                pubkey: this.findSessionMemberAccountAddress(sessionId, p),
-                                isSigner: false,
-                                isWritable: false,
-                            })),
-                        )
-                        .instruction(),
-                );
-            }
-            let participantToAdd = _.differenceBy(participants, oldParticipants, (pk) => pk.toString());
-            if (participantToAdd.length > 0) {
-                tx.add(
-                    await this.program.methods
-                        .addExpenseParticipants(participantToAdd)
-                        .accountsPartial({
-                            owner: member.publicKey,
-                            expense: expenseAccountPubkey,
-                            session: sessionAccountPubkey,
-                        })
-                        .remainingAccounts(
-                            _.map(participantToAdd, (p) => ({
-                                pubkey: this.findSessionMemberAccountAddress(sessionId, p),
-                                isSigner: false,
-                                isWritable: false,
-                            })),
-                        )
+                        .remainingAccounts(balanceAccounts)
                         .instruction(),
                 );
             }
@@ -653,6 +1595,8 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
             const memberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, member.publicKey);
             const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+            const expense = await this.program.account.expenseAccount.fetchNullable(expenseAccountPubkey);
+            const session = await this.program.account.sessionAccount.fetchNullable(sessionAccountPubkey);
 
             const tx = await this.program.methods
                 .deleteExpense()
@@ -660,7 +1604,9 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                     owner: member.publicKey,
                     session: sessionAccountPubkey,
                     expense: expenseAccountPubkey,
+                    budget: this._findBudgetAccountAddress(sessionId, session?.budgeted),
                 })
+                .remainingAccounts(expense ? this._balanceAccountMetas(sessionId, this._involvedMembers(expense)) : [])
                 .transaction();
 
             return this.signAndSendTransaction(member, tx, {
@@ -699,7 +1645,10 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                 .sort((a, b) => +(a.date > b.date) || -(a.date < b.date))
                 .map((account) => account.pubkey);
 
-            return (await this.getPage<InternalExpense>(this.program.account.expenseAccount, addresses, paginationOptions?.page, paginationOptions?.perPage)).map(this._mapExpense);
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(sessionId));
+            return (await this.getPage<InternalExpense>(this.program.account.expenseAccount, addresses, paginationOptions?.page, paginationOptions?.perPage)).map((expense) =>
+                this._mapExpense(expense, decimals),
+            );
         });
     }
 
@@ -714,28 +1663,29 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
     public async getExpense(expenseAccountPubkey: PublicKey): Promise<Expense> {
         return this.wrapFn(async () => {
             const expense = await this.program.account.expenseAccount.fetch(expenseAccountPubkey);
-            return this._mapExpense(expense);
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(expense.sessionId));
+            return this._mapExpense(expense, decimals);
         });
     }
 
-    public async addExpenseParticipants(owner: Wallet, sessionId: BN, expenseId: BN, participants: PublicKey[]): Promise<ITransactionResult> {
+    public async addExpenseParticipants(owner: Wallet, sessionId: BN, expenseId: BN, participants: PublicKey[], shares?: number[]): Promise<ITransactionResult> {
         const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
         const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+        const expense = await this.program.account.expenseAccount.fetchNullable(expenseAccountPubkey);
+        const session = await this.program.account.sessionAccount.fetchNullable(sessionAccountPubkey);
+        const splitMode = expense ? this._mapSplitMode(expense.splitMode) : SplitMode.Equal;
 
         const tx = await this.program.methods
-            .addExpenseParticipants(participants)
+            .addExpenseParticipants(this._toInternalParticipants(participants, splitMode, shares, session?.decimals ?? DEFAULT_DECIMALS))
             .accountsPartial({
                 owner: owner.publicKey,
                 expense: expenseAccountPubkey,
                 session: sessionAccountPubkey,
             })
-            .remainingAccounts(
-                _.map(participants, (p) => ({
-                    pubkey: this.findSessionMemberAccountAddress(sessionId, p),
-                    isSigner: false,
-                    isWritable: false,
-                })),
-            )
+            .remainingAccounts([
+                ...this._memberAccountMetas(sessionId, participants),
+                ...this._balanceAccountMetas(sessionId, [...(expense ? this._involvedMembers(expense) : []), ...participants]),
+            ])
             .transaction();
 
         return this.signAndSendTransaction(owner, tx, {
@@ -747,6 +1697,7 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
     public async removeExpenseParticipants(owner: Wallet, sessionId: BN, expenseId: BN, participants: PublicKey[]): Promise<ITransactionResult> {
         const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
         const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+        const expense = await this.program.account.expenseAccount.fetchNullable(expenseAccountPubkey);
 
         const tx = await this.program.methods
             .removeExpenseParticipants(participants)
@@ -755,13 +1706,7 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                 expense: expenseAccountPubkey,
                 session: sessionAccountPubkey,
             })
-            .remainingAccounts(
-                _.map(participants, (p) => ({
-                    pubkey: this.findSessionMemberAccountAddress(sessionId, p),
-                    isSigner: false,
-                    isWritable: false,
-                })),
-            )
+            .remainingAccounts(expense ? this._balanceAccountMetas(sessionId, this._involvedMembers(expense)) : [])
             .transaction();
 
         return this.signAndSendTransaction(owner, tx, {
@@ -770,37 +1715,433 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         });
     }
 
-    public async sendRefunds(payer: Wallet, sessionId: BN, refundsToSend: MemberRefund[]): Promise<ITransactionResult> {
+    public async setBudget(
+        admin: Wallet,
+        sessionId: BN,
+        // budget line is removed when null
+        limit: number | null,
+        // session total is budgeted when no category is given
+        options?: { category?: ExpenseCategory; hardCap?: boolean },
+    ): Promise<ITransactionResult> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
-            const fromMemberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, payer.publicKey);
+            const budgetAccountPubkey = this.findBudgetAccountAddress(sessionId);
+            const { decimals } = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
 
-            let refundId = await this._getNextRefundId(sessionAccountPubkey);
+            const tx = await this.program.methods
+                .setBudget(
+                    options?.category ? this._toInternalExpenseCategory(options.category) : null,
+                    limit === null ? null : this._toMinorUnits(limit, decimals),
+                    options?.hardCap ?? false,
+                )
+                .accountsPartial({
+                    signer: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                    budget: budgetAccountPubkey,
+                })
+                .transaction();
 
-            const priceData = await this.getPriceData();
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+                budgetAccountPubkey,
+            });
+        });
+    }
 
-            let instructions: TransactionInstruction[] = [];
-            let refundsAccountPubkeys: NodeJS.Dict<PublicKey> = {};
-            for (const transfer of refundsToSend) {
-                const toMemberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, transfer.to);
-                const refundAccountPubkey = this.findRefundAccountAddress(sessionId, refundId);
-                refundId = new BN(refundId + 1);
+    public async getBudget(budgetAccountPubkey: PublicKey): Promise<Budget> {
+        return this.wrapFn(async () => {
+            const budget = await this.program.account.budgetAccount.fetch(budgetAccountPubkey);
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(budget.sessionId));
+            return this._mapBudget(budget, decimals);
+        });
+    }
 
-                const amountInLamports = this.computePriceInLamportsFromAmount(transfer.amount, priceData);
-                const instruction = await this.program.methods
-                    .addRefund(transfer.amount, amountInLamports)
-                    .accountsPartial({
-                        fromAddr: payer.publicKey,
-                        sender: fromMemberAccountPubkey,
-                        toAddr: transfer.to,
-                        receiver: toMemberAccountPubkey,
-                        session: sessionAccountPubkey,
-                        refund: refundAccountPubkey,
-                    })
-                    .instruction();
-                refundsAccountPubkeys = { ...refundsAccountPubkeys, refundAccountPubkey };
-                instructions.push(instruction);
-            }
+    public findBudgetAccountAddress(sessionId: BN): PublicKey {
+        const [budgetAccountPubkey] = PublicKey.findProgramAddressSync([Buffer.from('budget'), sessionId.toArrayLike(Buffer, 'le', 8)], this.program.programId);
+        return budgetAccountPubkey;
+    }
+
+    public async createRecurringExpense(
+        member: Wallet,
+        sessionId: BN,
+        name: string,
+        amount: number,
+        interval: number,
+        firstDue: Date,
+        participants?: PublicKey[],
+        options?: Omit<ExpenseOptions, 'original'>,
+    ): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const recurringAccountPubkey = this.findRecurringExpenseAccountAddress(sessionId, new BN(session.recurringCount));
+            const splitMode = options?.splitMode ?? SplitMode.Equal;
+
+            const tx = await this.program.methods
+                .createRecurringExpense(
+                    name,
+                    this._toMinorUnits(amount, session.decimals),
+                    this._toInternalSplitMode(splitMode),
+                    this._toInternalParticipants(participants || [], splitMode, options?.shares, session.decimals),
+                    new BN(interval),
+                    new BN(Math.floor(firstDue.getTime() / 1000)),
+                    this._toInternalExpenseCategory(options?.category ?? ExpenseCategory.Other),
+                    options?.tags ?? [],
+                )
+                .accountsPartial({
+                    owner: member.publicKey,
+                    session: sessionAccountPubkey,
+                    member: this.findSessionMemberAccountAddress(sessionId, member.publicKey),
+                    recurring: recurringAccountPubkey,
+                })
+                .remainingAccounts(this._memberAccountMetas(sessionId, participants || []))
+                .transaction();
+
+            return this.signAndSendTransaction(member, tx, {
+                sessionAccountPubkey,
+                recurringAccountPubkey,
+            });
+        });
+    }
+
+    public async materializeRecurringExpense(signer: Wallet, sessionId: BN, recurringId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const recurringAccountPubkey = this.findRecurringExpenseAccountAddress(sessionId, recurringId);
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const recurring = await this.program.account.recurringExpenseAccount.fetch(recurringAccountPubkey);
+            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, new BN(session.expensesCount));
+
+            const tx = await this.program.methods
+                .materializeRecurringExpense()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    recurring: recurringAccountPubkey,
+                    expense: expenseAccountPubkey,
+                    budget: this._findBudgetAccountAddress(sessionId, session.budgeted),
+                })
+                .remainingAccounts(this._balanceAccountMetas(sessionId, [recurring.owner, ...recurring.participants.map((p) => p.member)]))
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                recurringAccountPubkey,
+                expenseAccountPubkey,
+            });
+        });
+    }
+
+    public async pauseRecurringExpense(signer: Wallet, sessionId: BN, recurringId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const recurringAccountPubkey = this.findRecurringExpenseAccountAddress(sessionId, recurringId);
+
+            const tx = await this.program.methods
+                .pauseRecurringExpense()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: this.findSessionAccountAddress(sessionId),
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                    recurring: recurringAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                recurringAccountPubkey,
+            });
+        });
+    }
+
+    public async resumeRecurringExpense(signer: Wallet, sessionId: BN, recurringId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const recurringAccountPubkey = this.findRecurringExpenseAccountAddress(sessionId, recurringId);
+
+            const tx = await this.program.methods
+                .resumeRecurringExpense()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: this.findSessionAccountAddress(sessionId),
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                    recurring: recurringAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                recurringAccountPubkey,
+            });
+        });
+    }
+
+    public async cancelRecurringExpense(signer: Wallet, sessionId: BN, recurringId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const recurringAccountPubkey = this.findRecurringExpenseAccountAddress(sessionId, recurringId);
+            const recurring = await this.program.account.recurringExpenseAccount.fetch(recurringAccountPubkey);
+
+            const tx = await this.program.methods
+                .cancelRecurringExpense()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: this.findSessionAccountAddress(sessionId),
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                    owner: recurring.owner,
+                    recurring: recurringAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                recurringAccountPubkey,
+            });
+        });
+    }
+
+    public async getRecurringExpense(recurringAccountPubkey: PublicKey): Promise<RecurringExpense> {
+        return this.wrapFn(async () => {
+            const recurring = await this.program.account.recurringExpenseAccount.fetch(recurringAccountPubkey);
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(recurring.sessionId));
+            return this._mapRecurringExpense(recurring, decimals);
+        });
+    }
+
+    public findRecurringExpenseAccountAddress(sessionId: BN, recurringId: BN): PublicKey {
+        const [recurringAccountPubkey] = PublicKey.findProgramAddressSync(
+            [Buffer.from('recurring'), sessionId.toArrayLike(Buffer, 'le', 8), recurringId.toArrayLike(Buffer, 'le', 2)],
+            this.program.programId,
+        );
+        return recurringAccountPubkey;
+    }
+
+    public async setExpenseApproval(admin: Wallet, sessionId: BN, expenseApproval: ExpenseApproval): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+
+            const tx = await this.program.methods
+                .setExpenseApproval(this._toInternalExpenseApproval(expenseApproval))
+                .accountsPartial({
+                    admin: admin.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, admin.publicKey),
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(admin, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async approveExpense(signer: Wallet, sessionId: BN, expenseId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+            const expense = await this.program.account.expenseAccount.fetch(expenseAccountPubkey);
+
+            const tx = await this.program.methods
+                .approveExpense()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    expense: expenseAccountPubkey,
+                })
+                .remainingAccounts(this._balanceAccountMetas(sessionId, this._involvedMembers(expense)))
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+                expenseAccountPubkey,
+            });
+        });
+    }
+
+    public async rejectExpense(signer: Wallet, sessionId: BN, expenseId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+
+            const tx = await this.program.methods
+                .rejectExpense()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    expense: expenseAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+                expenseAccountPubkey,
+            });
+        });
+    }
+
+    public async setExpenseReceipt(owner: Wallet, sessionId: BN, expenseId: BN, receipt: ExpenseReceipt | null): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+
+            const tx = await this.program.methods
+                .setExpenseReceipt(receipt && this._toInternalReceipt(receipt))
+                .accountsPartial({
+                    owner: owner.publicKey,
+                    session: sessionAccountPubkey,
+                    expense: expenseAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(owner, tx, {
+                expenseAccountPubkey,
+            });
+        });
+    }
+
+    public async disputeExpense(signer: Wallet, sessionId: BN, expenseId: BN, reason: string): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+            const expense = await this.program.account.expenseAccount.fetch(expenseAccountPubkey);
+
+            const tx = await this.program.methods
+                .disputeExpense(reason)
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    expense: expenseAccountPubkey,
+                })
+                .remainingAccounts(this._balanceAccountMetas(sessionId, this._involvedMembers(expense)))
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+                expenseAccountPubkey,
+            });
+        });
+    }
+
+    public async resolveDispute(signer: Wallet, sessionId: BN, expenseId: BN, amendment?: DisputeAmendment): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const expenseAccountPubkey = this.findExpenseAccountAddress(sessionId, expenseId);
+            const expense = await this.program.account.expenseAccount.fetch(expenseAccountPubkey);
+            const { decimals, budgeted } = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const participants = amendment?.participants;
+
+            const tx = await this.program.methods
+                .resolveDispute(
+                    amendment?.amount !== undefined ? this._toMinorUnits(amendment.amount, decimals) : null,
+                    participants ? this._toInternalParticipants(participants, this._mapSplitMode(expense.splitMode), amendment?.shares, decimals) : null,
+                )
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                    expense: expenseAccountPubkey,
+                    budget: this._findBudgetAccountAddress(sessionId, budgeted),
+                })
+                .remainingAccounts([
+                    ...this._memberAccountMetas(sessionId, participants || []),
+                    ...this._balanceAccountMetas(sessionId, [...this._involvedMembers(expense), ...(participants || [])]),
+                ])
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+                expenseAccountPubkey,
+            });
+        });
+    }
+
+    public async sendRefunds(
+        payer: Wallet,
+        sessionId: BN,
+        refundsToSend: MemberRefund[],
+        priceUpdateAccountPubkey: PublicKey = SOL_USD_PRICE_UPDATE_ACCOUNT,
+        maxSlippageBps: number = DEFAULT_MAX_SLIPPAGE_BPS,
+    ): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const fromMemberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, payer.publicKey);
+
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            let refundId = new BN(session.refundsCount);
+
+            const priceData = await this.getPriceData(priceUpdateAccountPubkey);
+
+            let instructions: TransactionInstruction[] = [];
+            let refundsAccountPubkeys: NodeJS.Dict<PublicKey> = {};
+            for (const transfer of refundsToSend) {
+                const toMemberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, transfer.to);
+                const refundAccountPubkey = this.findRefundAccountAddress(sessionId, refundId);
+                refundId = new BN(refundId + 1);
+
+                const amount = this._toMinorUnits(transfer.amount, session.decimals);
+                const expectedLamports = this.computePriceInLamportsFromAmount(amount, session.decimals, priceData);
+                const instruction = await this.program.methods
+                    .addRefund(amount, expectedLamports, maxSlippageBps, transfer.settlementIndex ?? null)
+                    .accountsPartial({
+                        fromAddr: payer.publicKey,
+                        sender: fromMemberAccountPubkey,
+                        toAddr: transfer.to,
+                        receiver: toMemberAccountPubkey,
+                        session: sessionAccountPubkey,
+                        senderBalance: this.findBalanceAccountAddress(sessionId, payer.publicKey),
+                        receiverBalance: this.findBalanceAccountAddress(sessionId, transfer.to),
+                        priceUpdate: priceUpdateAccountPubkey,
+                        settlement: transfer.settlementIndex !== undefined ? this.findSettlementAccountAddress(sessionId) : null,
+                        refund: refundAccountPubkey,
+                    })
+                    .instruction();
+                refundsAccountPubkeys = { ...refundsAccountPubkeys, refundAccountPubkey };
+                instructions.push(instruction);
+            }
+
+            const tx = new Transaction().add(...instructions);
+
+            return this.signAndSendTransaction(payer, tx, {
+                sessionAccountPubkey,
+                fromMemberAccountPubkey,
+                ...refundsAccountPubkeys,
+            });
+        });
+    }
+
+    public async sendTokenRefunds(payer: Wallet, sessionId: BN, refundsToSend: MemberRefund[]): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const fromMemberAccountPubkey = this.findSessionMemberAccountAddress(sessionId, payer.publicKey);
+
+            const session = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            if (!session.mint) {
+                throw new Error('Session mint is not set');
+            }
+            const tokenProgramId = await this._getTokenProgramId(session.mint);
+            let refundId = new BN(session.refundsCount);
+
+            let instructions: TransactionInstruction[] = [];
+            let refundsAccountPubkeys: NodeJS.Dict<PublicKey> = {};
+            for (const transfer of refundsToSend) {
+                const refundAccountPubkey = this.findRefundAccountAddress(sessionId, refundId);
+                refundId = new BN(refundId + 1);
+
+                const instruction = await this.program.methods
+                    .addTokenRefund(this._toMinorUnits(transfer.amount, session.decimals), transfer.settlementIndex ?? null)
+                    .accountsPartial({
+                        fromAddr: payer.publicKey,
+                        sender: fromMemberAccountPubkey,
+                        toAddr: transfer.to,
+                        receiver: this.findSessionMemberAccountAddress(sessionId, transfer.to),
+                        session: sessionAccountPubkey,
+                        senderBalance: this.findBalanceAccountAddress(sessionId, payer.publicKey),
+                        receiverBalance: this.findBalanceAccountAddress(sessionId, transfer.to),
+                        mint: session.mint,
+                        fromTokenAccount: this.findTokenAccountAddress(session.mint, payer.publicKey, tokenProgramId),
+                        toTokenAccount: this.findTokenAccountAddress(session.mint, transfer.to, tokenProgramId),
+                        settlement: transfer.settlementIndex !== undefined ? this.findSettlementAccountAddress(sessionId) : null,
+                        refund: refundAccountPubkey,
+                        tokenProgram: tokenProgramId,
+                    })
+                    .instruction();
+                refundsAccountPubkeys = { ...refundsAccountPubkeys, refundAccountPubkey };
+                instructions.push(instruction);
+            }
 
             const tx = new Transaction().add(...instructions);
 
@@ -812,17 +2153,202 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
         });
     }
 
+    public findTokenAccountAddress(mint: PublicKey, owner: PublicKey, tokenProgramId: PublicKey = utils.token.TOKEN_PROGRAM_ID): PublicKey {
+        const [tokenAccountPubkey] = PublicKey.findProgramAddressSync([owner.toBuffer(), tokenProgramId.toBuffer(), mint.toBuffer()], utils.token.ASSOCIATED_PROGRAM_ID);
+        return tokenAccountPubkey;
+    }
+
+    public async depositToVault(
+        depositor: Wallet,
+        sessionId: BN,
+        priceUpdateAccountPubkey: PublicKey = SOL_USD_PRICE_UPDATE_ACCOUNT,
+        maxSlippageBps: number = DEFAULT_MAX_SLIPPAGE_BPS,
+    ): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const balanceAccountPubkey = this.findBalanceAccountAddress(sessionId, depositor.publicKey);
+            const vaultAccountPubkey = this.findVaultAccountAddress(sessionId);
+            const depositAccountPubkey = this.findDepositAccountAddress(sessionId, depositor.publicKey);
+
+            const { decimals } = await this.program.account.sessionAccount.fetch(sessionAccountPubkey);
+            const { balance } = await this.program.account.balanceAccount.fetch(balanceAccountPubkey);
+            const priceData = await this.getPriceData(priceUpdateAccountPubkey);
+            // debtors deposit their whole net debt
+            const expectedLamports = balance.isNeg() ? this.computePriceInLamportsFromAmount(balance.abs(), decimals, priceData) : new BN(0);
+
+            const tx = await this.program.methods
+                .depositToVault(expectedLamports, maxSlippageBps)
+                .accountsPartial({
+                    depositor: depositor.publicKey,
+                    session: sessionAccountPubkey,
+                    balance: balanceAccountPubkey,
+                    vault: vaultAccountPubkey,
+                    deposit: depositAccountPubkey,
+                    priceUpdate: priceUpdateAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(depositor, tx, {
+                vaultAccountPubkey,
+                depositAccountPubkey,
+            });
+        });
+    }
+
+    public async withdrawFromVault(depositor: Wallet, sessionId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const vaultAccountPubkey = this.findVaultAccountAddress(sessionId);
+            const depositAccountPubkey = this.findDepositAccountAddress(sessionId, depositor.publicKey);
+
+            const tx = await this.program.methods
+                .withdrawFromVault()
+                .accountsPartial({
+                    depositor: depositor.publicKey,
+                    session: this.findSessionAccountAddress(sessionId),
+                    balance: this.findBalanceAccountAddress(sessionId, depositor.publicKey),
+                    vault: vaultAccountPubkey,
+                    deposit: depositAccountPubkey,
+                })
+                .transaction();
+
+            return this.signAndSendTransaction(depositor, tx, {
+                vaultAccountPubkey,
+                depositAccountPubkey,
+            });
+        });
+    }
+
+    public async settleSession(signer: Wallet, sessionId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const vaultAccountPubkey = this.findVaultAccountAddress(sessionId);
+            const members = await this.listSessionMembers(sessionId);
+
+            const tx = await this.program.methods
+                .settleSession()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    vault: vaultAccountPubkey,
+                })
+                // creditors are paid out to their wallet, which follows their balance
+                .remainingAccounts(
+                    _.flatMap(members, (member) => [
+                        { pubkey: this.findBalanceAccountAddress(sessionId, member.addr), isSigner: false, isWritable: true },
+                        { pubkey: member.addr, isSigner: false, isWritable: true },
+                    ]),
+                )
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+                vaultAccountPubkey,
+            });
+        });
+    }
+
+    public async finalizeSession(signer: Wallet, sessionId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const members = await this.listSessionMembers(sessionId);
+
+            const tx = await this.program.methods
+                .finalizeSession()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    member: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                })
+                .remainingAccounts(
+                    this._balanceAccountMetas(
+                        sessionId,
+                        members.map((member) => member.addr),
+                    ),
+                )
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+            });
+        });
+    }
+
+    public async computeSettlement(signer: Wallet, sessionId: BN): Promise<ITransactionResult> {
+        return this.wrapFn(async () => {
+            const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
+            const settlementAccountPubkey = this.findSettlementAccountAddress(sessionId);
+            const members = await this.listSessionMembers(sessionId);
+
+            const tx = await this.program.methods
+                .computeSettlement()
+                .accountsPartial({
+                    signer: signer.publicKey,
+                    session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, signer.publicKey),
+                    settlement: settlementAccountPubkey,
+                })
+                .remainingAccounts(
+                    this._balanceAccountMetas(
+                        sessionId,
+                        members.map((member) => member.addr),
+                    ),
+                )
+                .transaction();
+
+            return this.signAndSendTransaction(signer, tx, {
+                sessionAccountPubkey,
+                settlementAccountPubkey,
+            });
+        });
+    }
+
+    public async getSettlement(sessionId: BN): Promise<Settlement> {
+        return this.wrapFn(async () => {
+            const settlement = await this.program.account.settlementAccount.fetch(this.findSettlementAccountAddress(sessionId));
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(sessionId));
+            return this._mapSettlement(settlement, decimals);
+        });
+    }
+
+    public findSettlementAccountAddress(sessionId: BN): PublicKey {
+        const [settlementAccountPubkey] = PublicKey.findProgramAddressSync([Buffer.from('settlement'), sessionId.toArrayLike(Buffer, 'le', 8)], this.program.programId);
+        return settlementAccountPubkey;
+    }
+
+    public async getVault(sessionId: BN): Promise<Vault> {
+        return this.wrapFn(async () => {
+            const vault = await this.program.account.vaultAccount.fetch(this.findVaultAccountAddress(sessionId));
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(sessionId));
+            return this._mapVault(vault, decimals);
+        });
+    }
+
+    public findDepositAccountAddress(sessionId: BN, depositorPubkey: PublicKey): PublicKey {
+        const [depositAccountPubkey] = PublicKey.findProgramAddressSync(
+            [Buffer.from('deposit'), sessionId.toArrayLike(Buffer, 'le', 8), depositorPubkey.toBuffer()],
+            this.program.programId,
+        );
+        return depositAccountPubkey;
+    }
+
     public async deleteRefund(payer: Wallet, sessionId: BN, refundId: BN): Promise<ITransactionResult> {
         return this.wrapFn(async () => {
             const sessionAccountPubkey = this.findSessionAccountAddress(sessionId);
             const refundAccountPubkey = this.findRefundAccountAddress(sessionId, refundId);
+            const refund = await this.program.account.refundAccount.fetch(refundAccountPubkey);
+            const proposals = await this.listSessionProposals(sessionId);
 
             const tx = await this.program.methods
                 .deleteRefund()
                 .accountsPartial({
                     admin: payer.publicKey,
                     session: sessionAccountPubkey,
+                    signerMember: this.findSessionMemberAccountAddress(sessionId, payer.publicKey),
                     refund: refundAccountPubkey,
+                    senderBalance: this.findBalanceAccountAddress(sessionId, refund.from),
+                    receiverBalance: this.findBalanceAccountAddress(sessionId, refund.to),
+                    proposal: this._findProposalAccountAddress(proposals, { type: ProposalActionType.DeleteRefund, refundId: refund.refundId }),
+                    settlement: refund.settlementIndex !== null ? this.findSettlementAccountAddress(sessionId) : null,
                 })
                 .transaction();
 
@@ -859,7 +2385,10 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
                 .sort((a, b) => +(a.date > b.date) || -(a.date < b.date))
                 .map((account) => account.pubkey);
 
-            return (await this.getPage<InternalRefund>(this.program.account.refundAccount, addresses, paginationOptions?.page, paginationOptions?.perPage)).map(this._mapRefund);
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(sessionId));
+            return (await this.getPage<InternalRefund>(this.program.account.refundAccount, addresses, paginationOptions?.page, paginationOptions?.perPage)).map((refund) =>
+                this._mapRefund(refund, decimals),
+            );
         });
     }
 
@@ -874,70 +2403,401 @@ export class SolidrClient extends AbstractSolanaClient<Solidr> {
     public async getRefund(refundAccountPubkey: PublicKey): Promise<Refund> {
         return this.wrapFn(async () => {
             const refund = await this.program.account.refundAccount.fetch(refundAccountPubkey);
-            return this._mapRefund(refund);
+            const { decimals } = await this.program.account.sessionAccount.fetch(this.findSessionAccountAddress(refund.sessionId));
+            return this._mapRefund(refund, decimals);
         });
     }
 
     private _mapSessionStatus(internalStatus: InternalSessionStatus): SessionStatus {
         if (internalStatus.opened) return SessionStatus.Opened;
         if (internalStatus.closed) return SessionStatus.Closed;
+        if (internalStatus.settled) return SessionStatus.Settled;
         throw new Error('Bad session status');
     }
 
+    private _mapSplitMode(internalSplitMode: InternalSplitMode): SplitMode {
+        if (internalSplitMode.equal) return SplitMode.Equal;
+        if (internalSplitMode.weighted) return SplitMode.Weighted;
+        if (internalSplitMode.percentage) return SplitMode.Percentage;
+        if (internalSplitMode.exact) return SplitMode.Exact;
+        throw new Error('Bad split mode');
+    }
+
+    private _toInternalSplitMode(splitMode: SplitMode): InternalSplitMode {
+        return { [splitMode]: {} };
+    }
+
     private _mapSession = (internalSession: InternalSession): Session => {
         return {
-            ...internalSession,
+            sessionId: internalSession.sessionId,
+            name: internalSession.name,
+            description: internalSession.description,
             status: this._mapSessionStatus(internalSession.status),
+            admin: internalSession.admin,
+            expensesCount: internalSession.expensesCount,
+            refundsCount: internalSession.refundsCount,
             invitationHash: internalSession.invitationHash.toString(),
+            currency: internalSession.currency,
+            decimals: internalSession.decimals,
+            mint: internalSession.mint,
+            mintCurrency: internalSession.mintCurrency,
+            pendingAdmin: internalSession.pendingAdmin,
+            approvalThreshold: internalSession.approvalThreshold,
+            expenseApproval: this._mapExpenseApproval(internalSession.expenseApproval),
+            closedAt: internalSession.closedAt.isZero() ? null : new Date(internalSession.closedAt.toNumber() * 1000),
+            reopenWindow: internalSession.reopenWindow.toNumber(),
+        };
+    };
+
+    private _mapSessionMember = (internalMember: InternalSessionMember): SessionMember => {
+        return {
+            sessionId: internalMember.sessionId,
+            name: internalMember.name,
+            addr: internalMember.addr,
+            isAdmin: internalMember.isAdmin,
+            role: this._mapMemberRole(internalMember.role),
         };
     };
 
-    private _mapExpense = (internalExpense: InternalExpense): Expense => {
+    private _mapMemberRole(internalRole: InternalMemberRole): MemberRole {
+        if (internalRole.admin) return MemberRole.Admin;
+        if (internalRole.treasurer) return MemberRole.Treasurer;
+        if (internalRole.member) return MemberRole.Member;
+        if (internalRole.viewer) return MemberRole.Viewer;
+        throw new Error('Bad member role');
+    }
+
+    private _toInternalMemberRole(role: MemberRole): InternalMemberRole {
+        return { [role]: {} };
+    }
+
+    private _mapExpense = (internalExpense: InternalExpense, decimals: number): Expense => {
+        const splitMode = this._mapSplitMode(internalExpense.splitMode);
         return {
-            ...internalExpense,
-            amount: Math.round(internalExpense.amount * 100) / 100,
+            sessionId: internalExpense.sessionId,
+            expenseId: internalExpense.expenseId,
+            name: internalExpense.name,
+            owner: internalExpense.owner,
             date: new Date(internalExpense.date.toNumber() * 1000),
+            amount: this._fromMinorUnits(internalExpense.amount, decimals),
+            participants: internalExpense.participants.map((p) => p.member),
+            splitMode,
+            shares: internalExpense.participants.map((p) => this._fromInternalShare(splitMode, p.share, decimals)),
+            original: internalExpense.original && this._mapOriginal(internalExpense.original),
+            status: this._mapExpenseStatus(internalExpense.status),
+            approvals: internalExpense.approvals,
+            dispute: internalExpense.dispute && {
+                by: internalExpense.dispute.by,
+                date: new Date(internalExpense.dispute.date.toNumber() * 1000),
+                reason: internalExpense.dispute.reason,
+            },
+            category: this._mapExpenseCategory(internalExpense.category),
+            tags: internalExpense.tags,
+            receipt: internalExpense.receipt && {
+                hash: Buffer.from(internalExpense.receipt.hash).toString('hex'),
+                uri: internalExpense.receipt.uri,
+            },
+            payer: internalExpense.payer,
         };
     };
 
-    private _mapRefund = (internalRefund: InternalRefund): Refund => {
+    private _mapBudget = (internalBudget: InternalBudget, decimals: number): Budget => {
+        const mapLine = (line: InternalBudgetLine): BudgetLine => ({
+            limit: line.limit && this._fromMinorUnits(line.limit, decimals),
+            spent: this._fromMinorUnits(line.spent, decimals),
+            hardCap: line.hardCap,
+        });
         return {
-            ...internalRefund,
+            sessionId: internalBudget.sessionId,
+            total: mapLine(internalBudget.total),
+            // category lines are stored in category declaration order
+            categories: _.zipObject(Object.values(ExpenseCategory), internalBudget.categories.map(mapLine)) as Record<ExpenseCategory, BudgetLine>,
+        };
+    };
+
+    private _mapRecurringExpense = (internalRecurring: InternalRecurringExpense, decimals: number): RecurringExpense => {
+        const splitMode = this._mapSplitMode(internalRecurring.splitMode);
+        return {
+            sessionId: internalRecurring.sessionId,
+            recurringId: internalRecurring.recurringId,
+            owner: internalRecurring.owner,
+            name: internalRecurring.name,
+            amount: this._fromMinorUnits(internalRecurring.amount, decimals),
+            participants: internalRecurring.participants.map((p) => p.member),
+            splitMode,
+            shares: internalRecurring.participants.map((p) => this._fromInternalShare(splitMode, p.share, decimals)),
+            interval: internalRecurring.interval.toNumber(),
+            nextDue: new Date(internalRecurring.nextDue.toNumber() * 1000),
+            status: internalRecurring.status.paused ? RecurringStatus.Paused : RecurringStatus.Active,
+            category: this._mapExpenseCategory(internalRecurring.category),
+            tags: internalRecurring.tags,
+        };
+    };
+
+    private _toInternalReceipt(receipt: ExpenseReceipt): InternalExpenseReceipt {
+        return { hash: [...Buffer.from(receipt.hash, 'hex')], uri: receipt.uri };
+    }
+
+    private _mapExpenseCategory(internalCategory: InternalExpenseCategory): ExpenseCategory {
+        if (internalCategory.other) return ExpenseCategory.Other;
+        if (internalCategory.food) return ExpenseCategory.Food;
+        if (internalCategory.transport) return ExpenseCategory.Transport;
+        if (internalCategory.accommodation) return ExpenseCategory.Accommodation;
+        if (internalCategory.entertainment) return ExpenseCategory.Entertainment;
+        if (internalCategory.shopping) return ExpenseCategory.Shopping;
+        if (internalCategory.utilities) return ExpenseCategory.Utilities;
+        if (internalCategory.health) return ExpenseCategory.Health;
+        throw new Error('Bad expense category');
+    }
+
+    private _toInternalExpenseCategory(category: ExpenseCategory): InternalExpenseCategory {
+        return { [category]: {} };
+    }
+
+    private _mapExpenseStatus(internalStatus: InternalExpenseStatus): ExpenseStatus {
+        if (internalStatus.approved) return ExpenseStatus.Approved;
+        if (internalStatus.pending) return ExpenseStatus.Pending;
+        if (internalStatus.rejected) return ExpenseStatus.Rejected;
+        throw new Error('Bad expense status');
+    }
+
+    private _mapExpenseApproval(internalApproval: InternalExpenseApproval): ExpenseApproval {
+        if (internalApproval.disabled) return { mode: ExpenseApprovalMode.Disabled };
+        if (internalApproval.allParticipants) return { mode: ExpenseApprovalMode.AllParticipants };
+        if (internalApproval.quorum) return { mode: ExpenseApprovalMode.Quorum, approvals: internalApproval.quorum.approvals };
+        throw new Error('Bad expense approval');
+    }
+
+    private _toInternalExpenseApproval(expenseApproval: ExpenseApproval): InternalExpenseApproval {
+        if (expenseApproval.mode === ExpenseApprovalMode.Quorum) return { quorum: { approvals: expenseApproval.approvals } };
+        return { [expenseApproval.mode]: {} };
+    }
+
+    private _mapVault = (internalVault: InternalVault, decimals: number): Vault => {
+        return {
+            sessionId: internalVault.sessionId,
+            epoch: internalVault.epoch,
+            totalDeposited: this._fromMinorUnits(internalVault.totalDeposited, decimals),
+            totalLamports: internalVault.totalLamports,
+        };
+    };
+
+    private _mapOriginal = (internalOriginal: InternalOriginalAmount): OriginalAmount => {
+        return {
+            currency: internalOriginal.currency,
+            amount: this._fromMinorUnits(internalOriginal.amount, internalOriginal.decimals),
+            decimals: internalOriginal.decimals,
+            exchangeRate: this._fromMinorUnits(internalOriginal.exchangeRate, EXCHANGE_RATE_DECIMALS),
+        };
+    };
+
+    private _toInternalOriginal(original: OriginalAmount): InternalOriginalAmount {
+        return {
+            currency: original.currency,
+            amount: this._toMinorUnits(original.amount, original.decimals),
+            decimals: original.decimals,
+            exchangeRate: this._toMinorUnits(original.exchangeRate, EXCHANGE_RATE_DECIMALS),
+        };
+    }
+
+    private _mapRefund = (internalRefund: InternalRefund, decimals: number): Refund => {
+        return {
+            sessionId: internalRefund.sessionId,
+            refundId: internalRefund.refundId,
             date: new Date(internalRefund.date.toNumber() * 1000),
+            from: internalRefund.from,
+            to: internalRefund.to,
+            amount: this._fromMinorUnits(internalRefund.amount, decimals),
+            amountInLamports: internalRefund.amountInLamports,
+            mint: internalRefund.mint,
+            tokenAmount: internalRefund.tokenAmount,
+            settlementIndex: internalRefund.settlementIndex,
         };
     };
 
-    private async _getNextSessionId(): Promise<BN> {
-        return this.wrapFn(async () => {
-            const sessionCount = (await this.program.account.globalAccount.fetch(this.globalAccountPubkey)).sessionCount ?? 0;
-            return new BN(sessionCount);
-        });
+    private _mapSettlement = (internalSettlement: InternalSettlement, decimals: number): Settlement => {
+        return {
+            sessionId: internalSettlement.sessionId,
+            date: new Date(internalSettlement.date.toNumber() * 1000),
+            planId: internalSettlement.planId,
+            transfers: internalSettlement.transfers.map((transfer) => ({
+                from: transfer.from,
+                to: transfer.to,
+                amount: this._fromMinorUnits(transfer.amount, decimals),
+                paid: this._fromMinorUnits(transfer.paid, decimals),
+            })),
+        };
+    };
+
+    private _mapProposal = (internalProposal: InternalProposal): Proposal => {
+        return {
+            sessionId: internalProposal.sessionId,
+            proposalId: internalProposal.proposalId,
+            date: new Date(internalProposal.date.toNumber() * 1000),
+            proposer: internalProposal.proposer,
+            action: this._mapProposalAction(internalProposal.action),
+            approvals: internalProposal.approvals,
+            executed: internalProposal.executed,
+        };
+    };
+
+    private _mapInvitation = (internalInvitation: InternalInvitation): Invitation => {
+        return {
+            sessionId: internalInvitation.sessionId,
+            invitationId: internalInvitation.invitationId,
+            tokenHash: internalInvitation.tokenHash.toString(),
+            expiresAt: internalInvitation.expiresAt.isZero() ? null : new Date(internalInvitation.expiresAt.toNumber() * 1000),
+            maxUses: internalInvitation.maxUses,
+            remainingUses: internalInvitation.remainingUses,
+            revoked: internalInvitation.revoked,
+            label: internalInvitation.label,
+            creator: internalInvitation.creator,
+            role: internalInvitation.role ? this._mapMemberRole(internalInvitation.role) : null,
+        };
+    };
+
+    private _mapProposalAction(internalAction: InternalProposalAction): ProposalAction {
+        if (internalAction.closeSession) return { type: ProposalActionType.CloseSession };
+        if (internalAction.deleteSession) return { type: ProposalActionType.DeleteSession };
+        if (internalAction.deleteMember) return { type: ProposalActionType.DeleteMember, addr: internalAction.deleteMember.addr };
+        if (internalAction.deleteRefund) return { type: ProposalActionType.DeleteRefund, refundId: internalAction.deleteRefund.refundId };
+        if (internalAction.setApprovalThreshold) return { type: ProposalActionType.SetApprovalThreshold, threshold: internalAction.setApprovalThreshold.threshold };
+        if (internalAction.reopenSession) return { type: ProposalActionType.ReopenSession };
+        if (internalAction.setPriceFeed) return { type: ProposalActionType.SetPriceFeed, priceFeedId: '0x' + Buffer.from(internalAction.setPriceFeed.priceFeedId).toString('hex') };
+        throw new Error('Bad proposal action');
     }
 
-    private async _getNextExpenseId(sessionAccountPubkey: PublicKey): Promise<BN> {
-        return this.wrapFn(async () => {
-            const expensesCount = (await this.program.account.sessionAccount.fetch(sessionAccountPubkey)).expensesCount ?? 0;
-            return new BN(expensesCount);
-        });
+    private _toInternalProposalAction(action: ProposalAction): InternalProposalAction {
+        switch (action.type) {
+            case ProposalActionType.DeleteMember:
+                return { deleteMember: { addr: action.addr } };
+            case ProposalActionType.DeleteRefund:
+                return { deleteRefund: { refundId: action.refundId } };
+            case ProposalActionType.SetApprovalThreshold:
+                return { setApprovalThreshold: { threshold: action.threshold } };
+            case ProposalActionType.SetPriceFeed:
+                return { setPriceFeed: { priceFeedId: [...Buffer.from(action.priceFeedId.replace(/^0x/, ''), 'hex')] } };
+            default:
+                return { [action.type]: {} };
+        }
+    }
+
+    // budget account must be provided to instructions changing expenses amounts once session is budgeted
+    private _findBudgetAccountAddress(sessionId: BN, budgeted: boolean | undefined): PublicKey | null {
+        return budgeted ? this.findBudgetAccountAddress(sessionId) : null;
     }
 
-    private async _getNextRefundId(sessionAccountPubkey: PublicKey): Promise<BN> {
+    // latest pending proposal for given action, required by destructive actions when session has an approval threshold
+    private _findProposalAccountAddress(proposals: Proposal[], action: ProposalAction): PublicKey | null {
+        const actionKey = this._proposalActionKey(action);
+        const proposal = _.findLast(proposals, (proposal) => !proposal.executed && this._proposalActionKey(proposal.action) === actionKey);
+        return proposal ? this.findProposalAccountAddress(proposal.sessionId, new BN(proposal.proposalId)) : null;
+    }
+
+    private _proposalActionKey(action: ProposalAction): string {
+        return JSON.stringify(this._toInternalProposalAction(action));
+    }
+
+    private _mapMemberBalance = (internalBalance: InternalMemberBalanceAccount, decimals: number): MemberBalanceAccount => {
+        return {
+            sessionId: internalBalance.sessionId,
+            addr: internalBalance.addr,
+            totalPaid: this._fromMinorUnits(internalBalance.totalPaid, decimals),
+            totalOwed: this._fromMinorUnits(internalBalance.totalOwed, decimals),
+            balance: this._fromMinorUnits(internalBalance.balance, decimals),
+        };
+    };
+
+    private _toMinorUnits(amount: number, decimals: number): BN {
+        const minorUnits = Math.round(amount * 10 ** decimals);
+        // tolerate floating point noise only, not amounts finer than currency precision
+        if (Math.abs(minorUnits - amount * 10 ** decimals) > 1e-6) {
+            throw new Error('Amount exceeds session currency precision');
+        }
+        return new BN(minorUnits);
+    }
+
+    private _fromMinorUnits(amount: BN, decimals: number): number {
+        return amount.toNumber() / 10 ** decimals;
+    }
+
+    private _toInternalParticipants(participants: PublicKey[], splitMode: SplitMode, shares: number[] | undefined, decimals: number): InternalExpenseParticipant[] {
+        return participants.map((member, i) => ({ member, share: this._toInternalShare(splitMode, shares?.[i] ?? 1, decimals) }));
+    }
+
+    private _toInternalShare(splitMode: SplitMode, share: number, decimals: number): BN {
+        switch (splitMode) {
+            case SplitMode.Percentage:
+                // percentages are stored in basis points
+                return new BN(Math.round(share * 100));
+            case SplitMode.Exact:
+                return this._toMinorUnits(share, decimals);
+            default:
+                return new BN(share);
+        }
+    }
+
+    private _fromInternalShare(splitMode: SplitMode, share: BN, decimals: number): number {
+        switch (splitMode) {
+            case SplitMode.Percentage:
+                return share.toNumber() / 100;
+            case SplitMode.Exact:
+                return this._fromMinorUnits(share, decimals);
+            default:
+                return share.toNumber();
+        }
+    }
+
+    private _involvedMembers(expense: InternalExpense): PublicKey[] {
+        return [expense.owner, expense.payer, ...expense.participants.map((p) => p.member)];
+    }
+
+    private _memberAccountMetas(sessionId: BN, members: PublicKey[]): AccountMeta[] {
+        return members.map((p) => ({
+            pubkey: this.findSessionMemberAccountAddress(sessionId, p),
+            isSigner: false,
+            isWritable: false,
+        }));
+    }
+
+    private _balanceAccountMetas(sessionId: BN, members: PublicKey[]): AccountMeta[] {
+        return _.uniqBy(members, (pk) => pk.toString()).map((p) => ({
+            pubkey: this.findBalanceAccountAddress(sessionId, p),
+            isSigner: false,
+            isWritable: true,
+        }));
+    }
+
+    private async _getTokenProgramId(mint: PublicKey): Promise<PublicKey> {
+        // mint is owned either by Token or Token-2022 program
+        const mintAccount = await this.connection.getAccountInfo(mint);
+        if (!mintAccount) {
+            throw new Error('Mint account not found');
+        }
+        return mintAccount.owner;
+    }
+
+    private async _getNextSessionId(): Promise<BN> {
         return this.wrapFn(async () => {
-            const refundsCount = (await this.program.account.sessionAccount.fetch(sessionAccountPubkey)).refundsCount ?? 0;
-            return new BN(refundsCount);
+            const sessionCount = (await this.program.account.globalAccount.fetch(this.globalAccountPubkey)).sessionCount ?? 0;
+            return new BN(sessionCount);
         });
     }
 
-    private computePriceInLamportsFromAmount(amount: number, priceData: PriceData): BN {
-        const priceBN = new BN(priceData.price);
-        const exponentBN = new BN(10).pow(new BN(Math.abs(priceData.expo).toString()));
-        return new BN(amount * 1e9).mul(exponentBN).div(priceBN);
+    private computePriceInLamportsFromAmount(amount: BN, decimals: number, priceData: PriceData): BN {
+        // mirrors on-chain conversion: lamports = amount * 10^9 / (price * 10^exponent * 10^decimals)
+        let numerator = amount.mul(new BN(LAMPORTS_PER_SOL));
+        let denominator = priceData.price.mul(new BN(10).pow(new BN(decimals)));
+        if (priceData.expo < 0) {
+            numerator = numerator.mul(new BN(10).pow(new BN(-priceData.expo)));
+        } else {
+            denominator = denominator.mul(new BN(10).pow(new BN(priceData.expo)));
+        }
+        return numerator.div(denominator);
     }
 
-    private async getPriceData(): Promise<PriceData> {
-        const priceUpdates = await this.hermesConnection.getLatestPriceUpdates([
-            '0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d', // SOL -> USD
-        ]);
-        return priceUpdates.parsed![0].price as PriceData;
+    private async getPriceData(priceUpdateAccountPubkey: PublicKey): Promise<PriceData> {
+        const { priceMessage } = await this.program.account.priceUpdateV2.fetch(priceUpdateAccountPubkey);
+        return { price: priceMessage.price, expo: priceMessage.exponent };
     }
 }
@@ -40,4 +40,134 @@ pub enum SolidrError {
     ParticipantNotMember,
     #[msg("Expense owner cannot be removed from participants")]
     CannotRemoveExpenseOwner,
+    #[msg("Account does not belong to session")]
+    SessionMismatch,
+    #[msg("Account has already been migrated")]
+    AccountAlreadyMigrated,
+    #[msg("Balance account of a member is missing")]
+    MissingBalanceAccount,
+    #[msg("Refund sender and receiver must be different members")]
+    RefundToSelf,
+    #[msg("Refund amount can't be represented with token decimals")]
+    RefundAmountNotTransferable,
+    #[msg("Participants total weight must be greater than zero")]
+    WeightsMustBeGreaterThanZero,
+    #[msg("Participants percentages must sum to 100%")]
+    PercentagesMustSumToHundred,
+    #[msg("Participants exact amounts must sum to expense amount")]
+    ExactAmountsMustSumToTotal,
+    #[msg("Refund mint doesn't match session mint")]
+    InvalidRefundMint,
+    #[msg("Token currency doesn't match session currency")]
+    MintCurrencyMismatch,
+    #[msg("Price must be greater than zero")]
+    InvalidPrice,
+    #[msg("Price confidence interval is too wide")]
+    PriceConfidenceTooLow,
+    #[msg("Refund lamports differ from expected amount more than allowed slippage")]
+    SlippageExceeded,
+    #[msg("Currency must be an ISO-4217 alphabetic code")]
+    InvalidCurrency,
+    #[msg("Currency decimals can't exceed 4")]
+    InvalidCurrencyDecimals,
+    #[msg("Session price feed is not configured")]
+    MissingPriceFeed,
+    #[msg("Expense amount doesn't match original amount converted with exchange rate")]
+    ExchangeRateMismatch,
+    #[msg("Only members with a negative balance can deposit")]
+    NothingToDeposit,
+    #[msg("Balance and wallet of every session member must be provided")]
+    IncompleteMembersList,
+    #[msg("Vault doesn't cover all debts")]
+    VaultNotCovered,
+    #[msg("Vault still holds deposits")]
+    VaultNotEmpty,
+    #[msg("Settlement plan can't exceed 30 transfers")]
+    TooManySettlementTransfers,
+    #[msg("Refund doesn't match settlement plan transfer")]
+    InvalidSettlementTransfer,
+    #[msg("Refund amount exceeds remaining amount of settlement plan transfer")]
+    SettlementTransferExceeded,
+    #[msg("Every member balance must be settled")]
+    SessionNotSettled,
+    #[msg("Member balance must be zero")]
+    MemberBalanceNotZero,
+    #[msg("Member role doesn't allow this action")]
+    ForbiddenRole,
+    #[msg("Session administrator role can't be changed")]
+    CannotChangeSessionAdminRole,
+    #[msg("Signer isn't the proposed session administrator")]
+    NotPendingAdmin,
+    #[msg("Member is already session administrator")]
+    AlreadySessionAdmin,
+    #[msg("Approval threshold can't exceed members count nor 20")]
+    InvalidApprovalThreshold,
+    #[msg("Action requires a proposal approved by enough members")]
+    ApprovalRequired,
+    #[msg("Proposal doesn't match this action")]
+    ProposalMismatch,
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+    #[msg("Member has already approved this proposal")]
+    AlreadyApproved,
+    #[msg("Expense quorum must be between 1 and 20")]
+    InvalidExpenseQuorum,
+    #[msg("Expense isn't pending approval")]
+    ExpenseNotPending,
+    #[msg("Signer isn't an expense participant")]
+    NotExpenseParticipant,
+    #[msg("Participant has already approved this expense")]
+    ExpenseAlreadyApproved,
+    #[msg("Expense is frozen until its dispute is resolved")]
+    ExpenseDisputed,
+    #[msg("Expense isn't disputed")]
+    ExpenseNotDisputed,
+    #[msg("Dispute reason can't exceed 64 characters")]
+    DisputeReasonTooLong,
+    #[msg("Reopen window can't be negative")]
+    InvalidReopenWindow,
+    #[msg("Session administrator can't leave the session")]
+    AdminCannotLeave,
+    #[msg("Member is involved in expenses pending approval or disputed")]
+    MemberHasOutstandingExpenses,
+    #[msg("Every expense involving member must be provided")]
+    IncompleteExpensesList,
+    #[msg("Member paid expenses must be reassigned to another member")]
+    MemberOwnsExpenses,
+    #[msg("Member is still involved in expenses")]
+    MemberInvolvedInExpenses,
+    #[msg("Invitation must have a token hash and at least one use")]
+    InvalidInvitation,
+    #[msg("Invitation has expired")]
+    InvitationExpired,
+    #[msg("Invitation has no remaining use")]
+    InvitationExhausted,
+    #[msg("Invitation has been revoked")]
+    InvitationRevoked,
+    #[msg("Invitation label can't exceed 32 characters")]
+    InvitationLabelTooLong,
+    #[msg("Recurring expense interval must be at least one day")]
+    InvalidRecurringInterval,
+    #[msg("Recurring expense is paused")]
+    RecurringExpensePaused,
+    #[msg("Recurring expense isn't paused")]
+    RecurringExpenseNotPaused,
+    #[msg("Recurring expense isn't due yet")]
+    RecurringExpenseNotDue,
+    #[msg("Recurring expense first occurrence can't be in the past")]
+    RecurringFirstDueInPast,
+    #[msg("Too many expense tags")]
+    TooManyExpenseTags,
+    #[msg("Expense tags must be non empty and 16 characters max")]
+    InvalidExpenseTag,
+    #[msg("Session budget account must be provided")]
+    MissingBudgetAccount,
+    #[msg("Expense would exceed session budget")]
+    BudgetHardCapExceeded,
+    #[msg("Receipt URI must be non empty and 64 characters max")]
+    InvalidReceiptUri,
+    #[msg("Expense payer must be a session member")]
+    PayerNotMember,
+    #[msg("Expense must have at least one participant")]
+    ExpenseWithoutParticipants,
 }
@@ -0,0 +1,185 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::state::balances::*;
+use crate::state::expenses::*;
+use crate::state::sessions::SessionAccount;
+
+/**
+ * Accumulates balance deltas per member so that each balance account is written once.
+ */
+#[derive(Default)]
+pub struct BalanceChanges {
+    changes: Vec<BalanceChange>,
+}
+
+pub struct BalanceChange {
+    addr: Pubkey,
+    paid: i128,
+    owed: i128,
+    expenses: i32,
+    pending: i32,
+}
+
+impl BalanceChange {
+    fn is_empty(&self) -> bool {
+        self.paid == 0 && self.owed == 0 && self.expenses == 0 && self.pending == 0
+    }
+
+    pub fn apply_to(&self, balance: &mut BalanceAccount) -> Result<()> {
+        balance.apply(self.paid, self.owed)?;
+        balance.apply_counts(self.expenses, self.pending)
+    }
+}
+
+impl BalanceChanges {
+    pub fn add_expense(&mut self, expense: &ExpenseAccount) {
+        self.record_expense(expense, 1);
+    }
+
+    pub fn remove_expense(&mut self, expense: &ExpenseAccount) {
+        self.record_expense(expense, -1);
+    }
+
+    fn record_expense(&mut self, expense: &ExpenseAccount, sign: i128) {
+        let outstanding = expense.is_outstanding();
+        let mut involved: Vec<Pubkey> = expense.participants.iter().map(|p| p.member).collect();
+        for addr in [expense.payer, expense.owner] {
+            if !involved.contains(&addr) {
+                involved.push(addr);
+            }
+        }
+        for addr in involved {
+            let change = self.change(addr);
+            change.expenses += sign as i32;
+            if outstanding {
+                change.pending += sign as i32;
+            }
+        }
+
+        if expense.status != ExpenseStatus::Approved {
+            return;
+        }
+        self.push(expense.payer, sign * expense.amount as i128, 0);
+        for (participant, share) in expense.shares() {
+            self.push(participant, 0, sign * share as i128);
+        }
+    }
+
+    pub fn push(&mut self, addr: Pubkey, paid: i128, owed: i128) {
+        let change = self.change(addr);
+        change.paid += paid;
+        change.owed += owed;
+    }
+
+    fn change(&mut self, addr: Pubkey) -> &mut BalanceChange {
+        let index = match self.changes.iter().position(|change| change.addr == addr) {
+            Some(index) => index,
+            None => {
+                self.changes.push(BalanceChange {
+                    addr,
+                    paid: 0,
+                    owed: 0,
+                    expenses: 0,
+                    pending: 0,
+                });
+                self.changes.len() - 1
+            }
+        };
+        &mut self.changes[index]
+    }
+
+    /**
+     * Extracts accumulated deltas of a member whose balance account isn't provided as remaining account.
+     */
+    pub fn take(&mut self, addr: Pubkey) -> Option<BalanceChange> {
+        let index = self.changes.iter().position(|change| change.addr == addr)?;
+        Some(self.changes.remove(index))
+    }
+
+    /**
+     * Writes accumulated deltas into balance accounts provided as remaining accounts.
+     */
+    pub fn apply(
+        self,
+        program_id: &Pubkey,
+        remaining_accounts: &[AccountInfo<'_>],
+        session_id: u64,
+    ) -> Result<()> {
+        for change in self.changes {
+            if change.is_empty() {
+                continue;
+            }
+            let balance_pda_address = get_balance_pda_address(program_id, session_id, change.addr);
+            let account_info = remaining_accounts
+                .iter()
+                .find(|account| account.key() == balance_pda_address)
+                .ok_or(SolidrError::MissingBalanceAccount)?;
+
+            require!(account_info.is_writable, SolidrError::MissingBalanceAccount);
+
+            let mut balance = load_balance(program_id, account_info)?;
+            change.apply_to(&mut balance)?;
+            store_balance(account_info, &balance)?;
+        }
+        Ok(())
+    }
+}
+
+/**
+ * Deserializes a balance account provided as remaining account.
+ */
+pub fn load_balance(program_id: &Pubkey, account_info: &AccountInfo<'_>) -> Result<BalanceAccount> {
+    require!(
+        account_info.owner == program_id,
+        SolidrError::MissingBalanceAccount
+    );
+    let data = account_info.try_borrow_data()?;
+    BalanceAccount::try_deserialize(&mut &data[..])
+}
+
+/**
+ * Loads balances of every session member, ensuring none is missing or provided twice.
+ */
+pub fn load_session_balances<'a, 'info: 'a>(
+    program_id: &Pubkey,
+    session: &SessionAccount,
+    balance_infos: impl ExactSizeIterator<Item = &'a AccountInfo<'info>>,
+) -> Result<Vec<BalanceAccount>> {
+    require!(
+        balance_infos.len() == session.members_count as usize,
+        SolidrError::IncompleteMembersList
+    );
+
+    let mut balances: Vec<BalanceAccount> = vec![];
+    for balance_info in balance_infos {
+        let balance = load_balance(program_id, balance_info)?;
+        require!(
+            balance.session_id == session.session_id
+                && balance_info.key()
+                    == get_balance_pda_address(program_id, session.session_id, balance.addr)
+                && !balances.iter().any(|b| b.addr == balance.addr),
+            SolidrError::IncompleteMembersList
+        );
+        balances.push(balance);
+    }
+    Ok(balances)
+}
+
+pub fn store_balance(account_info: &AccountInfo<'_>, balance: &BalanceAccount) -> Result<()> {
+    let mut data = account_info.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    balance.try_serialize(&mut writer)
+}
+
+pub fn get_balance_pda_address(program_id: &Pubkey, session_id: u64, member: Pubkey) -> Pubkey {
+    let (pubkey, _) = Pubkey::find_program_address(
+        &[
+            BalanceAccount::SEED_PREFIX,
+            session_id.to_le_bytes().as_ref(),
+            member.as_ref(),
+        ],
+        program_id,
+    );
+    pubkey
+}
@@ -0,0 +1,135 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+use crate::state::budgets::*;
+use crate::state::expenses::ExpenseCategory;
+use crate::state::members::*;
+use crate::state::sessions::*;
+
+#[derive(Accounts)]
+pub struct SetBudgetContextData<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + BudgetAccount::INIT_SPACE,
+        seeds = [
+        BudgetAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub budget: Account<'info, BudgetAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_budget(
+    ctx: Context<SetBudgetContextData>,
+    category: Option<ExpenseCategory>,
+    limit: Option<u64>,
+    hard_cap: bool,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let budget = &mut ctx.accounts.budget;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+
+    // running totals only account for expenses recorded once session is budgeted
+    budget.session_id = session.session_id;
+    session.budgeted = true;
+
+    let line = budget.line_mut(category);
+    line.limit = limit;
+    line.hard_cap = hard_cap;
+
+    emit!(BudgetUpdated {
+        session_id: session.session_id,
+        category,
+        limit,
+        hard_cap,
+    });
+    Ok(())
+}
+
+/**
+ * Moves an expense amount between budget running totals, from its previous to its current category.
+ *
+ * @dev Budget account is mandatory once session is budgeted so that limits can't be bypassed.
+ * Previous amount is only removed when it was tracked, returns whether current amount is tracked
+ */
+pub fn track_budget(
+    session: &SessionAccount,
+    budget: Option<&mut Account<BudgetAccount>>,
+    tracked: bool,
+    previous: Option<(ExpenseCategory, u64)>,
+    current: Option<(ExpenseCategory, u64)>,
+) -> Result<bool> {
+    if !session.budgeted {
+        return Ok(false);
+    }
+    let budget = budget.ok_or(SolidrError::MissingBudgetAccount)?;
+    let previous = if tracked { previous } else { None };
+
+    // (category, removed amount, added amount) for total line then affected category lines
+    let mut changes: Vec<(Option<ExpenseCategory>, u64, u64)> = vec![(
+        None,
+        previous.map_or(0, |(_, amount)| amount),
+        current.map_or(0, |(_, amount)| amount),
+    )];
+    if let Some((category, amount)) = previous {
+        changes.push((Some(category), amount, 0));
+    }
+    if let Some((category, amount)) = current {
+        match changes.iter_mut().find(|change| change.0 == Some(category)) {
+            Some(change) => change.2 = amount,
+            None => changes.push((Some(category), 0, amount)),
+        }
+    }
+
+    for (category, removed, added) in changes {
+        let line = budget.line_mut(category);
+        let spent = line.spent;
+        line.spent = spent
+            .saturating_sub(removed)
+            .checked_add(added)
+            .ok_or(SolidrError::Overflow)?;
+
+        if let Some(limit) = line.limit {
+            if line.spent > limit && line.spent > spent {
+                require!(!line.hard_cap, SolidrError::BudgetHardCapExceeded);
+                if spent <= limit {
+                    emit!(BudgetExceeded {
+                        session_id: session.session_id,
+                        category,
+                        limit,
+                        spent: line.spent,
+                    });
+                }
+            }
+        }
+    }
+    Ok(current.is_some())
+}
@@ -2,9 +2,12 @@ use anchor_lang::prelude::*;
 use anchor_lang::solana_program::clock;
 
 use crate::{errors::*, state::sessions::*};
+use crate::instructions::balances::BalanceChanges;
+use crate::instructions::budgets::track_budget;
 use crate::is_session_member;
+use crate::state::budgets::BudgetAccount;
 use crate::state::expenses::*;
-use crate::state::members::MemberAccount;
+use crate::state::members::{MemberAccount, MemberRole};
 
 #[derive(Accounts)]
 pub struct AddExpenseContextData<'info> {
@@ -30,14 +33,32 @@ pub struct AddExpenseContextData<'info> {
     )]
     pub expense: Account<'info, ExpenseAccount>,
 
+    #[account(
+        mut,
+        seeds = [
+        BudgetAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub budget: Option<Account<'info, BudgetAccount>>,
+
     pub system_program: Program<'info, System>,
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn add_expense(
     ctx: Context<AddExpenseContextData>,
     name: String,
-    amount: f32,
-    participants: Vec<Pubkey>,
+    amount: u64,
+    split_mode: SplitMode,
+    participants: Vec<ExpenseParticipant>,
+    original: Option<OriginalAmount>,
+    category: ExpenseCategory,
+    tags: Vec<String>,
+    receipt: Option<ExpenseReceipt>,
+    payer: Option<Pubkey>,
+    exclude_payer: bool,
 ) -> Result<()> {
     let owner = &mut ctx.accounts.owner;
     let session = &mut ctx.accounts.session;
@@ -52,29 +73,89 @@ pub fn add_expense(
         owner.key() == member.addr.key() && member.session_id == session.session_id,
         SolidrError::NotSessionMember
     );
-    require!(amount > 0.0, SolidrError::ExpenseAmountMustBeGreaterThanZero);
+    require!(member.role != MemberRole::Viewer, SolidrError::ForbiddenRole);
+
+    // only treasury roles can record expenses paid by other members
+    let payer = payer.unwrap_or(owner.key());
+    if payer != owner.key() {
+        require!(
+            member.has_role(&[MemberRole::Admin, MemberRole::Treasurer]),
+            SolidrError::ForbiddenRole
+        );
+        let payer_pda_address = get_member_pda_address(ctx.program_id, session.session_id, payer);
+        require!(
+            is_session_member(ctx.program_id, &ctx.remaining_accounts, payer_pda_address)?,
+            SolidrError::PayerNotMember
+        );
+    }
+
+    require!(amount > 0, SolidrError::ExpenseAmountMustBeGreaterThanZero);
     require!(name.len() <= 20, SolidrError::ExpenseNameTooLong);
+    ExpenseAccount::validate_tags(&tags)?;
+    ExpenseAccount::validate_receipt(&receipt)?;
+    if let Some(original) = &original {
+        validate_original_amount(original, amount, session.decimals)?;
+    }
 
     expense.session_id = session.session_id;
     expense.expense_id = session.expenses_count;
     expense.name = name;
     expense.date = clock::Clock::get().unwrap().unix_timestamp;
     expense.owner = owner.key();
+    expense.payer = payer;
     expense.amount = amount;
-
-    expense.participants = vec![owner.key()];
-
-    let _ = add_participants(
-        &ctx.program_id,
+    expense.version = ExpenseAccount::VERSION;
+
+    expense.participants = if exclude_payer {
+        vec![]
+    } else {
+        vec![ExpenseParticipant {
+            member: payer,
+            share: split_mode.default_share(),
+        }]
+    };
+    expense.split_mode = split_mode;
+    expense.original = original;
+    expense.category = category;
+    expense.tags = tags;
+    expense.receipt = receipt;
+
+    expense.budgeted = track_budget(
+        session,
+        ctx.accounts.budget.as_mut(),
+        expense.budgeted,
+        None,
+        Some((expense.category, expense.amount)),
+    )?;
+
+    add_participants(
+        ctx.program_id,
         &ctx.remaining_accounts,
         expense,
         participants,
+    )?;
+    require!(
+        !expense.participants.is_empty(),
+        SolidrError::ExpenseWithoutParticipants
     );
+    expense.validate_split()?;
+    request_approval(session, expense);
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
     session.expenses_count += 1;
 
     emit!(ExpenseAdded {
         session_id: session.session_id,
         expense_id: expense.expense_id,
+        amount: expense.amount,
+        original: expense.original.clone(),
+        status: expense.status,
+        category: expense.category,
+        tags: expense.tags.clone(),
+        payer: expense.payer,
     });
 
     Ok(())
@@ -90,12 +171,26 @@ pub struct UpdateExpenseContextData<'info> {
 
     #[account(mut)]
     pub expense: Account<'info, ExpenseAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BudgetAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub budget: Option<Account<'info, BudgetAccount>>,
 }
 
 pub fn update_expense(
     ctx: Context<UpdateExpenseContextData>,
     name: String,
-    amount: f32,
+    amount: u64,
+    participants: Option<Vec<ExpenseParticipant>>,
+    original: Option<OriginalAmount>,
+    category: ExpenseCategory,
+    tags: Vec<String>,
 ) -> Result<()> {
     let owner = &mut ctx.accounts.owner;
     let session = &mut ctx.accounts.session;
@@ -109,20 +204,93 @@ pub fn update_expense(
         owner.key() == expense.owner.key() && session.session_id == expense.session_id,
         SolidrError::NotExpenseOwner
     );
-    require!(amount > 0.0, SolidrError::ExpenseAmountMustBeGreaterThanZero);
+    require!(expense.dispute.is_none(), SolidrError::ExpenseDisputed);
+    require!(amount > 0, SolidrError::ExpenseAmountMustBeGreaterThanZero);
     require!(name.len() <= 20, SolidrError::ExpenseNameTooLong);
+    ExpenseAccount::validate_tags(&tags)?;
+    if let Some(original) = &original {
+        validate_original_amount(original, amount, session.decimals)?;
+    }
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.remove_expense(expense);
+    let previous = (expense.category, expense.amount);
 
     expense.name = name;
     expense.amount = amount;
+    expense.original = original;
+    expense.category = category;
+    expense.tags = tags;
+
+    expense.budgeted = track_budget(
+        session,
+        ctx.accounts.budget.as_mut(),
+        expense.budgeted,
+        Some(previous),
+        Some((expense.category, expense.amount)),
+    )?;
+
+    if let Some(participants) = participants {
+        add_participants(
+            ctx.program_id,
+            &ctx.remaining_accounts,
+            expense,
+            participants,
+        )?;
+    }
+    expense.validate_split()?;
+    request_approval(session, expense);
+
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
 
     emit!(ExpenseUpdated {
         session_id: session.session_id,
         expense_id: expense.expense_id,
+        category: expense.category,
+        tags: expense.tags.clone(),
     });
 
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct SetExpenseReceiptContextData<'info> {
+    pub owner: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(mut)]
+    pub expense: Account<'info, ExpenseAccount>,
+}
+
+pub fn set_expense_receipt(
+    ctx: Context<SetExpenseReceiptContextData>,
+    receipt: Option<ExpenseReceipt>,
+) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let expense = &mut ctx.accounts.expense;
+
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(
+        ctx.accounts.owner.key() == expense.owner && session.session_id == expense.session_id,
+        SolidrError::NotExpenseOwner
+    );
+    ExpenseAccount::validate_receipt(&receipt)?;
+
+    expense.receipt = receipt;
+
+    emit!(ExpenseReceiptUpdated {
+        session_id: session.session_id,
+        expense_id: expense.expense_id,
+        receipt: expense.receipt.clone(),
+    });
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct DeleteExpenseContextData<'info> {
     #[account(mut)]
@@ -133,6 +301,16 @@ pub struct DeleteExpenseContextData<'info> {
 
     #[account(mut, close = owner)]
     pub expense: Account<'info, ExpenseAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BudgetAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub budget: Option<Account<'info, BudgetAccount>>,
 }
 
 pub fn delete_expense(
@@ -142,18 +320,34 @@ pub fn delete_expense(
     let session = &mut ctx.accounts.session;
     let expense = &mut ctx.accounts.expense;
 
+    require!(
+        session.session_id == expense.session_id,
+        SolidrError::SessionMismatch
+    );
     if session.status == SessionStatus::Opened {
         require!(
             owner.key() == expense.owner.key() && session.session_id == expense.session_id,
             SolidrError::NotExpenseOwner
         );
-    }
-    if session.status == SessionStatus::Closed {
+    } else {
         require!(
             session.admin.key() == ctx.accounts.owner.key(),
             SolidrError::ForbiddenAsNonAdmin
         );
     }
+    require!(expense.dispute.is_none(), SolidrError::ExpenseDisputed);
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.remove_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
+    expense.budgeted = track_budget(
+        session,
+        ctx.accounts.budget.as_mut(),
+        expense.budgeted,
+        Some((expense.category, expense.amount)),
+        None,
+    )?;
 
     emit!(ExpenseDeleted {
         session_id: session.session_id,
@@ -179,24 +373,38 @@ pub struct AddExpenseParticipantContextData<'info> {
 
 pub fn add_expense_participants(
     ctx: Context<AddExpenseParticipantContextData>,
-    participants: Vec<Pubkey>,
+    participants: Vec<ExpenseParticipant>,
 ) -> Result<()> {
     let owner = &mut ctx.accounts.owner;
     let expense = &mut ctx.accounts.expense;
     let session = &mut ctx.accounts.session;
 
+    require!(
+        session.session_id == expense.session_id,
+        SolidrError::SessionMismatch
+    );
     require!(owner.key() == expense.owner, SolidrError::NotExpenseOwner);
+    require!(expense.dispute.is_none(), SolidrError::ExpenseDisputed);
 
     require!(
         session.status == SessionStatus::Opened,
         SolidrError::SessionClosed
     );
-    let _ = add_participants(
-        &ctx.program_id,
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.remove_expense(expense);
+
+    add_participants(
+        ctx.program_id,
         &ctx.remaining_accounts,
         expense,
         participants,
-    );
+    )?;
+    expense.validate_split()?;
+    request_approval(session, expense);
+
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
     Ok(())
 }
 
@@ -222,35 +430,319 @@ pub fn remove_expense_participants(
     let expense = &mut ctx.accounts.expense;
     let session = &mut ctx.accounts.session;
 
+    require!(
+        session.session_id == expense.session_id,
+        SolidrError::SessionMismatch
+    );
     require!(owner.key() == expense.owner, SolidrError::NotExpenseOwner);
+    require!(expense.dispute.is_none(), SolidrError::ExpenseDisputed);
 
     require!(
         session.status == SessionStatus::Opened,
         SolidrError::SessionClosed
     );
 
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.remove_expense(expense);
+
     let mut i = 0;
 
     while i < expense.participants.len() {
-        if participants.contains(&expense.participants[i]) {
+        if participants.contains(&expense.participants[i].member) {
             require!(
-                expense.participants[i].key() != expense.owner.key(),
+                expense.participants[i].member.key() != expense.owner.key(),
                 SolidrError::CannotRemoveExpenseOwner,
             );
             emit!(ExpenseParticipantRemoved {
                 session_id: expense.session_id,
                 expense_id: expense.expense_id,
-                member_pubkey: expense.participants[i],
+                member_pubkey: expense.participants[i].member,
             });
             expense.participants.remove(i);
         } else {
             i += 1;
         }
     }
+    require!(
+        !expense.participants.is_empty(),
+        SolidrError::ExpenseWithoutParticipants
+    );
+    expense.validate_split()?;
+    request_approval(session, expense);
+
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
     Ok(())
 }
 
-fn get_member_pda_address(program_id: &Pubkey, session_id: u64, member: Pubkey) -> Pubkey {
+#[derive(Accounts)]
+pub struct ReviewExpenseContextData<'info> {
+    pub signer: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(mut)]
+    pub expense: Account<'info, ExpenseAccount>,
+}
+
+pub fn approve_expense(ctx: Context<ReviewExpenseContextData>) -> Result<()> {
+    let signer = ctx.accounts.signer.key();
+    let session = &ctx.accounts.session;
+    let expense = &mut ctx.accounts.expense;
+
+    check_review(session, expense, &signer)?;
+    require!(
+        !expense.approvals.contains(&signer),
+        SolidrError::ExpenseAlreadyApproved
+    );
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.remove_expense(expense);
+
+    expense.approvals.push(signer);
+    if expense.is_approved(&session.expense_approval) {
+        expense.status = ExpenseStatus::Approved;
+    }
+
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
+    emit!(ExpenseApproved {
+        session_id: expense.session_id,
+        expense_id: expense.expense_id,
+        addr: signer,
+        status: expense.status,
+    });
+    Ok(())
+}
+
+pub fn reject_expense(ctx: Context<ReviewExpenseContextData>) -> Result<()> {
+    let signer = ctx.accounts.signer.key();
+    let session = &ctx.accounts.session;
+    let expense = &mut ctx.accounts.expense;
+
+    check_review(session, expense, &signer)?;
+
+    expense.status = ExpenseStatus::Rejected;
+
+    emit!(ExpenseRejected {
+        session_id: expense.session_id,
+        expense_id: expense.expense_id,
+        owner: expense.owner,
+        addr: signer,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DisputeExpenseContextData<'info> {
+    pub signer: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(mut)]
+    pub expense: Account<'info, ExpenseAccount>,
+}
+
+pub fn dispute_expense(ctx: Context<DisputeExpenseContextData>, reason: String) -> Result<()> {
+    let signer = ctx.accounts.signer.key();
+    let session = &ctx.accounts.session;
+    let expense = &mut ctx.accounts.expense;
+
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(
+        session.session_id == expense.session_id,
+        SolidrError::SessionMismatch
+    );
+    require!(
+        expense.is_participant(&signer),
+        SolidrError::NotExpenseParticipant
+    );
+    require!(expense.dispute.is_none(), SolidrError::ExpenseDisputed);
+    require!(
+        reason.len() <= ExpenseAccount::MAX_DISPUTE_REASON_LEN,
+        SolidrError::DisputeReasonTooLong
+    );
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.remove_expense(expense);
+
+    expense.dispute = Some(ExpenseDispute {
+        by: signer,
+        date: clock::Clock::get().unwrap().unix_timestamp,
+        reason: reason.clone(),
+    });
+
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
+    emit!(ExpenseDisputed {
+        session_id: expense.session_id,
+        expense_id: expense.expense_id,
+        addr: signer,
+        reason,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ResolveDisputeContextData<'info> {
+    pub signer: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(mut)]
+    pub expense: Account<'info, ExpenseAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BudgetAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub budget: Option<Account<'info, BudgetAccount>>,
+}
+
+pub fn resolve_dispute(
+    ctx: Context<ResolveDisputeContextData>,
+    amount: Option<u64>,
+    participants: Option<Vec<ExpenseParticipant>>,
+) -> Result<()> {
+    let signer = ctx.accounts.signer.key();
+    let session = &ctx.accounts.session;
+    let expense = &mut ctx.accounts.expense;
+
+    require!(
+        session.session_id == expense.session_id,
+        SolidrError::SessionMismatch
+    );
+    require!(
+        signer == expense.owner || ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::NotExpenseOwner
+    );
+    require!(expense.dispute.is_some(), SolidrError::ExpenseNotDisputed);
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.remove_expense(expense);
+
+    expense.dispute = None;
+
+    let amended = amount.is_some() || participants.is_some();
+    if amended {
+        require!(
+            session.status == SessionStatus::Opened,
+            SolidrError::SessionClosed
+        );
+
+        if let Some(amount) = amount {
+            require!(amount > 0, SolidrError::ExpenseAmountMustBeGreaterThanZero);
+            expense.budgeted = track_budget(
+                session,
+                ctx.accounts.budget.as_mut(),
+                expense.budgeted,
+                Some((expense.category, expense.amount)),
+                Some((expense.category, amount)),
+            )?;
+            expense.amount = amount;
+            // original amount no longer matches once amount is amended
+            expense.original = None;
+        }
+        if let Some(participants) = participants {
+            add_participants(
+                ctx.program_id,
+                &ctx.remaining_accounts,
+                expense,
+                participants,
+            )?;
+        }
+        expense.validate_split()?;
+        request_approval(session, expense);
+    }
+
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
+    emit!(ExpenseDisputeResolved {
+        session_id: expense.session_id,
+        expense_id: expense.expense_id,
+        addr: signer,
+        amended,
+    });
+    Ok(())
+}
+
+fn check_review(session: &SessionAccount, expense: &ExpenseAccount, signer: &Pubkey) -> Result<()> {
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(
+        session.session_id == expense.session_id,
+        SolidrError::SessionMismatch
+    );
+    require!(
+        expense.status == ExpenseStatus::Pending,
+        SolidrError::ExpenseNotPending
+    );
+    require!(
+        expense.is_participant(signer),
+        SolidrError::NotExpenseParticipant
+    );
+    Ok(())
+}
+
+/**
+ * Resets participants approvals, owner approval being implicit.
+ *
+ * @dev Expense is approved right away when session doesn't require approvals
+ */
+pub fn request_approval(session: &SessionAccount, expense: &mut ExpenseAccount) {
+    // owner recording an expense they don't take part in doesn't vouch for it
+    expense.approvals = if expense.is_participant(&expense.owner) {
+        vec![expense.owner]
+    } else {
+        vec![]
+    };
+    expense.status = if expense.is_approved(&session.expense_approval) {
+        ExpenseStatus::Approved
+    } else {
+        ExpenseStatus::Pending
+    };
+}
+
+fn validate_original_amount(
+    original: &OriginalAmount,
+    amount: u64,
+    session_decimals: u8,
+) -> Result<()> {
+    SessionAccount::validate_currency(&original.currency, original.decimals)?;
+    require!(
+        original.amount > 0 && original.exchange_rate > 0,
+        SolidrError::ExpenseAmountMustBeGreaterThanZero
+    );
+    require!(
+        original.convert(session_decimals)? == amount,
+        SolidrError::ExchangeRateMismatch
+    );
+    Ok(())
+}
+
+pub fn get_member_pda_address(program_id: &Pubkey, session_id: u64, member: Pubkey) -> Pubkey {
     let (pubkey, _) = Pubkey::find_program_address(
         &[
             b"member",
@@ -262,34 +754,60 @@ fn get_member_pda_address(program_id: &Pubkey, session_id: u64, member: Pubkey)
     pubkey
 }
 
+/**
+ * Adds new participants to expense or updates share of existing ones.
+ */
 fn add_participants(
     program_id: &Pubkey,
     remaining_accounts: &&[AccountInfo<'_>],
     expense: &mut Account<ExpenseAccount>,
-    participants: Vec<Pubkey>,
+    participants: Vec<ExpenseParticipant>,
 ) -> Result<()> {
-    require!(
-        expense.participants.len() < 20 - participants.len(),
-        SolidrError::MaxParticipantsReached
-    );
+    for participant in participants.into_iter() {
+        let share = match expense.split_mode {
+            SplitMode::Equal => 1,
+            _ => participant.share,
+        };
+
+        if let Some(existing) = expense
+            .participants
+            .iter_mut()
+            .find(|p| p.member == participant.member)
+        {
+            existing.share = share;
+
+            emit!(ExpenseParticipantUpdated {
+                session_id: expense.session_id,
+                expense_id: expense.expense_id,
+                member_pubkey: participant.member,
+                share,
+            });
+            continue;
+        }
 
-    for &participant in participants.iter() {
         let member_pda_address =
-            get_member_pda_address(&program_id, expense.session_id, participant);
+            get_member_pda_address(program_id, expense.session_id, participant.member);
 
-        if !is_session_member(&program_id, &remaining_accounts, member_pda_address)? {
+        if !is_session_member(program_id, remaining_accounts, member_pda_address)? {
             return Err(SolidrError::ParticipantNotMember.into());
         }
 
-        if !expense.participants.contains(&participant) {
-            expense.participants.push(participant);
+        require!(
+            expense.participants.len() < ExpenseAccount::MAX_PARTICIPANTS,
+            SolidrError::MaxParticipantsReached
+        );
 
-            emit!(ExpenseParticipantAdded {
-                session_id: expense.session_id,
-                expense_id: expense.expense_id,
-                member_pubkey: participant,
-            });
-        }
+        expense.participants.push(ExpenseParticipant {
+            member: participant.member,
+            share,
+        });
+
+        emit!(ExpenseParticipantAdded {
+            session_id: expense.session_id,
+            expense_id: expense.expense_id,
+            member_pubkey: participant.member,
+            share,
+        });
     }
     Ok(())
 }
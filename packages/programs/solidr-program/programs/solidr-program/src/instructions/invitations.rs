@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock;
+use sha2::{Digest, Sha256};
+
+use crate::errors::*;
+use crate::state::invitations::*;
+use crate::state::members::*;
+use crate::state::sessions::*;
+
+#[derive(Accounts)]
+pub struct CreateInvitationContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + InvitationAccount::INIT_SPACE,
+        seeds = [
+        InvitationAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        session.invitations_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub invitation: Account<'info, InvitationAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_invitation(
+    ctx: Context<CreateInvitationContextData>,
+    label: String,
+    token_hash: [u8; 32],
+    expires_at: i64,
+    max_uses: u16,
+    role: Option<MemberRole>,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let invitation = &mut ctx.accounts.invitation;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(
+        label.len() <= InvitationAccount::MAX_LABEL_LEN,
+        SolidrError::InvitationLabelTooLong
+    );
+    require!(
+        max_uses > 0 && token_hash.iter().any(|&x| x != 0),
+        SolidrError::InvalidInvitation
+    );
+    require!(
+        expires_at == 0 || expires_at > clock::Clock::get().unwrap().unix_timestamp,
+        SolidrError::InvitationExpired
+    );
+
+    invitation.session_id = session.session_id;
+    invitation.invitation_id = session.invitations_count;
+    invitation.token_hash = token_hash;
+    invitation.expires_at = expires_at;
+    invitation.max_uses = max_uses;
+    invitation.remaining_uses = max_uses;
+    invitation.revoked = false;
+    invitation.label = label;
+    invitation.creator = ctx.accounts.admin.key();
+    invitation.role = role;
+
+    session.invitations_count += 1;
+
+    emit!(InvitationCreated {
+        session_id: invitation.session_id,
+        invitation_id: invitation.invitation_id,
+        label: invitation.label.clone(),
+        creator: invitation.creator,
+        role,
+        expires_at,
+        max_uses,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct RevokeInvitationContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        InvitationAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        invitation.invitation_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub invitation: Account<'info, InvitationAccount>,
+}
+
+pub fn revoke_invitation(ctx: Context<RevokeInvitationContextData>) -> Result<()> {
+    let invitation = &mut ctx.accounts.invitation;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(!invitation.revoked, SolidrError::InvitationRevoked);
+
+    invitation.revoked = true;
+
+    emit!(InvitationRevoked {
+        session_id: invitation.session_id,
+        invitation_id: invitation.invitation_id,
+    });
+    Ok(())
+}
+
+/**
+ * Checks token against invitation limits and consumes one use.
+ */
+pub fn use_invitation(invitation: &mut InvitationAccount, token: &str) -> Result<()> {
+    require!(!invitation.revoked, SolidrError::InvitationRevoked);
+    require!(
+        invitation.expires_at == 0
+            || clock::Clock::get().unwrap().unix_timestamp < invitation.expires_at,
+        SolidrError::InvitationExpired
+    );
+    require!(
+        invitation.remaining_uses > 0,
+        SolidrError::InvitationExhausted
+    );
+
+    let mut hasher = Sha256::new();
+    hasher.update(token.as_bytes());
+    let hashed_token = hasher.finalize();
+
+    require!(
+        hashed_token.as_slice() == invitation.token_hash.as_slice(),
+        SolidrError::InvalidInvitationHash
+    );
+
+    invitation.remaining_uses -= 1;
+    Ok(())
+}
@@ -1,8 +1,14 @@
 use anchor_lang::prelude::*;
-use sha2::{Digest, Sha256};
 
 use crate::errors::*;
+use crate::instructions::balances::BalanceChanges;
+use crate::instructions::invitations::use_invitation;
+use crate::instructions::proposals::consume_approval;
+use crate::state::balances::*;
+use crate::state::expenses::*;
+use crate::state::invitations::InvitationAccount;
 use crate::state::members::*;
+use crate::state::proposals::*;
 use crate::state::sessions::*;
 
 #[derive(Accounts)]
@@ -14,6 +20,16 @@ pub struct AddSessionMemberContextData<'info> {
     #[account(mut)]
     pub session: Account<'info, SessionAccount>,
 
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
     #[account(
         init_if_needed,
         payer = admin,
@@ -27,6 +43,19 @@ pub struct AddSessionMemberContextData<'info> {
     )]
     pub member: Account<'info, MemberAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + BalanceAccount::INIT_SPACE,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        addr.key().as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -37,13 +66,14 @@ pub fn add_session_member(
 ) -> Result<()> {
     let session = &mut ctx.accounts.session;
     let member: &mut Account<MemberAccount> = &mut ctx.accounts.member;
+    let balance = &mut ctx.accounts.balance;
 
     require!(
-        session.admin.key() == ctx.accounts.admin.key(),
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
         SolidrError::ForbiddenAsNonAdmin
     );
 
-    add_member(addr, name, session, member)
+    add_member(addr, name, None, session, member, balance)
 }
 
 #[derive(Accounts)]
@@ -92,10 +122,44 @@ pub struct DeleteSessionMemberContextData<'info> {
     #[account(mut)]
     pub session: Account<'info, SessionAccount>,
 
-    #[account(mut, close = admin)]
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        member.addr.as_ref(),
+        ],
+        bump
+    )]
     pub member: Account<'info, MemberAccount>,
 
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        member.addr.as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
     pub system_program: Program<'info, System>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
 }
 
 pub fn delete_session_member(
@@ -103,15 +167,36 @@ pub fn delete_session_member(
 ) -> Result<()> {
     let session = &mut ctx.accounts.session;
     let member: &mut Account<MemberAccount> = &mut ctx.accounts.member;
+    let balance = &ctx.accounts.balance;
 
     require!(
-        session.admin.key() == ctx.accounts.admin.key(),
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
         SolidrError::ForbiddenAsNonAdmin
     );
     require!(
-        session.status == SessionStatus::Closed,
+        session.status != SessionStatus::Opened,
         SolidrError::SessionNotClosed
     );
+    require!(
+        balance.balance.unsigned_abs() <= SessionAccount::SETTLED_BALANCE_TOLERANCE,
+        SolidrError::MemberBalanceNotZero
+    );
+    require!(
+        balance.pending_count == 0,
+        SolidrError::MemberHasOutstandingExpenses
+    );
+    require!(
+        balance.expenses_count == 0,
+        SolidrError::MemberInvolvedInExpenses
+    );
+
+    consume_approval(
+        session,
+        ctx.accounts.proposal.as_mut(),
+        ProposalAction::DeleteMember { addr: member.addr },
+    )?;
+
+    session.members_count = session.members_count.saturating_sub(1);
 
     emit!(MemberDeleted {
         session_id: member.session_id,
@@ -121,6 +206,259 @@ pub fn delete_session_member(
     Ok(())
 }
 
+#[derive(Accounts)]
+pub struct RemoveSessionMemberContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        member.addr.as_ref(),
+        ],
+        bump
+    )]
+    pub member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        member.addr.as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        new_owner_member.addr.as_ref(),
+        ],
+        bump
+    )]
+    pub new_owner_member: Option<Account<'info, MemberAccount>>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
+}
+
+pub fn remove_session_member(
+    ctx: Context<RemoveSessionMemberContextData>,
+    expenses_count: u8,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let member = &ctx.accounts.member;
+    let balance = &mut ctx.accounts.balance;
+    let removed = member.addr;
+    let new_owner = ctx.accounts.new_owner_member.as_ref().map(|m| m.addr);
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(removed != session.admin, SolidrError::AdminCannotLeave);
+    require!(new_owner != Some(removed), SolidrError::MemberOwnsExpenses);
+
+    consume_approval(
+        session,
+        ctx.accounts.proposal.as_mut(),
+        ProposalAction::DeleteMember { addr: removed },
+    )?;
+
+    let (expense_infos, balance_infos) = ctx
+        .remaining_accounts
+        .split_at((expenses_count as usize).min(ctx.remaining_accounts.len()));
+
+    let mut balance_changes = BalanceChanges::default();
+
+    for expense_info in expense_infos {
+        require!(
+            expense_info.owner == ctx.program_id && expense_info.is_writable,
+            SolidrError::IncompleteExpensesList
+        );
+        let mut expense = {
+            let data = expense_info.try_borrow_data()?;
+            ExpenseAccount::try_deserialize(&mut &data[..])?
+        };
+        require!(
+            expense.session_id == session.session_id,
+            SolidrError::SessionMismatch
+        );
+        require!(
+            expense.owner == removed || expense.payer == removed || expense.is_participant(&removed),
+            SolidrError::NotExpenseParticipant
+        );
+
+        balance_changes.remove_expense(&expense);
+
+        if expense.owner == removed {
+            let new_owner = new_owner.ok_or(SolidrError::MemberOwnsExpenses)?;
+            expense.owner = new_owner;
+
+            emit!(ExpenseOwnerReassigned {
+                session_id: expense.session_id,
+                expense_id: expense.expense_id,
+                previous_owner: removed,
+                new_owner,
+            });
+        }
+        if expense.payer == removed {
+            let new_payer = new_owner.ok_or(SolidrError::MemberOwnsExpenses)?;
+            expense.payer = new_payer;
+
+            emit!(ExpensePayerReassigned {
+                session_id: expense.session_id,
+                expense_id: expense.expense_id,
+                previous_payer: removed,
+                new_payer,
+            });
+        }
+        if expense.is_participant(&removed) {
+            expense.strip_participant(&removed);
+
+            emit!(ExpenseParticipantRemoved {
+                session_id: expense.session_id,
+                expense_id: expense.expense_id,
+                member_pubkey: removed,
+            });
+        }
+        if matches!(&expense.dispute, Some(dispute) if dispute.by == removed) {
+            expense.dispute = None;
+        }
+        if expense.status == ExpenseStatus::Pending
+            && expense.is_approved(&session.expense_approval)
+        {
+            expense.status = ExpenseStatus::Approved;
+        }
+
+        balance_changes.add_expense(&expense);
+
+        let mut data = expense_info.try_borrow_mut_data()?;
+        let mut writer: &mut [u8] = &mut data;
+        expense.try_serialize(&mut writer)?;
+
+        emit!(ExpenseUpdated {
+            session_id: expense.session_id,
+            expense_id: expense.expense_id,
+            category: expense.category,
+            tags: expense.tags.clone(),
+        });
+    }
+
+    if let Some(change) = balance_changes.take(removed) {
+        change.apply_to(balance)?;
+    }
+    balance_changes.apply(ctx.program_id, balance_infos, session.session_id)?;
+
+    require!(
+        balance.expenses_count == 0,
+        SolidrError::IncompleteExpensesList
+    );
+    require!(balance.balance == 0, SolidrError::MemberBalanceNotZero);
+
+    if session.pending_admin == Some(removed) {
+        session.pending_admin = None;
+    }
+    session.members_count = session.members_count.saturating_sub(1);
+
+    emit!(MemberRemoved {
+        session_id: member.session_id,
+        addr: removed,
+        name: member.name.clone(),
+        expenses_count: expenses_count as u16,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LeaveSessionContextData<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        close = signer,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+}
+
+pub fn leave_session(ctx: Context<LeaveSessionContextData>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let member = &ctx.accounts.member;
+    let balance = &ctx.accounts.balance;
+
+    require!(
+        member.addr != session.admin,
+        SolidrError::AdminCannotLeave
+    );
+    require!(balance.balance == 0, SolidrError::MemberBalanceNotZero);
+    require!(
+        balance.pending_count == 0,
+        SolidrError::MemberHasOutstandingExpenses
+    );
+    require!(
+        balance.expenses_count == 0,
+        SolidrError::MemberInvolvedInExpenses
+    );
+
+    if session.pending_admin == Some(member.addr) {
+        session.pending_admin = None;
+    }
+    session.members_count = session.members_count.saturating_sub(1);
+
+    emit!(MemberLeft {
+        session_id: member.session_id,
+        addr: member.addr,
+        name: member.name.clone(),
+    });
+    Ok(())
+}
+
 #[derive(Accounts)]
 pub struct JoinSessionAsMemberContextData<'info> {
     #[account(mut)]
@@ -142,6 +480,30 @@ pub struct JoinSessionAsMemberContextData<'info> {
     )]
     pub member: Account<'info, MemberAccount>,
 
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + BalanceAccount::INIT_SPACE,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        InvitationAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        invitation.invitation_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub invitation: Account<'info, InvitationAccount>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -152,31 +514,105 @@ pub fn join_session_as_member(
 ) -> Result<()> {
     let signer = &ctx.accounts.signer;
 
-    let session = &ctx.accounts.session;
+    let session = &mut ctx.accounts.session;
     let member: &mut Account<MemberAccount> = &mut ctx.accounts.member;
+    let balance = &mut ctx.accounts.balance;
 
+    use_invitation(&mut ctx.accounts.invitation, &token)?;
+
+    add_member(
+        signer.key(),
+        name,
+        Some(&ctx.accounts.invitation),
+        session,
+        member,
+        balance,
+    )
+}
+
+#[derive(Accounts)]
+pub struct SetMemberRoleContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        member.addr.as_ref(),
+        ],
+        bump
+    )]
+    pub member: Account<'info, MemberAccount>,
+}
+
+pub fn grant_role(ctx: Context<SetMemberRoleContextData>, role: MemberRole) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let member = &mut ctx.accounts.member;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
     require!(
-        !session.invitation_hash.iter().all(|&x| x == 0),
-        SolidrError::MissingInvitationHash
+        member.addr != session.admin,
+        SolidrError::CannotChangeSessionAdminRole
     );
 
-    let mut hasher = Sha256::new();
-    hasher.update(token.as_bytes());
-    let hashed_token = hasher.finalize();
+    member.role = role;
+
+    emit!(RoleGranted {
+        session_id: member.session_id,
+        addr: member.addr,
+        role,
+    });
+    Ok(())
+}
+
+pub fn revoke_role(ctx: Context<SetMemberRoleContextData>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let member = &mut ctx.accounts.member;
 
     require!(
-        hashed_token.as_slice() == session.invitation_hash.as_slice(),
-        SolidrError::InvalidInvitationHash
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        member.addr != session.admin,
+        SolidrError::CannotChangeSessionAdminRole
     );
 
-    add_member(signer.key(), name, session, member)
+    let role = member.role;
+    member.role = MemberRole::Member;
+
+    emit!(RoleRevoked {
+        session_id: member.session_id,
+        addr: member.addr,
+        role,
+    });
+    Ok(())
 }
 
 pub fn add_member(
     addr: Pubkey,
     name: String,
-    session: &Account<SessionAccount>,
+    invitation: Option<&InvitationAccount>,
+    session: &mut Account<SessionAccount>,
     member: &mut Account<MemberAccount>,
+    balance: &mut Account<BalanceAccount>,
 ) -> Result<()> {
     require!(
         session.status == SessionStatus::Opened,
@@ -192,12 +628,29 @@ pub fn add_member(
     member.name.clone_from(&name);
     member.addr = addr.key();
     member.is_admin = addr.key() == session.admin.key();
+    member.role = match invitation.and_then(|invitation| invitation.role) {
+        _ if member.is_admin => MemberRole::Admin,
+        Some(role) => role,
+        None => MemberRole::Member,
+    };
+
+    balance.session_id = session.session_id;
+    balance.addr = addr.key();
+    balance.total_paid = 0;
+    balance.total_owed = 0;
+    balance.balance = 0;
+    balance.pending_count = 0;
+    balance.expenses_count = 0;
+
+    session.members_count += 1;
 
     emit!(MemberAdded {
         session_id: member.session_id,
         addr: member.addr,
         name,
         is_admin: member.is_admin,
+        role: member.role,
+        invitation_id: invitation.map(|invitation| invitation.invitation_id),
     });
     Ok(())
 }
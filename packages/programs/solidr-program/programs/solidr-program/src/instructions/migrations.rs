@@ -0,0 +1,379 @@
+use anchor_lang::{prelude::*, system_program, Discriminator};
+use pyth_solana_receiver_sdk::price_update::get_feed_id_from_hex;
+
+use crate::{
+    errors::*,
+    instructions::balances::BalanceChanges,
+    instructions::refunds::SOL_USD_FEED_ID,
+    state::{balances::*, expenses::*, members::*, refunds::*, sessions::*},
+};
+
+#[derive(Accounts)]
+pub struct MigrateSessionContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: legacy layout can't be deserialized as SessionAccount, discriminator and size are checked on migration
+    #[account(mut, owner = crate::ID)]
+    pub session: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_session(ctx: Context<MigrateSessionContextData>) -> Result<()> {
+    let session_info = ctx.accounts.session.to_account_info();
+
+    let legacy: SessionAccountV0 = read_legacy_account(
+        &session_info,
+        &SessionAccount::DISCRIMINATOR,
+        8 + SessionAccountV0::INIT_SPACE,
+    )?;
+
+    require!(
+        legacy.admin.key() == ctx.accounts.admin.key(),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+
+    let session = SessionAccount {
+        session_id: legacy.session_id,
+        name: legacy.name,
+        description: legacy.description,
+        admin: legacy.admin,
+        expenses_count: legacy.expenses_count,
+        refunds_count: legacy.refunds_count,
+        status: legacy.status,
+        invitation_hash: legacy.invitation_hash,
+        decimals: SessionAccount::DEFAULT_DECIMALS,
+        version: SessionAccount::VERSION,
+        mint: None,
+        mint_currency: String::new(),
+        currency: SessionAccount::DEFAULT_CURRENCY.to_string(),
+        price_feed_id: get_feed_id_from_hex(SOL_USD_FEED_ID)?,
+        members_count: 0,
+        pending_admin: None,
+        approval_threshold: 0,
+        proposals_count: 0,
+        expense_approval: ExpenseApproval::Disabled,
+        closed_at: 0,
+        reopen_window: 0,
+        invitations_count: 0,
+        recurring_count: 0,
+        budgeted: false,
+    };
+
+    write_migrated_account(
+        &session_info,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+        8 + SessionAccount::INIT_SPACE,
+        &session,
+    )?;
+
+    emit!(SessionMigrated {
+        session_id: session.session_id,
+        version: session.version,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateExpenseContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    /// CHECK: legacy layout can't be deserialized as ExpenseAccount, discriminator and size are checked on migration
+    #[account(mut, owner = crate::ID)]
+    pub expense: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_expense(ctx: Context<MigrateExpenseContextData>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let expense_info = ctx.accounts.expense.to_account_info();
+
+    require!(
+        session.admin.key() == ctx.accounts.admin.key(),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+
+    let legacy: ExpenseAccountV0 = read_legacy_account(
+        &expense_info,
+        &ExpenseAccount::DISCRIMINATOR,
+        8 + ExpenseAccountV0::INIT_SPACE,
+    )?;
+
+    require!(
+        legacy.session_id == session.session_id,
+        SolidrError::SessionMismatch
+    );
+
+    let expense = ExpenseAccount {
+        session_id: legacy.session_id,
+        expense_id: legacy.expense_id,
+        date: legacy.date,
+        owner: legacy.owner,
+        amount: to_minor_units(legacy.amount, session.decimals)?,
+        name: legacy.name,
+        participants: legacy
+            .participants
+            .into_iter()
+            .map(|member| ExpenseParticipant { member, share: 1 })
+            .collect(),
+        version: ExpenseAccount::VERSION,
+        split_mode: SplitMode::Equal,
+        original: None,
+        status: ExpenseStatus::Approved,
+        approvals: vec![],
+        dispute: None,
+        category: ExpenseCategory::Other,
+        tags: vec![],
+        budgeted: false,
+        receipt: None,
+        payer: legacy.owner,
+    };
+
+    write_migrated_account(
+        &expense_info,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+        8 + ExpenseAccount::INIT_SPACE,
+        &expense,
+    )?;
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.add_expense(&expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
+    emit!(ExpenseMigrated {
+        session_id: expense.session_id,
+        expense_id: expense.expense_id,
+        version: expense.version,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MigrateRefundContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    /// CHECK: legacy layout can't be deserialized as RefundAccount, discriminator and size are checked on migration
+    #[account(mut, owner = crate::ID)]
+    pub refund: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_refund(ctx: Context<MigrateRefundContextData>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let refund_info = ctx.accounts.refund.to_account_info();
+
+    require!(
+        session.admin.key() == ctx.accounts.admin.key(),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+
+    let legacy: RefundAccountV0 = read_legacy_account(
+        &refund_info,
+        &RefundAccount::DISCRIMINATOR,
+        8 + RefundAccountV0::INIT_SPACE,
+    )?;
+
+    require!(
+        legacy.session_id == session.session_id,
+        SolidrError::SessionMismatch
+    );
+
+    let refund = RefundAccount {
+        session_id: legacy.session_id,
+        refund_id: legacy.refund_id,
+        date: legacy.date,
+        from: legacy.from,
+        to: legacy.to,
+        amount: to_minor_units(legacy.amount, session.decimals)?,
+        amount_in_lamports: legacy.amount_in_lamports,
+        version: RefundAccount::VERSION,
+        mint: None,
+        token_amount: 0,
+        settlement_index: None,
+        settlement_plan_id: 0,
+    };
+
+    write_migrated_account(
+        &refund_info,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+        8 + RefundAccount::INIT_SPACE,
+        &refund,
+    )?;
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.push(refund.from, refund.amount as i128, 0);
+    balance_changes.push(refund.to, 0, refund.amount as i128);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
+    emit!(RefundMigrated {
+        session_id: refund.session_id,
+        refund_id: refund.refund_id,
+        version: refund.version,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(addr: Pubkey)]
+pub struct MigrateMemberContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    /// CHECK: legacy layout can't be deserialized as MemberAccount, discriminator and size are checked on migration
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        addr.as_ref(),
+        ],
+        bump
+    )]
+    pub member: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + BalanceAccount::INIT_SPACE,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        addr.as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn migrate_member(ctx: Context<MigrateMemberContextData>, addr: Pubkey) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let member_info = ctx.accounts.member.to_account_info();
+
+    require!(
+        session.admin.key() == ctx.accounts.admin.key(),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+
+    let legacy: MemberAccountV0 = read_legacy_account(
+        &member_info,
+        &MemberAccount::DISCRIMINATOR,
+        8 + MemberAccountV0::INIT_SPACE,
+    )?;
+
+    require!(
+        legacy.session_id == session.session_id && legacy.addr == addr,
+        SolidrError::SessionMismatch
+    );
+
+    let member = MemberAccount {
+        session_id: legacy.session_id,
+        addr: legacy.addr,
+        name: legacy.name,
+        is_admin: legacy.is_admin,
+        role: if legacy.is_admin {
+            MemberRole::Admin
+        } else {
+            MemberRole::Member
+        },
+    };
+
+    write_migrated_account(
+        &member_info,
+        &ctx.accounts.admin,
+        &ctx.accounts.system_program,
+        8 + MemberAccount::INIT_SPACE,
+        &member,
+    )?;
+
+    let balance = &mut ctx.accounts.balance;
+    balance.session_id = session.session_id;
+    balance.addr = member.addr;
+    balance.total_paid = 0;
+    balance.total_owed = 0;
+    balance.balance = 0;
+
+    session.members_count += 1;
+
+    emit!(MemberMigrated {
+        session_id: member.session_id,
+        addr: member.addr,
+    });
+    Ok(())
+}
+
+/**
+ * Converts a legacy floating point amount into minor units of the session currency.
+ */
+pub fn to_minor_units(amount: f32, decimals: u8) -> Result<u64> {
+    let scaled = (amount as f64 * 10f64.powi(decimals as i32)).round();
+    require!(
+        scaled >= 0.0 && scaled <= u64::MAX as f64,
+        SolidrError::Overflow
+    );
+    Ok(scaled as u64)
+}
+
+fn read_legacy_account<T: AnchorDeserialize>(
+    account: &AccountInfo,
+    discriminator: &[u8],
+    legacy_len: usize,
+) -> Result<T> {
+    let data = account.try_borrow_data()?;
+
+    require!(
+        data.len() >= 8 && &data[..8] == discriminator,
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    require!(
+        data.len() == legacy_len,
+        SolidrError::AccountAlreadyMigrated
+    );
+
+    T::deserialize(&mut &data[8..]).map_err(|_| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+fn write_migrated_account<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    new_len: usize,
+    migrated: &T,
+) -> Result<()> {
+    let minimum_balance = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+
+    if minimum_balance > lamports {
+        let cpi_context = CpiContext::new(
+            system_program.to_account_info(),
+            system_program::Transfer {
+                from: payer.to_account_info(),
+                to: account.clone(),
+            },
+        );
+        system_program::transfer(cpi_context, minimum_balance - lamports)?;
+    }
+
+    account.realloc(new_len, true)?;
+
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    migrated.try_serialize(&mut writer)
+}
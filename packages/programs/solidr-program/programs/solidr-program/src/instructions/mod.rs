@@ -1,5 +1,13 @@
+pub mod balances;
+pub mod budgets;
 pub mod expenses;
 pub mod global;
+pub mod invitations;
 pub mod members;
+pub mod migrations;
+pub mod proposals;
+pub mod recurring;
 pub mod refunds;
 pub mod sessions;
+pub mod settlements;
+pub mod vaults;
@@ -0,0 +1,235 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock;
+
+use crate::errors::*;
+use crate::state::members::*;
+use crate::state::proposals::*;
+use crate::state::sessions::*;
+
+#[derive(Accounts)]
+pub struct SetApprovalThresholdContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
+}
+
+pub fn set_approval_threshold(
+    ctx: Context<SetApprovalThresholdContextData>,
+    threshold: u8,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(
+        threshold as usize <= ProposalAccount::MAX_APPROVALS
+            && threshold as u16 <= session.members_count,
+        SolidrError::InvalidApprovalThreshold
+    );
+
+    // current threshold applies, so that approvals can't be bypassed by disabling them
+    consume_approval(
+        session,
+        ctx.accounts.proposal.as_mut(),
+        ProposalAction::SetApprovalThreshold { threshold },
+    )?;
+
+    session.approval_threshold = threshold;
+
+    emit!(ApprovalThresholdUpdated {
+        session_id: session.session_id,
+        threshold,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CreateProposalContextData<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        proposer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        init,
+        payer = proposer,
+        space = 8 + ProposalAccount::INIT_SPACE,
+        seeds = [
+        ProposalAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        session.proposals_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, ProposalAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_proposal(
+    ctx: Context<CreateProposalContextData>,
+    action: ProposalAction,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let proposal = &mut ctx.accounts.proposal;
+
+    require!(
+        ctx.accounts.signer_member.has_role(action.allowed_roles()),
+        SolidrError::ForbiddenRole
+    );
+
+    proposal.session_id = session.session_id;
+    proposal.proposal_id = session.proposals_count;
+    proposal.date = clock::Clock::get().unwrap().unix_timestamp;
+    proposal.proposer = ctx.accounts.proposer.key();
+    proposal.action = action;
+    proposal.approvals = vec![proposal.proposer];
+    proposal.executed = false;
+
+    session.proposals_count += 1;
+
+    emit!(ProposalCreated {
+        session_id: proposal.session_id,
+        proposal_id: proposal.proposal_id,
+        action: proposal.action.clone(),
+    });
+    emit!(ProposalApproved {
+        session_id: proposal.session_id,
+        proposal_id: proposal.proposal_id,
+        addr: proposal.proposer,
+        approvals_count: 1,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposalContextData<'info> {
+    pub signer: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        ProposalAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        proposal.proposal_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub proposal: Account<'info, ProposalAccount>,
+}
+
+pub fn approve_proposal(ctx: Context<ApproveProposalContextData>) -> Result<()> {
+    let proposal = &mut ctx.accounts.proposal;
+    let signer = ctx.accounts.signer.key();
+
+    require!(!proposal.executed, SolidrError::ProposalAlreadyExecuted);
+    require!(
+        ctx.accounts.signer_member.role != MemberRole::Viewer,
+        SolidrError::ForbiddenRole
+    );
+    require!(!proposal.has_approved(&signer), SolidrError::AlreadyApproved);
+    require!(
+        proposal.approvals.len() < ProposalAccount::MAX_APPROVALS,
+        SolidrError::InvalidApprovalThreshold
+    );
+
+    proposal.approvals.push(signer);
+
+    emit!(ProposalApproved {
+        session_id: proposal.session_id,
+        proposal_id: proposal.proposal_id,
+        addr: signer,
+        approvals_count: proposal.approvals.len() as u8,
+    });
+    Ok(())
+}
+
+/**
+ * Ensures action has been approved when session requires it, marking the proposal as executed.
+ *
+ * @dev Without approval threshold, actions are executed directly and proposal is ignored
+ */
+pub fn consume_approval(
+    session: &SessionAccount,
+    proposal: Option<&mut Account<ProposalAccount>>,
+    action: ProposalAction,
+) -> Result<()> {
+    if session.approval_threshold == 0 {
+        return Ok(());
+    }
+    consume_proposal(session, proposal, action, session.approval_threshold as usize)
+}
+
+/**
+ * Ensures action has been approved by given number of members, marking the proposal as executed.
+ */
+pub fn consume_proposal(
+    session: &SessionAccount,
+    proposal: Option<&mut Account<ProposalAccount>>,
+    action: ProposalAction,
+    threshold: usize,
+) -> Result<()> {
+    let proposal = proposal.ok_or(SolidrError::ApprovalRequired)?;
+
+    require!(
+        proposal.session_id == session.session_id && proposal.action == action,
+        SolidrError::ProposalMismatch
+    );
+    require!(!proposal.executed, SolidrError::ProposalAlreadyExecuted);
+    require!(
+        proposal.approvals.len() >= threshold,
+        SolidrError::ApprovalRequired
+    );
+
+    proposal.executed = true;
+
+    emit!(ProposalExecuted {
+        session_id: proposal.session_id,
+        proposal_id: proposal.proposal_id,
+    });
+    Ok(())
+}
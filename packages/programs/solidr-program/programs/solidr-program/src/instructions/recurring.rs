@@ -0,0 +1,380 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock;
+
+use crate::errors::*;
+use crate::instructions::balances::BalanceChanges;
+use crate::instructions::budgets::track_budget;
+use crate::instructions::expenses::{get_member_pda_address, request_approval};
+use crate::is_session_member;
+use crate::state::budgets::BudgetAccount;
+use crate::state::expenses::*;
+use crate::state::members::*;
+use crate::state::recurring::*;
+use crate::state::sessions::*;
+
+#[derive(Accounts)]
+pub struct CreateRecurringExpenseContextData<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        owner.key().as_ref(),
+        ],
+        bump
+    )]
+    pub member: Account<'info, MemberAccount>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + RecurringExpenseAccount::INIT_SPACE,
+        seeds = [
+        RecurringExpenseAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        session.recurring_count.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub recurring: Account<'info, RecurringExpenseAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn create_recurring_expense(
+    ctx: Context<CreateRecurringExpenseContextData>,
+    name: String,
+    amount: u64,
+    split_mode: SplitMode,
+    participants: Vec<ExpenseParticipant>,
+    interval: i64,
+    first_due: i64,
+    category: ExpenseCategory,
+    tags: Vec<String>,
+) -> Result<()> {
+    let owner = ctx.accounts.owner.key();
+    let session = &mut ctx.accounts.session;
+    let recurring = &mut ctx.accounts.recurring;
+
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(
+        ctx.accounts.member.role != MemberRole::Viewer,
+        SolidrError::ForbiddenRole
+    );
+    require!(amount > 0, SolidrError::ExpenseAmountMustBeGreaterThanZero);
+    require!(name.len() <= 20, SolidrError::ExpenseNameTooLong);
+    require!(
+        interval >= RecurringExpenseAccount::MIN_INTERVAL,
+        SolidrError::InvalidRecurringInterval
+    );
+    require!(
+        first_due >= clock::Clock::get().unwrap().unix_timestamp,
+        SolidrError::RecurringFirstDueInPast
+    );
+    ExpenseAccount::validate_tags(&tags)?;
+
+    let mut template_participants = vec![ExpenseParticipant {
+        member: owner,
+        share: split_mode.default_share(),
+    }];
+    for participant in participants.into_iter() {
+        let share = match split_mode {
+            SplitMode::Equal => 1,
+            _ => participant.share,
+        };
+        if let Some(existing) = template_participants
+            .iter_mut()
+            .find(|p| p.member == participant.member)
+        {
+            existing.share = share;
+            continue;
+        }
+
+        let member_pda_address =
+            get_member_pda_address(ctx.program_id, session.session_id, participant.member);
+        require!(
+            is_session_member(ctx.program_id, &ctx.remaining_accounts, member_pda_address)?,
+            SolidrError::ParticipantNotMember
+        );
+        require!(
+            template_participants.len() < ExpenseAccount::MAX_PARTICIPANTS,
+            SolidrError::MaxParticipantsReached
+        );
+        template_participants.push(ExpenseParticipant {
+            member: participant.member,
+            share,
+        });
+    }
+    split_mode.validate(amount, &template_participants)?;
+
+    recurring.session_id = session.session_id;
+    recurring.recurring_id = session.recurring_count;
+    recurring.owner = owner;
+    recurring.name = name;
+    recurring.amount = amount;
+    recurring.split_mode = split_mode;
+    recurring.participants = template_participants;
+    recurring.interval = interval;
+    recurring.next_due = first_due;
+    recurring.status = RecurringStatus::Active;
+    recurring.category = category;
+    recurring.tags = tags;
+
+    session.recurring_count += 1;
+
+    emit!(RecurringExpenseCreated {
+        session_id: recurring.session_id,
+        recurring_id: recurring.recurring_id,
+        amount,
+        interval,
+        next_due: recurring.next_due,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct MaterializeRecurringExpenseContextData<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        RecurringExpenseAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        recurring.recurring_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub recurring: Account<'info, RecurringExpenseAccount>,
+
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + ExpenseAccount::INIT_SPACE,
+        seeds = [
+        ExpenseAccount::SEED_PREFIX.as_ref(),
+        session.session_id.to_le_bytes().as_ref(),
+        session.expenses_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub expense: Account<'info, ExpenseAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BudgetAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub budget: Option<Account<'info, BudgetAccount>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn materialize_recurring_expense(
+    ctx: Context<MaterializeRecurringExpenseContextData>,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let recurring = &mut ctx.accounts.recurring;
+    let expense = &mut ctx.accounts.expense;
+    let now = clock::Clock::get().unwrap().unix_timestamp;
+
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(
+        recurring.status == RecurringStatus::Active,
+        SolidrError::RecurringExpensePaused
+    );
+    require!(now >= recurring.next_due, SolidrError::RecurringExpenseNotDue);
+
+    expense.session_id = session.session_id;
+    expense.expense_id = session.expenses_count;
+    expense.name.clone_from(&recurring.name);
+    expense.date = now;
+    expense.owner = recurring.owner;
+    expense.payer = recurring.owner;
+    expense.amount = recurring.amount;
+    expense.version = ExpenseAccount::VERSION;
+    expense.participants.clone_from(&recurring.participants);
+    expense.split_mode = recurring.split_mode.clone();
+    expense.original = None;
+    expense.dispute = None;
+    expense.category = recurring.category;
+    expense.tags.clone_from(&recurring.tags);
+    expense.receipt = None;
+    request_approval(session, expense);
+
+    expense.budgeted = track_budget(
+        session,
+        ctx.accounts.budget.as_mut(),
+        expense.budgeted,
+        None,
+        Some((expense.category, expense.amount)),
+    )?;
+
+    let mut balance_changes = BalanceChanges::default();
+    balance_changes.add_expense(expense);
+    balance_changes.apply(ctx.program_id, ctx.remaining_accounts, session.session_id)?;
+
+    session.expenses_count += 1;
+    recurring.next_due += recurring.interval;
+
+    emit!(ExpenseAdded {
+        session_id: session.session_id,
+        expense_id: expense.expense_id,
+        amount: expense.amount,
+        original: None,
+        status: expense.status,
+        category: expense.category,
+        tags: expense.tags.clone(),
+        payer: expense.payer,
+    });
+    emit!(RecurringExpenseMaterialized {
+        session_id: recurring.session_id,
+        recurring_id: recurring.recurring_id,
+        expense_id: expense.expense_id,
+        next_due: recurring.next_due,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct UpdateRecurringExpenseContextData<'info> {
+    pub signer: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        RecurringExpenseAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        recurring.recurring_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub recurring: Account<'info, RecurringExpenseAccount>,
+}
+
+pub fn pause_recurring_expense(ctx: Context<UpdateRecurringExpenseContextData>) -> Result<()> {
+    let recurring = &mut ctx.accounts.recurring;
+
+    require!(
+        ctx.accounts.signer.key() == recurring.owner
+            || ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::NotExpenseOwner
+    );
+    require!(
+        recurring.status == RecurringStatus::Active,
+        SolidrError::RecurringExpensePaused
+    );
+
+    recurring.status = RecurringStatus::Paused;
+
+    emit!(RecurringExpensePaused {
+        session_id: recurring.session_id,
+        recurring_id: recurring.recurring_id,
+    });
+    Ok(())
+}
+
+pub fn resume_recurring_expense(ctx: Context<UpdateRecurringExpenseContextData>) -> Result<()> {
+    let recurring = &mut ctx.accounts.recurring;
+
+    require!(
+        ctx.accounts.signer.key() == recurring.owner
+            || ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::NotExpenseOwner
+    );
+    require!(
+        recurring.status == RecurringStatus::Paused,
+        SolidrError::RecurringExpenseNotPaused
+    );
+
+    // occurrences due while paused are not materialized
+    recurring.skip_missed(clock::Clock::get().unwrap().unix_timestamp);
+    recurring.status = RecurringStatus::Active;
+
+    emit!(RecurringExpenseResumed {
+        session_id: recurring.session_id,
+        recurring_id: recurring.recurring_id,
+        next_due: recurring.next_due,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CancelRecurringExpenseContextData<'info> {
+    pub signer: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    /// CHECK: safe as only receiving template rent and ensured to be template owner
+    #[account(mut, address = recurring.owner)]
+    pub owner: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [
+        RecurringExpenseAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        recurring.recurring_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub recurring: Account<'info, RecurringExpenseAccount>,
+}
+
+pub fn cancel_recurring_expense(ctx: Context<CancelRecurringExpenseContextData>) -> Result<()> {
+    let recurring = &ctx.accounts.recurring;
+
+    require!(
+        ctx.accounts.signer.key() == recurring.owner
+            || ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::NotExpenseOwner
+    );
+
+    emit!(RecurringExpenseCancelled {
+        session_id: recurring.session_id,
+        recurring_id: recurring.recurring_id,
+    });
+    Ok(())
+}
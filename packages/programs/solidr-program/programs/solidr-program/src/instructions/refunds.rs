@@ -1,10 +1,26 @@
 use anchor_lang::solana_program::clock;
+use anchor_lang::solana_program::native_token::LAMPORTS_PER_SOL;
 use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use pyth_solana_receiver_sdk::price_update::{Price, PriceUpdateV2};
 
-use crate::state::members::MemberAccount;
+use crate::instructions::proposals::consume_approval;
+use crate::instructions::settlements::{fulfill_settlement_transfer, revert_settlement_transfer};
+use crate::state::balances::BalanceAccount;
+use crate::state::members::{MemberAccount, MemberRole};
+use crate::state::proposals::{ProposalAccount, ProposalAction};
 use crate::state::refunds::*;
+use crate::state::settlements::SettlementAccount;
 use crate::{errors::*, state::sessions::*};
 
+pub const SOL_USD_FEED_ID: &str =
+    "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+pub const MAX_PRICE_AGE: u64 = 60;
+pub const MAX_PRICE_CONFIDENCE_BPS: u64 = 200;
+pub const BPS_DENOMINATOR: u64 = 10_000;
+
 #[derive(Accounts)]
 pub struct RefundContextData<'info> {
     /// CHECK: safe as used only for transfer and ensured belonging to session member
@@ -24,6 +40,40 @@ pub struct RefundContextData<'info> {
     #[account(mut)]
     pub session: Account<'info, SessionAccount>,
 
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        from_addr.key().as_ref(),
+        ],
+        bump
+    )]
+    pub sender_balance: Account<'info, BalanceAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        to_addr.key().as_ref(),
+        ],
+        bump
+    )]
+    pub receiver_balance: Account<'info, BalanceAccount>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        mut,
+        seeds = [
+        SettlementAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub settlement: Option<Account<'info, SettlementAccount>>,
+
     #[account(
         init,
         payer = from_addr,
@@ -42,8 +92,10 @@ pub struct RefundContextData<'info> {
 
 pub fn add_refund(
     ctx: Context<RefundContextData>,
-    amount: f32,
-    amount_in_lamports: u64,
+    amount: u64,
+    expected_lamports: u64,
+    max_slippage_bps: u16,
+    settlement_index: Option<u16>,
 ) -> Result<()> {
     let session = &mut ctx.accounts.session;
     let refund = &mut ctx.accounts.refund;
@@ -62,7 +114,17 @@ pub fn add_refund(
         to_addr.key() == receiver.addr.key() && receiver.session_id == session.session_id,
         SolidrError::NotSessionMember
     );
-    require!(amount > 0.0, SolidrError::RefundAmountMustBeGreaterThanZero);
+    require!(from_addr.key() != to_addr.key(), SolidrError::RefundToSelf);
+    require!(amount > 0, SolidrError::RefundAmountMustBeGreaterThanZero);
+
+    let price = get_sol_price(&ctx.accounts.price_update, &session.price_feed_id)?;
+    let amount_in_lamports = amount_to_lamports(amount, session.decimals, &price)?;
+
+    let tolerance = expected_lamports as u128 * max_slippage_bps as u128 / BPS_DENOMINATOR as u128;
+    require!(
+        (amount_in_lamports as u128).abs_diff(expected_lamports as u128) <= tolerance,
+        SolidrError::SlippageExceeded
+    );
 
     let cpi_context = CpiContext::new(
         ctx.accounts.system_program.to_account_info(),
@@ -80,6 +142,192 @@ pub fn add_refund(
     refund.to = to_addr.key();
     refund.amount = amount;
     refund.amount_in_lamports = amount_in_lamports;
+    refund.version = RefundAccount::VERSION;
+    refund.mint = None;
+    refund.token_amount = 0;
+    refund.settlement_index = settlement_index;
+    refund.settlement_plan_id = 0;
+
+    ctx.accounts.sender_balance.apply(amount as i128, 0)?;
+    ctx.accounts.receiver_balance.apply(0, amount as i128)?;
+
+    if let Some(index) = settlement_index {
+        let settlement = ctx
+            .accounts
+            .settlement
+            .as_mut()
+            .ok_or(SolidrError::InvalidSettlementTransfer)?;
+        fulfill_settlement_transfer(settlement, index, from_addr.key(), to_addr.key(), amount)?;
+        refund.settlement_plan_id = settlement.plan_id;
+    }
+
+    session.refunds_count += 1;
+
+    emit!(RefundAdded {
+        session_id: session.session_id,
+        refund_id: refund.refund_id,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct TokenRefundContextData<'info> {
+    #[account(mut)]
+    pub from_addr: Signer<'info>,
+
+    #[account(mut)]
+    pub sender: Account<'info, MemberAccount>,
+
+    /// CHECK: safe as used only as token account authority and ensured belonging to session member
+    pub to_addr: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub receiver: Account<'info, MemberAccount>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        from_addr.key().as_ref(),
+        ],
+        bump
+    )]
+    pub sender_balance: Account<'info, BalanceAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        to_addr.key().as_ref(),
+        ],
+        bump
+    )]
+    pub receiver_balance: Account<'info, BalanceAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = from_addr,
+        token::token_program = token_program,
+    )]
+    pub from_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = to_addr,
+        token::token_program = token_program,
+    )]
+    pub to_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        SettlementAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub settlement: Option<Account<'info, SettlementAccount>>,
+
+    #[account(
+        init,
+        payer = from_addr,
+        space = 8 + RefundAccount::INIT_SPACE,
+        seeds = [
+        RefundAccount::SEED_PREFIX.as_ref(),
+        session.session_id.to_le_bytes().as_ref(),
+        session.refunds_count.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub refund: Account<'info, RefundAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn add_token_refund(
+    ctx: Context<TokenRefundContextData>,
+    amount: u64,
+    settlement_index: Option<u16>,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let refund = &mut ctx.accounts.refund;
+    let mint = &ctx.accounts.mint;
+
+    let from_addr = &ctx.accounts.from_addr;
+    let sender = &ctx.accounts.sender;
+
+    let to_addr = &ctx.accounts.to_addr;
+    let receiver = &ctx.accounts.receiver;
+
+    require!(
+        from_addr.key() == sender.addr.key() && sender.session_id == session.session_id,
+        SolidrError::NotSessionMember
+    );
+    require!(
+        to_addr.key() == receiver.addr.key() && receiver.session_id == session.session_id,
+        SolidrError::NotSessionMember
+    );
+    require!(from_addr.key() != to_addr.key(), SolidrError::RefundToSelf);
+    require!(
+        session.mint == Some(mint.key()),
+        SolidrError::InvalidRefundMint
+    );
+    require!(
+        session.mint_currency == session.currency,
+        SolidrError::MintCurrencyMismatch
+    );
+    require!(amount > 0, SolidrError::RefundAmountMustBeGreaterThanZero);
+
+    let token_amount = amount_to_token_amount(amount, session.decimals, mint.decimals)?;
+
+    let cpi_context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.from_token_account.to_account_info(),
+            mint: mint.to_account_info(),
+            to: ctx.accounts.to_token_account.to_account_info(),
+            authority: from_addr.to_account_info(),
+        },
+    );
+    transfer_checked(cpi_context, token_amount, mint.decimals)?;
+
+    refund.session_id = session.session_id;
+    refund.refund_id = session.refunds_count;
+    refund.date = clock::Clock::get().unwrap().unix_timestamp;
+    refund.from = from_addr.key();
+    refund.to = to_addr.key();
+    refund.amount = amount;
+    refund.amount_in_lamports = 0;
+    refund.version = RefundAccount::VERSION;
+    refund.mint = Some(mint.key());
+    refund.token_amount = token_amount;
+    refund.settlement_index = settlement_index;
+    refund.settlement_plan_id = 0;
+
+    ctx.accounts.sender_balance.apply(amount as i128, 0)?;
+    ctx.accounts.receiver_balance.apply(0, amount as i128)?;
+
+    if let Some(index) = settlement_index {
+        let settlement = ctx
+            .accounts
+            .settlement
+            .as_mut()
+            .ok_or(SolidrError::InvalidSettlementTransfer)?;
+        fulfill_settlement_transfer(settlement, index, from_addr.key(), to_addr.key(), amount)?;
+        refund.settlement_plan_id = settlement.plan_id;
+    }
 
     session.refunds_count += 1;
 
@@ -99,10 +347,64 @@ pub struct DeleteRefundContextData<'info> {
     #[account(mut)]
     pub session: Account<'info, SessionAccount>,
 
-    #[account(mut, close = admin)]
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+        RefundAccount::SEED_PREFIX.as_ref(),
+        session.session_id.to_le_bytes().as_ref(),
+        refund.refund_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
     pub refund: Account<'info, RefundAccount>,
 
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        refund.from.as_ref(),
+        ],
+        bump
+    )]
+    pub sender_balance: Account<'info, BalanceAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        refund.to.as_ref(),
+        ],
+        bump
+    )]
+    pub receiver_balance: Account<'info, BalanceAccount>,
+
     pub system_program: Program<'info, System>,
+
+    #[account(
+        mut,
+        seeds = [
+        SettlementAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub settlement: Option<Account<'info, SettlementAccount>>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
 }
 
 pub fn delete_refund(ctx: Context<DeleteRefundContextData>) -> Result<()> {
@@ -110,17 +412,111 @@ pub fn delete_refund(ctx: Context<DeleteRefundContextData>) -> Result<()> {
     let refund = &mut ctx.accounts.refund;
 
     require!(
-        session.admin.key() == ctx.accounts.admin.key(),
-        SolidrError::ForbiddenAsNonAdmin
+        ctx.accounts.signer_member
+            .has_role(&[MemberRole::Admin, MemberRole::Treasurer]),
+        SolidrError::ForbiddenRole
     );
     require!(
-        session.status == SessionStatus::Closed,
+        session.status != SessionStatus::Opened,
         SolidrError::SessionNotClosed
     );
 
+    consume_approval(
+        session,
+        ctx.accounts.proposal.as_mut(),
+        ProposalAction::DeleteRefund {
+            refund_id: refund.refund_id,
+        },
+    )?;
+
+    ctx.accounts.sender_balance.apply(-(refund.amount as i128), 0)?;
+    ctx.accounts.receiver_balance.apply(0, -(refund.amount as i128))?;
+
+    if let Some(index) = refund.settlement_index {
+        let settlement = ctx
+            .accounts
+            .settlement
+            .as_mut()
+            .ok_or(SolidrError::InvalidSettlementTransfer)?;
+        // a plan recomputed after the refund already accounts for it and doesn't hold the fulfilled transfer anymore
+        if settlement.plan_id == refund.settlement_plan_id {
+            revert_settlement_transfer(settlement, index, refund.from, refund.to, refund.amount)?;
+        }
+    }
+
     emit!(RefundDeleted {
         session_id: session.session_id,
         refund_id: refund.refund_id,
     });
     Ok(())
 }
+
+/**
+ * Rescales an amount in minor units of session currency into raw units of session's token.
+ *
+ * @dev Amounts that can't be represented exactly with mint decimals are rejected
+ */
+pub fn amount_to_token_amount(amount: u64, decimals: u8, mint_decimals: u8) -> Result<u64> {
+    let token_amount = if mint_decimals >= decimals {
+        (amount as u128)
+            .checked_mul(10u128.pow((mint_decimals - decimals) as u32))
+            .ok_or(SolidrError::Overflow)?
+    } else {
+        let divisor = 10u128.pow((decimals - mint_decimals) as u32);
+        let amount = amount as u128;
+        require!(
+            amount / divisor * divisor == amount,
+            SolidrError::RefundAmountNotTransferable
+        );
+        amount / divisor
+    };
+
+    require!(token_amount > 0, SolidrError::RefundAmountNotTransferable);
+    u64::try_from(token_amount).map_err(|_| SolidrError::Overflow.into())
+}
+
+/**
+ * Reads SOL price in session currency from Pyth price update, rejecting stale or low-confidence prices.
+ */
+pub fn get_sol_price(price_update: &PriceUpdateV2, feed_id: &[u8; 32]) -> Result<Price> {
+    require!(
+        !feed_id.iter().all(|&x| x == 0),
+        SolidrError::MissingPriceFeed
+    );
+    let price = price_update.get_price_no_older_than(&Clock::get()?, MAX_PRICE_AGE, feed_id)?;
+
+    require!(price.price > 0, SolidrError::InvalidPrice);
+    require!(
+        price.conf as u128 * BPS_DENOMINATOR as u128
+            <= price.price as u128 * MAX_PRICE_CONFIDENCE_BPS as u128,
+        SolidrError::PriceConfidenceTooLow
+    );
+    Ok(price)
+}
+
+/**
+ * Converts an amount in minor units of session currency into lamports.
+ *
+ * lamports = amount * 10^9 / (price * 10^exponent * 10^decimals)
+ */
+pub fn amount_to_lamports(amount: u64, decimals: u8, price: &Price) -> Result<u64> {
+    let mut numerator = (amount as u128)
+        .checked_mul(LAMPORTS_PER_SOL as u128)
+        .ok_or(SolidrError::Overflow)?;
+    let mut denominator = (price.price as u128)
+        .checked_mul(10u128.pow(decimals as u32))
+        .ok_or(SolidrError::Overflow)?;
+
+    if price.exponent < 0 {
+        numerator = numerator
+            .checked_mul(10u128.pow(price.exponent.unsigned_abs()))
+            .ok_or(SolidrError::Overflow)?;
+    } else {
+        denominator = denominator
+            .checked_mul(10u128.pow(price.exponent as u32))
+            .ok_or(SolidrError::Overflow)?;
+    }
+
+    require!(denominator > 0, SolidrError::DivisionByZero);
+    u64::try_from(numerator / denominator).map_err(|_| SolidrError::Overflow.into())
+}
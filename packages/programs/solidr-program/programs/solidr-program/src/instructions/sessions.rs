@@ -1,9 +1,19 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock;
+use anchor_spl::token_interface::{Mint, TokenInterface};
+use pyth_solana_receiver_sdk::price_update::get_feed_id_from_hex;
 
 use crate::{
     errors::*,
-    instructions::members::add_member,
-    state::{global::*, members::*, sessions::*},
+    instructions::{
+        members::add_member,
+        proposals::{consume_approval, consume_proposal},
+        refunds::SOL_USD_FEED_ID,
+    },
+    state::{
+        balances::*, expenses::ExpenseAccount, global::*, members::*, proposals::*, sessions::*,
+        vaults::*,
+    },
 };
 
 #[derive(Accounts)]
@@ -39,6 +49,19 @@ pub struct OpenSessionContextData<'info> {
     )]
     pub member: Account<'info, MemberAccount>,
 
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + BalanceAccount::INIT_SPACE,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        global.session_count.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
     pub system_program: Program<'info, System>,
 }
 
@@ -47,16 +70,20 @@ pub fn open_session(
     name: String,
     description: String,
     member_name: String,
+    currency: String,
+    decimals: u8,
 ) -> Result<()> {
     let global = &mut ctx.accounts.global;
     let session = &mut ctx.accounts.session;
     let member = &mut ctx.accounts.member;
+    let balance = &mut ctx.accounts.balance;
 
     require!(name.len() <= 20, SolidrError::SessionNameTooLong);
     require!(
         description.len() <= 80,
         SolidrError::SessionDescriptionTooLong
     );
+    SessionAccount::validate_currency(&currency, decimals)?;
 
     session.session_id = global.session_count;
     session.admin = ctx.accounts.admin.key();
@@ -65,13 +92,42 @@ pub fn open_session(
     session.status = SessionStatus::Opened;
     session.expenses_count = 0;
     session.refunds_count = 0;
+    session.members_count = 0;
+    session.decimals = decimals;
+    session.version = SessionAccount::VERSION;
+    session.mint = None;
+    session.mint_currency = String::new();
+    session.pending_admin = None;
+    session.approval_threshold = 0;
+    session.proposals_count = 0;
+    session.expense_approval = ExpenseApproval::Disabled;
+    session.closed_at = 0;
+    session.reopen_window = 0;
+    session.invitations_count = 0;
+    session.recurring_count = 0;
+    session.budgeted = false;
+    session.price_feed_id = if currency == SessionAccount::DEFAULT_CURRENCY {
+        get_feed_id_from_hex(SOL_USD_FEED_ID)?
+    } else {
+        [0; 32]
+    };
+    session.currency = currency;
 
     global.session_count += 1;
 
-    let _ = add_member(ctx.accounts.admin.key(), member_name, session, member);
+    let _ = add_member(
+        ctx.accounts.admin.key(),
+        member_name,
+        None,
+        session,
+        member,
+        balance,
+    );
 
     emit!(SessionOpened {
-        session_id: session.session_id
+        session_id: session.session_id,
+        currency: session.currency.clone(),
+        decimals: session.decimals,
     });
     Ok(())
 }
@@ -83,6 +139,16 @@ pub struct UpdateSessionContextData<'info> {
 
     #[account(mut)]
     pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
 }
 
 pub fn update_session(
@@ -93,7 +159,7 @@ pub fn update_session(
     let session = &mut ctx.accounts.session;
 
     require!(
-        session.admin.key() == ctx.accounts.admin.key(),
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
         SolidrError::ForbiddenAsNonAdmin
     );
 
@@ -124,13 +190,26 @@ pub struct CloseSessionContextData<'info> {
 
     #[account(mut)]
     pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
 }
 
 pub fn close_session(ctx: Context<CloseSessionContextData>) -> Result<()> {
     let session = &mut ctx.accounts.session;
 
     require!(
-        session.admin.key() == ctx.accounts.admin.key(),
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
         SolidrError::ForbiddenAsNonAdmin
     );
 
@@ -139,7 +218,14 @@ pub fn close_session(ctx: Context<CloseSessionContextData>) -> Result<()> {
         SolidrError::SessionClosed
     );
 
+    consume_approval(
+        session,
+        ctx.accounts.proposal.as_mut(),
+        ProposalAction::CloseSession,
+    )?;
+
     session.status = SessionStatus::Closed;
+    session.closed_at = clock::Clock::get().unwrap().unix_timestamp;
     session.invitation_hash = [0; 32];
 
     emit!(SessionClosed {
@@ -157,22 +243,70 @@ pub struct DeleteSessionContextData<'info> {
     #[account(mut, close = admin)]
     pub session: Account<'info, SessionAccount>,
 
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_balance: Account<'info, BalanceAccount>,
+
     pub system_program: Program<'info, System>,
+
+    /// CHECK: vault may not have been created yet, it is deserialized only when owned by the program
+    #[account(
+        seeds = [
+        VaultAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub vault: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
 }
 
 pub fn delete_session(ctx: Context<crate::instructions::sessions::DeleteSessionContextData>) -> Result<()> {
     let session = &mut ctx.accounts.session;
 
     require!(
-        session.admin.key() == ctx.accounts.admin.key(),
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
         SolidrError::ForbiddenAsNonAdmin
     );
 
     require!(
-        session.status == SessionStatus::Closed,
+        session.status != SessionStatus::Opened,
         SolidrError::SessionClosed
     );
 
+    let vault_info = ctx.accounts.vault.to_account_info();
+    if vault_info.owner == ctx.program_id && !vault_info.data_is_empty() {
+        let vault = VaultAccount::try_deserialize(&mut &vault_info.try_borrow_data()?[..])?;
+        require!(vault.total_deposited == 0, SolidrError::VaultNotEmpty);
+    }
+
+    consume_approval(
+        session,
+        ctx.accounts.proposal.as_mut(),
+        ProposalAction::DeleteSession,
+    )?;
 
     emit!(SessionDeleted {
         session_id: session.session_id
@@ -182,33 +316,374 @@ pub fn delete_session(ctx: Context<crate::instructions::sessions::DeleteSessionC
 }
 
 #[derive(Accounts)]
-pub struct SetSessionHashContextData<'info> {
+pub struct SetSessionMintContextData<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
 
     #[account(mut)]
     pub session: Account<'info, SessionAccount>,
 
-    pub system_program: Program<'info, System>,
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Option<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+pub fn set_session_mint(ctx: Context<SetSessionMintContextData>, currency: String) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+
+    require!(
+        ctx.accounts.signer_member
+            .has_role(&[MemberRole::Admin, MemberRole::Treasurer]),
+        SolidrError::ForbiddenRole
+    );
+
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+
+    session.mint = ctx.accounts.mint.as_ref().map(|mint| mint.key());
+    if session.mint.is_some() {
+        require!(
+            currency == session.currency,
+            SolidrError::MintCurrencyMismatch
+        );
+        session.mint_currency = currency;
+    } else {
+        session.mint_currency = String::new();
+    }
+
+    emit!(SessionMintUpdated {
+        session_id: session.session_id,
+        mint: session.mint,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetSessionPriceFeedContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
 }
 
-pub fn set_session_token_hash(
-    ctx: Context<SetSessionHashContextData>,
-    hash: [u8; 32],
+pub fn set_session_price_feed(
+    ctx: Context<SetSessionPriceFeedContextData>,
+    price_feed_id: [u8; 32],
 ) -> Result<()> {
     let session = &mut ctx.accounts.session;
 
+    require!(
+        ctx.accounts.signer_member
+            .has_role(&[MemberRole::Admin, MemberRole::Treasurer]),
+        SolidrError::ForbiddenRole
+    );
+
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+
+    if session.price_feed_id != [0; 32] {
+        // once pinned, price feed drives refund conversions so a majority of members must approve changes
+        let majority = (session.members_count as usize / 2 + 1).min(ProposalAccount::MAX_APPROVALS);
+        consume_proposal(
+            session,
+            ctx.accounts.proposal.as_mut(),
+            ProposalAction::SetPriceFeed { price_feed_id },
+            majority.max(session.approval_threshold as usize),
+        )?;
+    }
+
+    session.price_feed_id = price_feed_id;
+
+    emit!(SessionPriceFeedUpdated {
+        session_id: session.session_id,
+        price_feed_id,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(new_admin: Pubkey)]
+pub struct ProposeAdminContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        new_admin.as_ref(),
+        ],
+        bump
+    )]
+    pub new_admin_member: Account<'info, MemberAccount>,
+}
+
+pub fn propose_admin(ctx: Context<ProposeAdminContextData>, new_admin: Pubkey) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+
     require!(
         session.admin.key() == ctx.accounts.admin.key(),
         SolidrError::ForbiddenAsNonAdmin
     );
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(new_admin != session.admin, SolidrError::AlreadySessionAdmin);
+
+    session.pending_admin = Some(new_admin);
+
+    emit!(AdminProposed {
+        session_id: session.session_id,
+        addr: new_admin,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdminContextData<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        session.admin.as_ref(),
+        ],
+        bump
+    )]
+    pub previous_admin_member: Account<'info, MemberAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub new_admin_member: Account<'info, MemberAccount>,
+}
+
+pub fn accept_admin(ctx: Context<AcceptAdminContextData>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let previous_admin_member = &mut ctx.accounts.previous_admin_member;
+    let new_admin_member = &mut ctx.accounts.new_admin_member;
 
     require!(
         session.status == SessionStatus::Opened,
         SolidrError::SessionClosed
     );
+    require!(
+        session.pending_admin == Some(ctx.accounts.signer.key()),
+        SolidrError::NotPendingAdmin
+    );
+
+    let previous_admin = session.admin;
+
+    previous_admin_member.is_admin = false;
+    previous_admin_member.role = MemberRole::Member;
+    new_admin_member.is_admin = true;
+    new_admin_member.role = MemberRole::Admin;
 
-    session.invitation_hash = hash;
+    session.admin = new_admin_member.addr;
+    session.pending_admin = None;
 
+    emit!(AdminTransferred {
+        session_id: session.session_id,
+        previous_admin,
+        new_admin: session.admin,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetExpenseApprovalContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+}
+
+pub fn set_expense_approval(
+    ctx: Context<SetExpenseApprovalContextData>,
+    expense_approval: ExpenseApproval,
+) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    if let ExpenseApproval::Quorum { approvals } = expense_approval {
+        require!(
+            approvals > 0 && approvals as usize <= ExpenseAccount::MAX_PARTICIPANTS,
+            SolidrError::InvalidExpenseQuorum
+        );
+    }
+
+    session.expense_approval = expense_approval;
+
+    emit!(ExpenseApprovalUpdated {
+        session_id: session.session_id,
+        expense_approval,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SetReopenWindowContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+}
+
+pub fn set_reopen_window(ctx: Context<SetReopenWindowContextData>, reopen_window: i64) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+    require!(reopen_window >= 0, SolidrError::InvalidReopenWindow);
+
+    session.reopen_window = reopen_window;
+
+    emit!(SessionUpdated {
+        session_id: session.session_id
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ReopenSessionContextData<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        admin.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(mut)]
+    pub proposal: Option<Account<'info, ProposalAccount>>,
+}
+
+pub fn reopen_session(ctx: Context<ReopenSessionContextData>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+
+    require!(
+        ctx.accounts.signer_member.has_role(&[MemberRole::Admin]),
+        SolidrError::ForbiddenAsNonAdmin
+    );
+    require!(
+        session.status != SessionStatus::Opened,
+        SolidrError::SessionNotClosed
+    );
+
+    let now = clock::Clock::get().unwrap().unix_timestamp;
+    let within_window =
+        session.reopen_window > 0 && now <= session.closed_at.saturating_add(session.reopen_window);
+
+    if within_window {
+        consume_approval(
+            session,
+            ctx.accounts.proposal.as_mut(),
+            ProposalAction::ReopenSession,
+        )?;
+    } else {
+        // out of reopen window, a majority of members must approve
+        let majority = (session.members_count as usize / 2 + 1).min(ProposalAccount::MAX_APPROVALS);
+        consume_proposal(
+            session,
+            ctx.accounts.proposal.as_mut(),
+            ProposalAction::ReopenSession,
+            majority.max(session.approval_threshold as usize),
+        )?;
+    }
+
+    session.status = SessionStatus::Opened;
+    session.closed_at = 0;
+
+    emit!(SessionReopened {
+        session_id: session.session_id
+    });
     Ok(())
 }
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock;
+
+use crate::errors::*;
+use crate::instructions::balances::load_session_balances;
+use crate::state::members::*;
+use crate::state::sessions::*;
+use crate::state::settlements::*;
+
+#[derive(Accounts)]
+pub struct ComputeSettlementContextData<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub signer_member: Account<'info, MemberAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = 8 + SettlementAccount::INIT_SPACE,
+        seeds = [
+        SettlementAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub settlement: Account<'info, SettlementAccount>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn compute_settlement(ctx: Context<ComputeSettlementContextData>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let settlement = &mut ctx.accounts.settlement;
+
+    require!(
+        ctx.accounts.signer_member
+            .has_role(&[MemberRole::Admin, MemberRole::Treasurer]),
+        SolidrError::ForbiddenRole
+    );
+    require!(
+        session.status == SessionStatus::Closed,
+        SolidrError::SessionNotClosed
+    );
+
+    let balances = load_session_balances(
+        ctx.program_id,
+        session,
+        ctx.remaining_accounts.iter(),
+    )?;
+
+    let mut debtors: Vec<(Pubkey, u64)> = vec![];
+    let mut creditors: Vec<(Pubkey, u64)> = vec![];
+    for balance in balances.iter() {
+        if balance.balance < 0 {
+            debtors.push((balance.addr, balance.balance.unsigned_abs()));
+        } else if balance.balance > 0 {
+            creditors.push((balance.addr, balance.balance as u64));
+        }
+    }
+
+    settlement.session_id = session.session_id;
+    settlement.date = clock::Clock::get().unwrap().unix_timestamp;
+    settlement.plan_id += 1;
+    settlement.transfers = simplify_debts(debtors, creditors)?;
+
+    emit!(SettlementComputed {
+        session_id: session.session_id,
+        transfers_count: settlement.transfers.len() as u16,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct FinalizeSessionContextData<'info> {
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        seeds = [
+        MemberAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        signer.key().as_ref(),
+        ],
+        bump
+    )]
+    pub member: Account<'info, MemberAccount>,
+}
+
+pub fn finalize_session(ctx: Context<FinalizeSessionContextData>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+
+    require!(
+        session.status == SessionStatus::Closed,
+        SolidrError::SessionNotClosed
+    );
+
+    let balances = load_session_balances(
+        ctx.program_id,
+        session,
+        ctx.remaining_accounts.iter(),
+    )?;
+
+    require!(
+        balances
+            .iter()
+            .all(|b| b.balance.unsigned_abs() <= SessionAccount::SETTLED_BALANCE_TOLERANCE),
+        SolidrError::SessionNotSettled
+    );
+
+    session.status = SessionStatus::Settled;
+
+    emit!(SessionSettled {
+        session_id: session.session_id,
+    });
+    Ok(())
+}
+
+/**
+ * Greedily matches largest debts with largest credits, producing at most n - 1 transfers.
+ *
+ * @dev Ties are ordered by public key so that every caller gets the same canonical plan
+ */
+fn simplify_debts(
+    mut debtors: Vec<(Pubkey, u64)>,
+    mut creditors: Vec<(Pubkey, u64)>,
+) -> Result<Vec<SettlementTransfer>> {
+    debtors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    creditors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut transfers: Vec<SettlementTransfer> = vec![];
+    let (mut i, mut j) = (0, 0);
+
+    while i < debtors.len() && j < creditors.len() {
+        let amount = debtors[i].1.min(creditors[j].1);
+
+        require!(
+            transfers.len() < SettlementAccount::MAX_TRANSFERS,
+            SolidrError::TooManySettlementTransfers
+        );
+        transfers.push(SettlementTransfer {
+            from: debtors[i].0,
+            to: creditors[j].0,
+            amount,
+            paid: 0,
+        });
+
+        debtors[i].1 -= amount;
+        creditors[j].1 -= amount;
+        if debtors[i].1 == 0 {
+            i += 1;
+        }
+        if creditors[j].1 == 0 {
+            j += 1;
+        }
+    }
+    Ok(transfers)
+}
+
+/**
+ * Records a refund against a settlement plan entry.
+ */
+pub fn fulfill_settlement_transfer(
+    settlement: &mut SettlementAccount,
+    index: u16,
+    from: Pubkey,
+    to: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let transfer = settlement
+        .transfers
+        .get_mut(index as usize)
+        .ok_or(SolidrError::InvalidSettlementTransfer)?;
+
+    require!(
+        transfer.from == from && transfer.to == to,
+        SolidrError::InvalidSettlementTransfer
+    );
+    require!(
+        amount <= transfer.amount - transfer.paid,
+        SolidrError::SettlementTransferExceeded
+    );
+
+    transfer.paid += amount;
+
+    if transfer.is_fulfilled() {
+        emit!(SettlementTransferFulfilled {
+            session_id: settlement.session_id,
+            index,
+            from,
+            to,
+            amount: transfer.amount,
+        });
+    }
+    Ok(())
+}
+
+/**
+ * Reverts a partial payment of a settlement transfer when the refund fulfilling it is deleted.
+ */
+pub fn revert_settlement_transfer(
+    settlement: &mut SettlementAccount,
+    index: u16,
+    from: Pubkey,
+    to: Pubkey,
+    amount: u64,
+) -> Result<()> {
+    let transfer = settlement
+        .transfers
+        .get_mut(index as usize)
+        .ok_or(SolidrError::InvalidSettlementTransfer)?;
+
+    require!(
+        transfer.from == from && transfer.to == to,
+        SolidrError::InvalidSettlementTransfer
+    );
+
+    transfer.paid = transfer.paid.saturating_sub(amount);
+    Ok(())
+}
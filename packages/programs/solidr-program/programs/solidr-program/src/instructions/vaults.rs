@@ -0,0 +1,323 @@
+use anchor_lang::{prelude::*, system_program};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+
+use crate::errors::*;
+use crate::instructions::balances::{load_session_balances, store_balance};
+use crate::instructions::refunds::{amount_to_lamports, get_sol_price, BPS_DENOMINATOR};
+use crate::state::balances::BalanceAccount;
+use crate::state::sessions::*;
+use crate::state::vaults::*;
+
+#[derive(Accounts)]
+pub struct DepositToVaultContextData<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        depositor.key().as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + VaultAccount::INIT_SPACE,
+        seeds = [
+        VaultAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub vault: Account<'info, VaultAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = depositor,
+        space = 8 + DepositAccount::INIT_SPACE,
+        seeds = [
+        DepositAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        depositor.key().as_ref(),
+        ],
+        bump
+    )]
+    pub deposit: Account<'info, DepositAccount>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn deposit_to_vault(
+    ctx: Context<DepositToVaultContextData>,
+    expected_lamports: u64,
+    max_slippage_bps: u16,
+) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let balance = &mut ctx.accounts.balance;
+    let vault = &mut ctx.accounts.vault;
+    let deposit = &mut ctx.accounts.deposit;
+
+    require!(
+        session.status == SessionStatus::Closed,
+        SolidrError::SessionNotClosed
+    );
+    require!(balance.balance < 0, SolidrError::NothingToDeposit);
+
+    let amount = balance.balance.unsigned_abs();
+
+    let price = get_sol_price(&ctx.accounts.price_update, &session.price_feed_id)?;
+    let lamports = amount_to_lamports(amount, session.decimals, &price)?;
+
+    let tolerance = expected_lamports as u128 * max_slippage_bps as u128 / BPS_DENOMINATOR as u128;
+    require!(
+        (lamports as u128).abs_diff(expected_lamports as u128) <= tolerance,
+        SolidrError::SlippageExceeded
+    );
+
+    let cpi_context = CpiContext::new(
+        ctx.accounts.system_program.to_account_info(),
+        system_program::Transfer {
+            from: ctx.accounts.depositor.to_account_info(),
+            to: vault.to_account_info(),
+        },
+    );
+    system_program::transfer(cpi_context, lamports)?;
+
+    vault.session_id = session.session_id;
+    vault.total_deposited = vault
+        .total_deposited
+        .checked_add(amount)
+        .ok_or(SolidrError::Overflow)?;
+    vault.total_lamports = vault
+        .total_lamports
+        .checked_add(lamports)
+        .ok_or(SolidrError::Overflow)?;
+
+    if deposit.epoch != vault.epoch {
+        deposit.amount = 0;
+        deposit.lamports = 0;
+    }
+    deposit.session_id = session.session_id;
+    deposit.addr = ctx.accounts.depositor.key();
+    deposit.epoch = vault.epoch;
+    deposit.amount += amount;
+    deposit.lamports += lamports;
+
+    balance.apply(amount as i128, 0)?;
+
+    emit!(VaultDeposited {
+        session_id: session.session_id,
+        addr: deposit.addr,
+        amount,
+        lamports,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromVaultContextData<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        BalanceAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        depositor.key().as_ref(),
+        ],
+        bump
+    )]
+    pub balance: Account<'info, BalanceAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        VaultAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub vault: Account<'info, VaultAccount>,
+
+    #[account(
+        mut,
+        close = depositor,
+        seeds = [
+        DepositAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        depositor.key().as_ref(),
+        ],
+        bump
+    )]
+    pub deposit: Account<'info, DepositAccount>,
+}
+
+pub fn withdraw_from_vault(ctx: Context<WithdrawFromVaultContextData>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let balance = &mut ctx.accounts.balance;
+    let vault = &mut ctx.accounts.vault;
+    let deposit = &ctx.accounts.deposit;
+
+    // deposits from a previous epoch have already been paid out, only rent is reclaimed
+    if deposit.epoch != vault.epoch {
+        return Ok(());
+    }
+
+    require!(
+        session.status == SessionStatus::Opened,
+        SolidrError::SessionClosed
+    );
+
+    transfer_from_vault(
+        &vault.to_account_info(),
+        &ctx.accounts.depositor.to_account_info(),
+        deposit.lamports,
+    )?;
+
+    vault.total_deposited -= deposit.amount;
+    vault.total_lamports -= deposit.lamports;
+
+    balance.apply(-(deposit.amount as i128), 0)?;
+
+    emit!(VaultWithdrawn {
+        session_id: session.session_id,
+        addr: deposit.addr,
+        amount: deposit.amount,
+        lamports: deposit.lamports,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct SettleSessionContextData<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut)]
+    pub session: Account<'info, SessionAccount>,
+
+    #[account(
+        mut,
+        seeds = [
+        VaultAccount::SEED_PREFIX,
+        session.session_id.to_le_bytes().as_ref(),
+        ],
+        bump
+    )]
+    pub vault: Account<'info, VaultAccount>,
+}
+
+pub fn settle_session(ctx: Context<SettleSessionContextData>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let vault = &mut ctx.accounts.vault;
+    let program_id = ctx.program_id;
+
+    require!(
+        session.status == SessionStatus::Closed,
+        SolidrError::SessionNotClosed
+    );
+    require!(
+        ctx.remaining_accounts.chunks_exact(2).remainder().is_empty(),
+        SolidrError::IncompleteMembersList
+    );
+
+    let balances = load_session_balances(
+        program_id,
+        session,
+        ctx.remaining_accounts.iter().step_by(2),
+    )?;
+
+    let mut creditors: Vec<(usize, BalanceAccount)> = vec![];
+    let mut total_credit: u64 = 0;
+
+    for (i, balance) in balances.into_iter().enumerate() {
+        require!(
+            ctx.remaining_accounts[2 * i + 1].key() == balance.addr,
+            SolidrError::IncompleteMembersList
+        );
+        require!(balance.balance >= 0, SolidrError::VaultNotCovered);
+
+        if balance.balance > 0 {
+            total_credit += balance.balance as u64;
+            creditors.push((2 * i, balance));
+        }
+    }
+
+    require!(
+        total_credit == vault.total_deposited,
+        SolidrError::VaultNotCovered
+    );
+
+    let vault_info = vault.to_account_info();
+    let mut remaining_lamports = vault.total_lamports;
+    let creditors_count = creditors.len();
+
+    for (i, (index, mut balance)) in creditors.into_iter().enumerate() {
+        let (balance_info, wallet_info) = (
+            &ctx.remaining_accounts[index],
+            &ctx.remaining_accounts[index + 1],
+        );
+        let credit = balance.balance as u64;
+        let lamports = if i + 1 == creditors_count {
+            remaining_lamports
+        } else {
+            (vault.total_lamports as u128 * credit as u128 / total_credit as u128) as u64
+        };
+        remaining_lamports -= lamports;
+
+        transfer_from_vault(&vault_info, wallet_info, lamports)?;
+
+        balance.apply(0, credit as i128)?;
+        store_balance(balance_info, &balance)?;
+
+        emit!(VaultPaidOut {
+            session_id: session.session_id,
+            addr: balance.addr,
+            amount: credit,
+            lamports,
+        });
+    }
+
+    emit!(VaultSettled {
+        session_id: session.session_id,
+        epoch: vault.epoch,
+        total_amount: vault.total_deposited,
+        total_lamports: vault.total_lamports,
+    });
+
+    vault.total_deposited = 0;
+    vault.total_lamports = 0;
+    vault.epoch += 1;
+
+    // every debt has been deposited and paid out, all balances are now zero
+    session.status = SessionStatus::Settled;
+
+    emit!(SessionSettled {
+        session_id: session.session_id,
+    });
+    Ok(())
+}
+
+fn transfer_from_vault(vault: &AccountInfo, to: &AccountInfo, lamports: u64) -> Result<()> {
+    **vault.try_borrow_mut_lamports()? = vault
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(SolidrError::Overflow)?;
+    **to.try_borrow_mut_lamports()? = to
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(SolidrError::Overflow)?;
+    Ok(())
+}
@@ -1,6 +1,15 @@
 use anchor_lang::prelude::*;
 
-use crate::instructions::{expenses::*, global::*, members::*, refunds::*, sessions::*};
+use crate::instructions::{
+    budgets::*, expenses::*, global::*, invitations::*, members::*, migrations::*, proposals::*,
+    recurring::*, refunds::*, sessions::*, settlements::*, vaults::*,
+};
+use crate::state::expenses::{
+    ExpenseCategory, ExpenseParticipant, ExpenseReceipt, OriginalAmount, SplitMode,
+};
+use crate::state::members::MemberRole;
+use crate::state::proposals::ProposalAction;
+use crate::state::sessions::ExpenseApproval;
 
 pub mod errors;
 pub mod instructions;
@@ -26,18 +35,22 @@ pub mod solidr {
      * @param name The session name
      * @param description The session description
      * @param member_name The administrator's name
+     * @param currency The ISO-4217 code of the currency used for all session amounts
+     * @param decimals The number of decimals of currency minor units, ie. 2 for EUR or 0 for JPY
      */
     pub fn open_session(
         ctx: Context<OpenSessionContextData>,
         name: String,
         description: String,
         member_name: String,
+        currency: String,
+        decimals: u8,
     ) -> Result<()> {
-        sessions::open_session(ctx, name, description, member_name)
+        sessions::open_session(ctx, name, description, member_name, currency, decimals)
     }
 
     /**
-     * Session's administrator can update the session's name and description.
+     * Session members with Admin role can update the session's name and description.
      *
      * @param name The new session name
      * @param description The new session description
@@ -51,39 +64,116 @@ pub mod solidr {
     }
 
     /**
-     * Administrator can close sessions he created.
+     * Session members with Admin role can close the session.
      *
-     * @dev An event SessionClosed is emitted
+     * @dev When session has an approval threshold, an approved proposal must be provided.
+     * An event SessionClosed is emitted
      */
     pub fn close_session(ctx: Context<CloseSessionContextData>) -> Result<()> {
         sessions::close_session(ctx)
     }
 
     /**
-     * Session's administrator can delete the session.
+     * Session members with Admin role can reopen a closed or settled session.
      *
-     * @dev An event SessionDeleted is emitted
+     * @dev Within the reopen window, session approval threshold applies. Afterwards, or without reopen window,
+     * a proposal approved by a majority of members must be provided. An event SessionReopened is emitted
+     */
+    pub fn reopen_session(ctx: Context<ReopenSessionContextData>) -> Result<()> {
+        sessions::reopen_session(ctx)
+    }
+
+    /**
+     * Session members with Admin role can set the delay during which a closed session can be reopened without majority approval.
+     *
+     * @dev Zero disables the window. An event SessionUpdated is emitted
+     *
+     * @param reopen_window The delay in seconds after session closing
+     */
+    pub fn set_reopen_window(
+        ctx: Context<SetReopenWindowContextData>,
+        reopen_window: i64,
+    ) -> Result<()> {
+        sessions::set_reopen_window(ctx, reopen_window)
+    }
+
+    /**
+     * Session members with Admin role can delete the session.
+     *
+     * @dev Session can't be deleted while its vault still holds deposits. Signer's member and balance accounts are closed along with the session.
+     * When session has an approval threshold, an approved proposal must be provided. An event SessionDeleted is emitted
      */
     pub fn delete_session(ctx: Context<DeleteSessionContextData>) -> Result<()> {
         sessions::delete_session(ctx)
     }
 
     /**
-     * Session's administrator can set invitation token hash
+     * Session's administrator can propose another member as session administrator.
      *
-     * @param hash The token hash to store in session
+     * @dev Session must be opened. Any previous proposal is replaced. An event AdminProposed is emitted
+     *
+     * @param new_admin The address of the proposed administrator
      */
-    pub fn set_session_token_hash(
-        ctx: Context<SetSessionHashContextData>,
-        hash: [u8; 32],
+    pub fn propose_admin(ctx: Context<ProposeAdminContextData>, new_admin: Pubkey) -> Result<()> {
+        sessions::propose_admin(ctx, new_admin)
+    }
+
+    /**
+     * Proposed member can accept session administration.
+     *
+     * @dev Session must be opened. New administrator gets Admin role and previous one is downgraded to Member role. An event AdminTransferred is emitted
+     */
+    pub fn accept_admin(ctx: Context<AcceptAdminContextData>) -> Result<()> {
+        sessions::accept_admin(ctx)
+    }
+
+    /**
+     * Session members with Admin or Treasurer role can set the token mint used for token refunds.
+     *
+     * @dev Omitting mint account disables token refunds. Token amounts are not converted, mint must be denominated in session currency. An event SessionMintUpdated is emitted
+     *
+     * @param currency The ISO-4217 code of the currency the token is pegged to
+     */
+    pub fn set_session_mint(
+        ctx: Context<SetSessionMintContextData>,
+        currency: String,
     ) -> Result<()> {
-        sessions::set_session_token_hash(ctx, hash)
+        sessions::set_session_mint(ctx, currency)
     }
 
     /**
-     * Session administrator can add members.
+     * Session members with Admin or Treasurer role can set the Pyth feed giving SOL price in session currency.
      *
-     * @dev members can be added only by session administrator when session is opened
+     * @dev SOL/USD feed is set by default for USD sessions. Once set, changing the feed requires
+     * a proposal approved by a majority of members. An event SessionPriceFeedUpdated is emitted
+     *
+     * @param price_feed_id The Pyth price feed id
+     */
+    pub fn set_session_price_feed(
+        ctx: Context<SetSessionPriceFeedContextData>,
+        price_feed_id: [u8; 32],
+    ) -> Result<()> {
+        sessions::set_session_price_feed(ctx, price_feed_id)
+    }
+
+    /**
+     * Session members with Admin role can require participants approval before expenses count toward balances.
+     *
+     * @dev Applies to expenses added or updated afterwards. An event ExpenseApprovalUpdated is emitted
+     *
+     * @param expense_approval The approvals required for new expenses
+     */
+    pub fn set_expense_approval(
+        ctx: Context<SetExpenseApprovalContextData>,
+        expense_approval: ExpenseApproval,
+    ) -> Result<()> {
+        sessions::set_expense_approval(ctx, expense_approval)
+    }
+
+    /**
+     * Session members with Admin role can add members.
+     *
+     * @dev members can be added only when session is opened. New members get Member role
      * An event MemberAdded is emitted
      *
      * @param addr The address of the member to add
@@ -97,6 +187,26 @@ pub mod solidr {
         members::add_session_member(ctx, addr, name)
     }
 
+    /**
+     * Session members with Admin role can grant a role to another member.
+     *
+     * @dev Session administrator role can't be changed. An event RoleGranted is emitted
+     *
+     * @param role The role to grant
+     */
+    pub fn grant_role(ctx: Context<SetMemberRoleContextData>, role: MemberRole) -> Result<()> {
+        members::grant_role(ctx, role)
+    }
+
+    /**
+     * Session members with Admin role can revoke the role of another member, who falls back to Member role.
+     *
+     * @dev Session administrator role can't be changed. An event RoleRevoked is emitted
+     */
+    pub fn revoke_role(ctx: Context<SetMemberRoleContextData>) -> Result<()> {
+        members::revoke_role(ctx)
+    }
+
     pub fn update_session_member(
         ctx: Context<UpdateSessionMemberContextData>,
         name: String,
@@ -105,19 +215,82 @@ pub mod solidr {
     }
 
     /**
-     * Session administrator can remove a member from the session.
+     * Session members with Admin role can remove a member from the session.
      *
-     * @dev Members can be removed only by session administrator when session is opened
+     * @dev Members can be removed only when session is closed, their balance is settled and no expense involves them anymore.
+     * When session has an approval threshold, an approved proposal must be provided
      * An event MemberRemoved is emitted
      */
     pub fn delete_session_member(ctx: Context<DeleteSessionMemberContextData>) -> Result<()> {
         members::delete_session_member(ctx)
     }
 
+    /**
+     * Session members with Admin role can remove a member from an opened session.
+     *
+     * @dev Every expense involving the member must be provided as first remaining accounts, followed by balance
+     * accounts of their owners and participants. Member is stripped from participants and amounts are re-split.
+     * Expenses paid by the member are reassigned to the new owner when provided, otherwise removal fails.
+     * Member balance must be zero afterwards. When session has an approval threshold, an approved proposal must be provided.
+     * Events ExpenseOwnerReassigned, ExpenseParticipantRemoved
+     * and ExpenseUpdated are emitted for each expense, then an event MemberRemoved is emitted
+     *
+     * @param expenses_count The number of expense accounts provided as remaining accounts
+     */
+    pub fn remove_session_member(
+        ctx: Context<RemoveSessionMemberContextData>,
+        expenses_count: u8,
+    ) -> Result<()> {
+        members::remove_session_member(ctx, expenses_count)
+    }
+
+    /**
+     * Members with a zero balance and no expense involving them can leave the session. Rent is returned to them.
+     *
+     * @dev Session administrator can't leave. Members still involved in expenses must first be removed from them,
+     * or be removed by an administrator with remove_session_member. An event MemberLeft is emitted
+     */
+    pub fn leave_session(ctx: Context<LeaveSessionContextData>) -> Result<()> {
+        members::leave_session(ctx)
+    }
+
+    /**
+     * Session members with Admin role can create named invitations limited in time and number of uses.
+     * Several invitations can be active concurrently.
+     *
+     * @dev An event InvitationCreated is emitted
+     *
+     * @param label The name of the invitation, ie. the group it's shared with
+     * @param token_hash The hash of the token shared with invitees
+     * @param expires_at The timestamp after which invitation can't be used, zero for no expiry
+     * @param max_uses The number of members who can join with this invitation
+     * @param role The role given to members joining with this invitation, Member role by default
+     */
+    pub fn create_invitation(
+        ctx: Context<CreateInvitationContextData>,
+        label: String,
+        token_hash: [u8; 32],
+        expires_at: i64,
+        max_uses: u16,
+        role: Option<MemberRole>,
+    ) -> Result<()> {
+        invitations::create_invitation(ctx, label, token_hash, expires_at, max_uses, role)
+    }
+
+    /**
+     * Session members with Admin role can revoke an invitation.
+     *
+     * @dev An event InvitationRevoked is emitted
+     */
+    pub fn revoke_invitation(ctx: Context<RevokeInvitationContextData>) -> Result<()> {
+        invitations::revoke_invitation(ctx)
+    }
+
     /**
      * Anyone can join a session with correct information provided with a share link.
      *
-     * An event MemberAdded is emitted
+     * @dev Token is checked against the invitation account and one use is consumed.
+     * Legacy session invitation hash is no longer accepted. An event MemberAdded is emitted
      *
      * @param name The nickname of the member to add
      * @param token The token shared by session's administrator
@@ -131,28 +304,75 @@ pub mod solidr {
     }
 
     /**
-     * Adds a new expense to the session.
+     * Adds a new expense to the session. Members with Admin or Treasurer role can record an expense paid by another member.
+     *
+     * @dev Balance accounts of owner, payer and participants must be provided as remaining accounts, along with payer member account when not signer.
+     * Expense is pending until approved by participants when session requires it. Budget account is required once session is budgeted
      *
      * @param name The name of the expense
-     * @param amount The amount of the expense
+     * @param amount The amount of the expense in minor units of session currency
+     * @param split_mode The way participants shares are interpreted
+     * @param participants The participants and their shares. Payer is added with default share if not listed, unless excluded
+     * @param original The amount in original currency and exchange rate used, when paid in another currency
+     * @param category The spending category of the expense
+     * @param tags Free-form tags of the expense, 5 max
+     * @param receipt The SHA-256 digest and storage URI of the receipt, if any
+     * @param payer The member who paid the expense, signer when none
+     * @param exclude_payer Whether payer didn't take part in the expense
      */
+    #[allow(clippy::too_many_arguments)]
     pub fn add_expense(
         ctx: Context<AddExpenseContextData>,
         name: String,
-        amount: f32,
-        participants: Vec<Pubkey>,
+        amount: u64,
+        split_mode: SplitMode,
+        participants: Vec<ExpenseParticipant>,
+        original: Option<OriginalAmount>,
+        category: ExpenseCategory,
+        tags: Vec<String>,
+        receipt: Option<ExpenseReceipt>,
+        payer: Option<Pubkey>,
+        exclude_payer: bool,
     ) -> Result<()> {
-        expenses::add_expense(ctx, name, amount, participants)
+        expenses::add_expense(
+            ctx,
+            name,
+            amount,
+            split_mode,
+            participants,
+            original,
+            category,
+            tags,
+            receipt,
+            payer,
+            exclude_payer,
+        )
     }
 
     /**
-     * Adds participants to expense.
+     * Expense owner can attach, replace or remove the receipt of an expense.
      *
-     * @param participants The public keys of the participants
+     * @dev An event ExpenseReceiptUpdated is emitted
+     *
+     * @param receipt The SHA-256 digest and storage URI of the receipt, or none to remove it
+     */
+    pub fn set_expense_receipt(
+        ctx: Context<SetExpenseReceiptContextData>,
+        receipt: Option<ExpenseReceipt>,
+    ) -> Result<()> {
+        expenses::set_expense_receipt(ctx, receipt)
+    }
+
+    /**
+     * Adds participants to expense. Shares of already registered participants are updated.
+     *
+     * @dev Balance accounts of owner and all participants must be provided as remaining accounts
+     *
+     * @param participants The participants and their shares
      */
     pub fn add_expense_participants(
         ctx: Context<AddExpenseParticipantContextData>,
-        participants: Vec<Pubkey>,
+        participants: Vec<ExpenseParticipant>,
     ) -> Result<()> {
         expenses::add_expense_participants(ctx, participants)
     }
@@ -160,6 +380,8 @@ pub mod solidr {
     /**
      * Removes participant from expense.
      *
+     * @dev Balance accounts of owner and all participants must be provided as remaining accounts
+     *
      * @param participants The public keys of the participants
      */
     pub fn remove_expense_participants(
@@ -172,42 +394,368 @@ pub mod solidr {
     /**
      * Updates an existing expense in the session.
      *
+     * @dev Balance accounts of owner and participants must be provided as remaining accounts.
+     * Participants approvals are reset when session requires them
+     *
      * @param name The name of the expense to be updated
-     * @param amount The new amount of the expense
+     * @param amount The new amount of the expense in minor units of session currency
+     * @param participants Optional participants shares to add or update along with amount
+     * @param original The amount in original currency and exchange rate used, when paid in another currency
+     * @param category The new spending category of the expense
+     * @param tags The new free-form tags of the expense, 5 max
      */
+    #[allow(clippy::too_many_arguments)]
     pub fn update_expense(
         ctx: Context<UpdateExpenseContextData>,
         name: String,
-        amount: f32,
+        amount: u64,
+        participants: Option<Vec<ExpenseParticipant>>,
+        original: Option<OriginalAmount>,
+        category: ExpenseCategory,
+        tags: Vec<String>,
     ) -> Result<()> {
-        expenses::update_expense(ctx, name, amount)
+        expenses::update_expense(ctx, name, amount, participants, original, category, tags)
     }
 
     /**
      * Deletes an existing expense in the session.
      *
-     * @param name The name of the expense to be deleted
-     * @param amount The amount of the expense to be deleted
+     * @dev Balance accounts of owner and participants must be provided as remaining accounts
      */
     pub fn delete_expense(ctx: Context<DeleteExpenseContextData>) -> Result<()> {
         expenses::delete_expense(ctx)
     }
 
+    /**
+     * Participants of a pending expense can approve it. Expense counts toward balances once enough participants approved.
+     *
+     * @dev Balance accounts of owner and participants must be provided as remaining accounts.
+     * An event ExpenseApproved is emitted
+     */
+    pub fn approve_expense(ctx: Context<ReviewExpenseContextData>) -> Result<()> {
+        expenses::approve_expense(ctx)
+    }
+
+    /**
+     * Participants of a pending expense can reject it. Owner can then update or delete it.
+     *
+     * @dev Rejected expense remains outstanding for its participants. An event ExpenseRejected is emitted
+     */
+    pub fn reject_expense(ctx: Context<ReviewExpenseContextData>) -> Result<()> {
+        expenses::reject_expense(ctx)
+    }
+
+    /**
+     * Participants can dispute an expense, freezing its update and deletion until resolved.
+     *
+     * @dev Balance accounts of owner and participants must be provided as remaining accounts.
+     * An event ExpenseDisputed is emitted
+     *
+     * @param reason The short reason of the dispute
+     */
+    pub fn dispute_expense(ctx: Context<DisputeExpenseContextData>, reason: String) -> Result<()> {
+        expenses::dispute_expense(ctx, reason)
+    }
+
+    /**
+     * Expense owner or session members with Admin role can resolve a dispute, amending the expense or not.
+     *
+     * @dev Balance accounts of owner and participants must be provided as remaining accounts.
+     * An event ExpenseDisputeResolved is emitted
+     *
+     * @param amount The amended amount in minor units of session currency, if any
+     * @param participants The participants shares to add or update, if any
+     */
+    pub fn resolve_dispute(
+        ctx: Context<ResolveDisputeContextData>,
+        amount: Option<u64>,
+        participants: Option<Vec<ExpenseParticipant>>,
+    ) -> Result<()> {
+        expenses::resolve_dispute(ctx, amount, participants)
+    }
+
+    /**
+     * Session administrator can define a spending budget, either for session total or for a single category.
+     *
+     * @dev Budget account is created on first call and must then be provided to every instruction changing expenses amounts.
+     * Running totals only account for expenses recorded once session is budgeted. An event BudgetUpdated is emitted
+     *
+     * @param category The budgeted category, or session total when none
+     * @param limit The limit in minor units of session currency, or none to only track spending
+     * @param hard_cap Whether expenses crossing the limit fail instead of only emitting BudgetExceeded
+     */
+    pub fn set_budget(
+        ctx: Context<SetBudgetContextData>,
+        category: Option<ExpenseCategory>,
+        limit: Option<u64>,
+        hard_cap: bool,
+    ) -> Result<()> {
+        budgets::set_budget(ctx, category, limit, hard_cap)
+    }
+
+    /**
+     * Session members can create a recurring expense template, materialized as a new expense at each interval.
+     *
+     * @dev Member accounts of participants must be provided as remaining accounts. An event RecurringExpenseCreated is emitted
+     *
+     * @param name The name of materialized expenses
+     * @param amount The amount of materialized expenses in minor units of session currency
+     * @param split_mode The way participants shares are interpreted
+     * @param participants The participants and their shares. Owner is added with default share if not listed
+     * @param interval The delay in seconds between two occurrences, at least one day
+     * @param first_due The timestamp of the first occurrence, not in the past
+     * @param category The spending category of materialized expenses
+     * @param tags Free-form tags of materialized expenses, 5 max
+     */
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_recurring_expense(
+        ctx: Context<CreateRecurringExpenseContextData>,
+        name: String,
+        amount: u64,
+        split_mode: SplitMode,
+        participants: Vec<ExpenseParticipant>,
+        interval: i64,
+        first_due: i64,
+        category: ExpenseCategory,
+        tags: Vec<String>,
+    ) -> Result<()> {
+        recurring::create_recurring_expense(
+            ctx,
+            name,
+            amount,
+            split_mode,
+            participants,
+            interval,
+            first_due,
+            category,
+            tags,
+        )
+    }
+
+    /**
+     * Anyone can materialize the next occurrence of an active recurring expense once due.
+     *
+     * @dev Balance accounts of owner and participants must be provided as remaining accounts.
+     * Signer pays expense account rent. Events ExpenseAdded and RecurringExpenseMaterialized are emitted
+     */
+    pub fn materialize_recurring_expense(
+        ctx: Context<MaterializeRecurringExpenseContextData>,
+    ) -> Result<()> {
+        recurring::materialize_recurring_expense(ctx)
+    }
+
+    /**
+     * Recurring expense owner or session members with Admin role can pause a recurring expense.
+     *
+     * @dev An event RecurringExpensePaused is emitted
+     */
+    pub fn pause_recurring_expense(ctx: Context<UpdateRecurringExpenseContextData>) -> Result<()> {
+        recurring::pause_recurring_expense(ctx)
+    }
+
+    /**
+     * Recurring expense owner or session members with Admin role can resume a paused recurring expense.
+     *
+     * @dev Occurrences due while paused are skipped. An event RecurringExpenseResumed is emitted
+     */
+    pub fn resume_recurring_expense(ctx: Context<UpdateRecurringExpenseContextData>) -> Result<()> {
+        recurring::resume_recurring_expense(ctx)
+    }
+
+    /**
+     * Recurring expense owner or session members with Admin role can cancel a recurring expense.
+     *
+     * @dev Template account is closed and rent returned to its owner. An event RecurringExpenseCancelled is emitted
+     */
+    pub fn cancel_recurring_expense(ctx: Context<CancelRecurringExpenseContextData>) -> Result<()> {
+        recurring::cancel_recurring_expense(ctx)
+    }
+
     /**
      * Adds a new refund to the session. lamports corresponding to given amount will be transfered to mentionned "to" account
      *
-     * @param amount The amount of the refund corresponding to session currency
-     * @param amount_in_lamports The amount in lamports of the refund. It must be calculated offchain.
+     * @dev Lamports are computed from Pyth SOL price. Sender and receiver balances are updated
+     *
+     * @param amount The amount of the refund in minor units of session currency
+     * @param expected_lamports The amount in lamports expected by sender when building the transaction
+     * @param max_slippage_bps The maximum allowed difference in basis points between expected and computed lamports
+     * @param settlement_index The index of settlement plan transfer fulfilled by this refund, if any
      */
     pub fn add_refund(
         ctx: Context<RefundContextData>,
-        amount: f32,
-        amount_in_lamports: u64,
+        amount: u64,
+        expected_lamports: u64,
+        max_slippage_bps: u16,
+        settlement_index: Option<u16>,
+    ) -> Result<()> {
+        refunds::add_refund(
+            ctx,
+            amount,
+            expected_lamports,
+            max_slippage_bps,
+            settlement_index,
+        )
+    }
+
+    /**
+     * Adds a new refund to the session paid with session's token. Tokens are transfered with Token or Token-2022 program.
+     *
+     * @dev Sender and receiver balances are updated. Transferred tokens are amount rescaled to mint decimals, mint currency must match session currency
+     *
+     * @param amount The amount of the refund in minor units of session currency
+     * @param settlement_index The index of settlement plan transfer fulfilled by this refund, if any
+     */
+    pub fn add_token_refund(
+        ctx: Context<TokenRefundContextData>,
+        amount: u64,
+        settlement_index: Option<u16>,
     ) -> Result<()> {
-        refunds::add_refund(ctx, amount, amount_in_lamports)
+        refunds::add_token_refund(ctx, amount, settlement_index)
     }
 
+    /**
+     * Session members with Admin or Treasurer role can delete a refund of a closed session.
+     *
+     * @dev Sender and receiver balances are reverted, as well as the settlement transfer fulfilled by the refund
+     * unless its plan has been recomputed since, in which case session settlement account must be provided.
+     * When session has an approval threshold, an approved proposal must be provided. An event RefundDeleted is emitted
+     */
     pub fn delete_refund(ctx: Context<DeleteRefundContextData>) -> Result<()> {
         refunds::delete_refund(ctx)
     }
+
+    /**
+     * Session members with Admin role can set the number of approvals required by destructive actions.
+     *
+     * @dev Zero disables approvals. Changing an existing threshold requires an approved proposal.
+     * An event ApprovalThresholdUpdated is emitted
+     *
+     * @param threshold The number of members approvals required
+     */
+    pub fn set_approval_threshold(
+        ctx: Context<SetApprovalThresholdContextData>,
+        threshold: u8,
+    ) -> Result<()> {
+        proposals::set_approval_threshold(ctx, threshold)
+    }
+
+    /**
+     * Members allowed to perform a destructive action can propose it for approval. Proposer approves it.
+     *
+     * @dev Events ProposalCreated and ProposalApproved are emitted
+     *
+     * @param action The action to approve
+     */
+    pub fn create_proposal(
+        ctx: Context<CreateProposalContextData>,
+        action: ProposalAction,
+    ) -> Result<()> {
+        proposals::create_proposal(ctx, action)
+    }
+
+    /**
+     * Session members can approve a pending proposal.
+     *
+     * @dev Approved action is executed by providing the proposal to the action instruction.
+     * An event ProposalApproved is emitted
+     */
+    pub fn approve_proposal(ctx: Context<ApproveProposalContextData>) -> Result<()> {
+        proposals::approve_proposal(ctx)
+    }
+
+    /**
+     * Session members with Admin or Treasurer role can compute the canonical settlement plan of a closed session from members balances.
+     *
+     * @dev Balance accounts of every session member must be provided as remaining accounts.
+     * Any previous plan is replaced and its refunds are no longer linked to the new plan. An event SettlementComputed is emitted
+     */
+    pub fn compute_settlement(ctx: Context<ComputeSettlementContextData>) -> Result<()> {
+        settlements::compute_settlement(ctx)
+    }
+
+    /**
+     * Any session member can mark a closed session as settled once every member balance is zero.
+     *
+     * @dev Balance accounts of every session member must be provided as remaining accounts.
+     * A rounding tolerance of one minor unit is allowed per member. An event SessionSettled is emitted
+     */
+    pub fn finalize_session(ctx: Context<FinalizeSessionContextData>) -> Result<()> {
+        settlements::finalize_session(ctx)
+    }
+
+    /**
+     * Members owing money on a closed session can deposit their net debt into session vault.
+     *
+     * @dev Lamports are computed from Pyth SOL price. An event VaultDeposited is emitted
+     *
+     * @param expected_lamports The amount in lamports expected by depositor when building the transaction
+     * @param max_slippage_bps The maximum allowed difference in basis points between expected and computed lamports
+     */
+    pub fn deposit_to_vault(
+        ctx: Context<DepositToVaultContextData>,
+        expected_lamports: u64,
+        max_slippage_bps: u16,
+    ) -> Result<()> {
+        vaults::deposit_to_vault(ctx, expected_lamports, max_slippage_bps)
+    }
+
+    /**
+     * Depositors can withdraw their deposit when session has been reopened.
+     *
+     * @dev Deposit account is closed. An event VaultWithdrawn is emitted
+     */
+    pub fn withdraw_from_vault(ctx: Context<WithdrawFromVaultContextData>) -> Result<()> {
+        vaults::withdraw_from_vault(ctx)
+    }
+
+    /**
+     * Anyone can pay out all creditors from session vault once all debts have been deposited.
+     *
+     * @dev Balance and wallet of every session member must be provided as remaining accounts pairs.
+     * An event VaultPaidOut is emitted for each creditor, then events VaultSettled and SessionSettled are emitted
+     */
+    pub fn settle_session(ctx: Context<SettleSessionContextData>) -> Result<()> {
+        vaults::settle_session(ctx)
+    }
+
+    /**
+     * Session's administrator can migrate a session created with the legacy layout.
+     *
+     * @dev Session currency and decimals are set to USD defaults. An event SessionMigrated is emitted
+     */
+    pub fn migrate_session(ctx: Context<MigrateSessionContextData>) -> Result<()> {
+        migrations::migrate_session(ctx)
+    }
+
+    /**
+     * Session's administrator can convert a legacy expense floating amount into minor units.
+     *
+     * @dev Session and members must be migrated first. Balance of every member involved in the expense
+     * must be provided as remaining accounts to replay it into the ledger. An event ExpenseMigrated is emitted
+     */
+    pub fn migrate_expense(ctx: Context<MigrateExpenseContextData>) -> Result<()> {
+        migrations::migrate_expense(ctx)
+    }
+
+    /**
+     * Session's administrator can convert a legacy refund floating amount into minor units.
+     *
+     * @dev Session and members must be migrated first. Sender and receiver balances
+     * must be provided as remaining accounts to replay it into the ledger. An event RefundMigrated is emitted
+     */
+    pub fn migrate_refund(ctx: Context<MigrateRefundContextData>) -> Result<()> {
+        migrations::migrate_refund(ctx)
+    }
+
+    /**
+     * Session's administrator can migrate a member created before roles were introduced.
+     *
+     * @dev Session administrator gets Admin role, others get Member role. Member balance account is created.
+     * An event MemberMigrated is emitted
+     *
+     * @param addr The address of the migrated member
+     */
+    pub fn migrate_member(ctx: Context<MigrateMemberContextData>, addr: Pubkey) -> Result<()> {
+        migrations::migrate_member(ctx, addr)
+    }
 }
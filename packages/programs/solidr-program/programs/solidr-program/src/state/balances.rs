@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct BalanceAccount {
+    // 8 discriminator
+    pub session_id: u64, // 8
+    pub addr: Pubkey,    // 32
+    pub total_paid: u64, // 8
+    pub total_owed: u64, // 8
+    pub balance: i64,    // 8
+    pub pending_count: u16, // 2
+    pub expenses_count: u16, // 2
+}
+
+impl BalanceAccount {
+    pub const SEED_PREFIX: &'static [u8; 7] = b"balance";
+
+    /**
+     * Applies signed deltas on paid and owed totals and recomputes net balance.
+     *
+     * @dev Refunds sent are accounted as paid and refunds received as owed,
+     * so that a fully settled member has a zero net balance.
+     */
+    pub fn apply(&mut self, paid: i128, owed: i128) -> Result<()> {
+        self.total_paid = apply_delta(self.total_paid, paid)?;
+        self.total_owed = apply_delta(self.total_owed, owed)?;
+        self.balance = i64::try_from(self.total_paid as i128 - self.total_owed as i128)
+            .map_err(|_| SolidrError::Overflow)?;
+        Ok(())
+    }
+
+    /**
+     * Counts expenses involving member, and those still awaiting approval or dispute resolution.
+     */
+    pub fn apply_counts(&mut self, expenses: i32, pending: i32) -> Result<()> {
+        self.expenses_count = u16::try_from(self.expenses_count as i32 + expenses)
+            .map_err(|_| SolidrError::Overflow)?;
+        self.pending_count = u16::try_from(self.pending_count as i32 + pending)
+            .map_err(|_| SolidrError::Overflow)?;
+        Ok(())
+    }
+}
+
+fn apply_delta(total: u64, delta: i128) -> Result<u64> {
+    u64::try_from(total as i128 + delta).map_err(|_| SolidrError::Overflow.into())
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+
+use crate::state::expenses::ExpenseCategory;
+
+#[account]
+#[derive(InitSpace)]
+pub struct BudgetAccount {
+    // 8 discriminator
+    pub session_id: u64,                                     // 8
+    pub total: BudgetLine,                                   // 18
+    pub categories: [BudgetLine; ExpenseCategory::COUNT],    // 8 * 18
+}
+
+impl BudgetAccount {
+    pub const SEED_PREFIX: &'static [u8; 6] = b"budget";
+
+    pub fn line_mut(&mut self, category: Option<ExpenseCategory>) -> &mut BudgetLine {
+        match category {
+            Some(category) => &mut self.categories[category as usize],
+            None => &mut self.total,
+        }
+    }
+}
+
+/**
+ * Running total of expenses amounts, optionally capped by a limit.
+ */
+#[derive(Clone, Copy, Default, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub struct BudgetLine {
+    pub limit: Option<u64>, // 1 + 8
+    pub spent: u64,         // 8
+    /// Expenses crossing the limit fail instead of only emitting BudgetExceeded
+    pub hard_cap: bool, // 1
+}
+
+#[event]
+pub struct BudgetUpdated {
+    pub session_id: u64,
+    pub category: Option<ExpenseCategory>,
+    pub limit: Option<u64>,
+    pub hard_cap: bool,
+}
+
+#[event]
+pub struct BudgetExceeded {
+    pub session_id: u64,
+    pub category: Option<ExpenseCategory>,
+    pub limit: u64,
+    pub spent: u64,
+}
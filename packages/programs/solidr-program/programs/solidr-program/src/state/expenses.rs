@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::*;
+use crate::state::sessions::ExpenseApproval;
+
 #[account]
 #[derive(InitSpace)]
 pub struct ExpenseAccount {
@@ -8,27 +11,346 @@ pub struct ExpenseAccount {
     pub expense_id: u16, // 2
     pub date: i64,       // 8
     pub owner: Pubkey,   // 32
-    pub amount: f32,     // 4
+    pub amount: u64,     // 8
     #[max_len(20)]
     pub name: String, // 4 + 20
-    #[max_len(10)]
-    pub participants: Vec<Pubkey>, // ?
+    #[max_len(20)]
+    pub participants: Vec<ExpenseParticipant>, // 4 + 20 * 40
+    pub version: u8, // 1
+    pub split_mode: SplitMode, // 1
+    pub original: Option<OriginalAmount>, // 1 + 24
+    pub status: ExpenseStatus, // 1
+    #[max_len(20)]
+    pub approvals: Vec<Pubkey>, // 4 + 20 * 32
+    pub dispute: Option<ExpenseDispute>, // 1 + 108
+    pub category: ExpenseCategory, // 1
+    #[max_len(5, 16)]
+    pub tags: Vec<String>, // 4 + 5 * (4 + 16)
+    /// Whether amount counts toward session budget, expenses recorded before budgeting don't
+    pub budgeted: bool, // 1
+    pub receipt: Option<ExpenseReceipt>, // 1 + 100
+    /// Member who paid the expense, owner being the member who recorded it
+    pub payer: Pubkey, // 32
 }
 
 impl ExpenseAccount {
     pub const SEED_PREFIX: &'static [u8; 7] = b"expense";
+    pub const VERSION: u8 = 1;
+    pub const MAX_PARTICIPANTS: usize = 20;
+    pub const MAX_DISPUTE_REASON_LEN: usize = 64;
+    pub const MAX_TAGS: usize = 5;
+    pub const MAX_TAG_LEN: usize = 16;
+    pub const MAX_RECEIPT_URI_LEN: usize = 64;
+
+    pub fn is_participant(&self, member: &Pubkey) -> bool {
+        self.participants.iter().any(|p| p.member == *member)
+    }
+
+    /**
+     * Removes a member from participants, re-splitting amount between remaining ones.
+     *
+     * @dev Percentage and exact shares become weights so that remaining participants keep their proportions
+     */
+    pub fn strip_participant(&mut self, member: &Pubkey) {
+        self.participants.retain(|p| p.member != *member);
+        self.approvals.retain(|approval| approval != member);
+
+        if matches!(self.split_mode, SplitMode::Percentage | SplitMode::Exact) {
+            self.split_mode = SplitMode::Weighted;
+        }
+        if self.split_mode == SplitMode::Weighted && self.participants.iter().all(|p| p.share == 0) {
+            self.split_mode = SplitMode::Equal;
+        }
+        if self.participants.is_empty() {
+            self.participants.push(ExpenseParticipant {
+                member: self.payer,
+                share: 1,
+            });
+        }
+    }
+
+    /**
+     * An expense is outstanding until approved and while disputed.
+     */
+    pub fn is_outstanding(&self) -> bool {
+        self.status != ExpenseStatus::Approved || self.dispute.is_some()
+    }
+
+    /**
+     * Checks whether participants approvals satisfy session approval mode.
+     */
+    pub fn is_approved(&self, expense_approval: &ExpenseApproval) -> bool {
+        let approvals = self
+            .participants
+            .iter()
+            .filter(|p| self.approvals.contains(&p.member))
+            .count();
+        match expense_approval {
+            ExpenseApproval::Disabled => true,
+            ExpenseApproval::AllParticipants => approvals == self.participants.len(),
+            ExpenseApproval::Quorum { approvals: quorum } => {
+                approvals >= (*quorum as usize).min(self.participants.len())
+            }
+        }
+    }
+
+    /**
+     * Ensures free-form tags fit into expense account.
+     */
+    pub fn validate_tags(tags: &[String]) -> Result<()> {
+        require!(tags.len() <= Self::MAX_TAGS, SolidrError::TooManyExpenseTags);
+        require!(
+            tags.iter().all(|tag| !tag.is_empty() && tag.len() <= Self::MAX_TAG_LEN),
+            SolidrError::InvalidExpenseTag
+        );
+        Ok(())
+    }
+
+    /**
+     * Ensures receipt storage URI fits into expense account.
+     */
+    pub fn validate_receipt(receipt: &Option<ExpenseReceipt>) -> Result<()> {
+        if let Some(receipt) = receipt {
+            require!(
+                !receipt.uri.is_empty() && receipt.uri.len() <= Self::MAX_RECEIPT_URI_LEN,
+                SolidrError::InvalidReceiptUri
+            );
+        }
+        Ok(())
+    }
+
+    /**
+     * Ensures participants shares are consistent with expense split mode.
+     */
+    pub fn validate_split(&self) -> Result<()> {
+        self.split_mode.validate(self.amount, &self.participants)
+    }
+
+    /**
+     * Splits expense amount between participants according to split mode.
+     *
+     * @dev Except for exact amounts, remaining minor units are assigned one by one to first participants
+     */
+    pub fn shares(&self) -> Vec<(Pubkey, u64)> {
+        if self.split_mode == SplitMode::Exact {
+            return self.participants.iter().map(|p| (p.member, p.share)).collect();
+        }
+
+        let weights: Vec<u128> = self
+            .participants
+            .iter()
+            .map(|p| match self.split_mode {
+                SplitMode::Equal => 1,
+                _ => p.share as u128,
+            })
+            .collect();
+        let total: u128 = weights.iter().sum();
+        if total == 0 {
+            return self.participants.iter().map(|p| (p.member, 0)).collect();
+        }
+
+        let mut shares: Vec<u64> = weights
+            .iter()
+            .map(|weight| (self.amount as u128 * weight / total) as u64)
+            .collect();
+        let mut remainder = self.amount - shares.iter().sum::<u64>();
+        for (share, weight) in shares.iter_mut().zip(weights.iter()) {
+            if remainder == 0 {
+                break;
+            }
+            if *weight > 0 {
+                *share += 1;
+                remainder -= 1;
+            }
+        }
+
+        self.participants
+            .iter()
+            .zip(shares)
+            .map(|(p, share)| (p.member, share))
+            .collect()
+    }
+}
+
+/**
+ * Defines how `share` of each participant is interpreted.
+ */
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub enum SplitMode {
+    /// Shares are ignored, amount is split equally
+    Equal,
+    /// Shares are relative weights
+    Weighted,
+    /// Shares are basis points summing to 10000
+    Percentage,
+    /// Shares are amounts in minor units summing to expense amount
+    Exact,
+}
+
+impl SplitMode {
+    pub const PERCENTAGE_TOTAL: u64 = 10_000;
+
+    /**
+     * Ensures participants shares are consistent with split mode for given amount.
+     */
+    pub fn validate(&self, amount: u64, participants: &[ExpenseParticipant]) -> Result<()> {
+        let total: u128 = participants.iter().map(|p| p.share as u128).sum();
+        match self {
+            SplitMode::Equal => {}
+            SplitMode::Weighted => {
+                require!(total > 0, SolidrError::WeightsMustBeGreaterThanZero)
+            }
+            SplitMode::Percentage => require!(
+                total == SplitMode::PERCENTAGE_TOTAL as u128,
+                SolidrError::PercentagesMustSumToHundred
+            ),
+            SplitMode::Exact => require!(
+                total == amount as u128,
+                SolidrError::ExactAmountsMustSumToTotal
+            ),
+        }
+        Ok(())
+    }
+
+    /**
+     * Share given to expense owner when not explicitly listed in participants.
+     */
+    pub fn default_share(&self) -> u64 {
+        match self {
+            SplitMode::Equal | SplitMode::Weighted => 1,
+            SplitMode::Percentage | SplitMode::Exact => 0,
+        }
+    }
+}
+
+/**
+ * Only approved expenses count toward members balances.
+ */
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub enum ExpenseStatus {
+    Approved,
+    Pending,
+    Rejected,
+}
+
+/**
+ * Spending category used by reports.
+ */
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub enum ExpenseCategory {
+    Other,
+    Food,
+    Transport,
+    Accommodation,
+    Entertainment,
+    Shopping,
+    Utilities,
+    Health,
+}
+
+impl ExpenseCategory {
+    pub const COUNT: usize = 8;
+}
+
+/**
+ * Contestation raised by a participant, freezing the expense until resolved.
+ */
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub struct ExpenseDispute {
+    pub by: Pubkey, // 32
+    pub date: i64,  // 8
+    #[max_len(64)]
+    pub reason: String, // 4 + 64
+}
+
+/**
+ * Proof of purchase stored off-chain, committed to by its SHA-256 digest.
+ */
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub struct ExpenseReceipt {
+    pub hash: [u8; 32], // 32
+    #[max_len(64)]
+    pub uri: String, // 4 + 64
+}
+
+/**
+ * Amount as written on the receipt when expense was paid in another currency than session's one.
+ */
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub struct OriginalAmount {
+    #[max_len(3)]
+    pub currency: String, // 4 + 3
+    pub amount: u64,        // 8
+    pub decimals: u8,       // 1
+    pub exchange_rate: u64, // 8
+}
+
+impl OriginalAmount {
+    /// Exchange rate is the price of one original currency unit in session currency, scaled by 10^9
+    pub const EXCHANGE_RATE_DECIMALS: u32 = 9;
+
+    /**
+     * Converts original amount into minor units of session currency, rounding half up.
+     */
+    pub fn convert(&self, session_decimals: u8) -> Result<u64> {
+        let numerator = (self.amount as u128)
+            .checked_mul(self.exchange_rate as u128)
+            .and_then(|value| value.checked_mul(10u128.pow(session_decimals as u32)))
+            .ok_or(SolidrError::Overflow)?;
+        let denominator = 10u128.pow(Self::EXCHANGE_RATE_DECIMALS + self.decimals as u32);
+
+        u64::try_from((numerator + denominator / 2) / denominator)
+            .map_err(|_| SolidrError::Overflow.into())
+    }
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub struct ExpenseParticipant {
+    pub member: Pubkey, // 32
+    pub share: u64,     // 8
+}
+
+/**
+ * Expense layout with floating point amount, only used by migration.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct ExpenseAccountV0 {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub date: i64,
+    pub owner: Pubkey,
+    pub amount: f32,
+    #[max_len(20)]
+    pub name: String,
+    #[max_len(10)]
+    pub participants: Vec<Pubkey>,
 }
 
 #[event]
 pub struct ExpenseAdded {
     pub session_id: u64,
     pub expense_id: u16,
+    pub amount: u64,
+    pub original: Option<OriginalAmount>,
+    pub status: ExpenseStatus,
+    pub category: ExpenseCategory,
+    pub tags: Vec<String>,
+    pub payer: Pubkey,
 }
 
 #[event]
 pub struct ExpenseUpdated {
     pub session_id: u64,
     pub expense_id: u16,
+    pub category: ExpenseCategory,
+    pub tags: Vec<String>,
+}
+
+#[event]
+pub struct ExpenseReceiptUpdated {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub receipt: Option<ExpenseReceipt>,
 }
 
 #[event]
@@ -37,11 +359,75 @@ pub struct ExpenseDeleted {
     pub expense_id: u16,
 }
 
+#[event]
+pub struct ExpenseApproved {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub addr: Pubkey,
+    pub status: ExpenseStatus,
+}
+
+#[event]
+pub struct ExpenseRejected {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub owner: Pubkey,
+    pub addr: Pubkey,
+}
+
+#[event]
+pub struct ExpenseDisputed {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub addr: Pubkey,
+    pub reason: String,
+}
+
+#[event]
+pub struct ExpenseDisputeResolved {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub addr: Pubkey,
+    pub amended: bool,
+}
+
+#[event]
+pub struct ExpenseOwnerReassigned {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct ExpensePayerReassigned {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub previous_payer: Pubkey,
+    pub new_payer: Pubkey,
+}
+
+#[event]
+pub struct ExpenseMigrated {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub version: u8,
+}
+
 #[event]
 pub struct ExpenseParticipantAdded {
     pub session_id: u64,
     pub expense_id: u16,
     pub member_pubkey: Pubkey,
+    pub share: u64,
+}
+
+#[event]
+pub struct ExpenseParticipantUpdated {
+    pub session_id: u64,
+    pub expense_id: u16,
+    pub member_pubkey: Pubkey,
+    pub share: u64,
 }
 
 #[event]
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::state::members::MemberRole;
+
+#[account]
+#[derive(InitSpace)]
+pub struct InvitationAccount {
+    // 8 discriminator
+    pub session_id: u64,         // 8
+    pub invitation_id: u16,      // 2
+    pub token_hash: [u8; 32],    // 32
+    pub expires_at: i64,         // 8
+    pub max_uses: u16,           // 2
+    pub remaining_uses: u16,     // 2
+    pub revoked: bool,           // 1
+    #[max_len(32)]
+    pub label: String, // 4 + 32
+    pub creator: Pubkey,         // 32
+    pub role: Option<MemberRole>, // 1 + 1
+}
+
+impl InvitationAccount {
+    pub const SEED_PREFIX: &'static [u8; 10] = b"invitation";
+    pub const MAX_LABEL_LEN: usize = 32;
+}
+
+#[event]
+pub struct InvitationCreated {
+    pub session_id: u64,
+    pub invitation_id: u16,
+    pub label: String,
+    pub creator: Pubkey,
+    pub role: Option<MemberRole>,
+    pub expires_at: i64,
+    pub max_uses: u16,
+}
+
+#[event]
+pub struct InvitationRevoked {
+    pub session_id: u64,
+    pub invitation_id: u16,
+}
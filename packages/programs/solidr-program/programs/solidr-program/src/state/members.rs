@@ -8,10 +8,42 @@ pub struct MemberAccount {
     #[max_len(40)]
     pub name: String, // 4 + 40
     pub is_admin: bool,  // 1
+    pub role: MemberRole, // 1
 }
 
 impl MemberAccount {
     pub const SEED_PREFIX: &'static [u8; 6] = b"member";
+
+    pub fn has_role(&self, roles: &[MemberRole]) -> bool {
+        roles.contains(&self.role)
+    }
+}
+
+/**
+ * Defines what a member is allowed to do in a session.
+ */
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub enum MemberRole {
+    /// Manages session, members and roles
+    Admin,
+    /// Manages refunds and payment settings
+    Treasurer,
+    /// Adds expenses and refunds
+    Member,
+    /// Can't add expenses, only settles debts
+    Viewer,
+}
+
+/**
+ * Member layout before roles were introduced, only used by migration.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct MemberAccountV0 {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    #[max_len(40)]
+    pub name: String,
+    pub is_admin: bool,
 }
 
 #[event]
@@ -20,6 +52,8 @@ pub struct MemberAdded {
     pub addr: Pubkey,
     pub name: String,
     pub is_admin: bool,
+    pub role: MemberRole,
+    pub invitation_id: Option<u16>,
 }
 #[event]
 pub struct MemberUpdated {
@@ -33,3 +67,33 @@ pub struct MemberDeleted {
     pub addr: Pubkey,
     pub name: String,
 }
+#[event]
+pub struct MemberRemoved {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    pub name: String,
+    pub expenses_count: u16,
+}
+#[event]
+pub struct MemberLeft {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    pub name: String,
+}
+#[event]
+pub struct RoleGranted {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    pub role: MemberRole,
+}
+#[event]
+pub struct RoleRevoked {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    pub role: MemberRole,
+}
+#[event]
+pub struct MemberMigrated {
+    pub session_id: u64,
+    pub addr: Pubkey,
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::state::members::MemberRole;
+
+#[account]
+#[derive(InitSpace)]
+pub struct ProposalAccount {
+    // 8 discriminator
+    pub session_id: u64,         // 8
+    pub proposal_id: u16,        // 2
+    pub date: i64,               // 8
+    pub proposer: Pubkey,        // 32
+    pub action: ProposalAction,  // 1 + 32
+    #[max_len(20)]
+    pub approvals: Vec<Pubkey>, // 4 + 20 * 32
+    pub executed: bool,         // 1
+}
+
+impl ProposalAccount {
+    pub const SEED_PREFIX: &'static [u8; 8] = b"proposal";
+    pub const MAX_APPROVALS: usize = 20;
+
+    pub fn has_approved(&self, addr: &Pubkey) -> bool {
+        self.approvals.iter().any(|approval| approval == addr)
+    }
+}
+
+/**
+ * Destructive actions requiring members approval when session has an approval threshold.
+ */
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub enum ProposalAction {
+    CloseSession,
+    DeleteSession,
+    DeleteMember { addr: Pubkey },
+    DeleteRefund { refund_id: u16 },
+    SetApprovalThreshold { threshold: u8 },
+    SetPriceFeed { price_feed_id: [u8; 32] },
+    ReopenSession,
+}
+
+impl ProposalAction {
+    /**
+     * Roles allowed to propose and execute the action, same as for direct execution.
+     */
+    pub fn allowed_roles(&self) -> &'static [MemberRole] {
+        match self {
+            ProposalAction::DeleteRefund { .. } | ProposalAction::SetPriceFeed { .. } => {
+                &[MemberRole::Admin, MemberRole::Treasurer]
+            }
+            _ => &[MemberRole::Admin],
+        }
+    }
+}
+
+#[event]
+pub struct ApprovalThresholdUpdated {
+    pub session_id: u64,
+    pub threshold: u8,
+}
+
+#[event]
+pub struct ProposalCreated {
+    pub session_id: u64,
+    pub proposal_id: u16,
+    pub action: ProposalAction,
+}
+
+#[event]
+pub struct ProposalApproved {
+    pub session_id: u64,
+    pub proposal_id: u16,
+    pub addr: Pubkey,
+    pub approvals_count: u8,
+}
+
+#[event]
+pub struct ProposalExecuted {
+    pub session_id: u64,
+    pub proposal_id: u16,
+}
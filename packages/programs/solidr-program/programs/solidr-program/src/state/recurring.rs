@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+
+use crate::state::expenses::{ExpenseCategory, ExpenseParticipant, SplitMode};
+
+#[account]
+#[derive(InitSpace)]
+pub struct RecurringExpenseAccount {
+    // 8 discriminator
+    pub session_id: u64,   // 8
+    pub recurring_id: u16, // 2
+    pub owner: Pubkey,     // 32
+    #[max_len(20)]
+    pub name: String, // 4 + 20
+    pub amount: u64,           // 8
+    pub split_mode: SplitMode, // 1
+    #[max_len(20)]
+    pub participants: Vec<ExpenseParticipant>, // 4 + 20 * 40
+    pub interval: i64,                // 8
+    pub next_due: i64,                // 8
+    pub status: RecurringStatus,      // 1
+    pub category: ExpenseCategory,    // 1
+    #[max_len(5, 16)]
+    pub tags: Vec<String>, // 4 + 5 * (4 + 16)
+}
+
+impl RecurringExpenseAccount {
+    pub const SEED_PREFIX: &'static [u8; 9] = b"recurring";
+    pub const MIN_INTERVAL: i64 = 86_400;
+
+    /**
+     * Moves next due date to the first occurrence not before given timestamp, skipping missed ones.
+     */
+    pub fn skip_missed(&mut self, now: i64) {
+        if self.next_due < now {
+            let missed = (now - self.next_due + self.interval - 1) / self.interval;
+            self.next_due += missed * self.interval;
+        }
+    }
+}
+
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub enum RecurringStatus {
+    Active,
+    Paused,
+}
+
+#[event]
+pub struct RecurringExpenseCreated {
+    pub session_id: u64,
+    pub recurring_id: u16,
+    pub amount: u64,
+    pub interval: i64,
+    pub next_due: i64,
+}
+
+#[event]
+pub struct RecurringExpenseMaterialized {
+    pub session_id: u64,
+    pub recurring_id: u16,
+    pub expense_id: u16,
+    pub next_due: i64,
+}
+
+#[event]
+pub struct RecurringExpensePaused {
+    pub session_id: u64,
+    pub recurring_id: u16,
+}
+
+#[event]
+pub struct RecurringExpenseResumed {
+    pub session_id: u64,
+    pub recurring_id: u16,
+    pub next_due: i64,
+}
+
+#[event]
+pub struct RecurringExpenseCancelled {
+    pub session_id: u64,
+    pub recurring_id: u16,
+}
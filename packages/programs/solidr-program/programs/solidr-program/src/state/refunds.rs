@@ -9,12 +9,32 @@ pub struct RefundAccount {
     pub date: i64,               // 8
     pub from: Pubkey,            // 32
     pub to: Pubkey,              // 32
-    pub amount: f32,             // 16
-    pub amount_in_lamports: u64, // 32
+    pub amount: u64,             // 8
+    pub amount_in_lamports: u64, // 8
+    pub version: u8,             // 1
+    pub mint: Option<Pubkey>,    // 1 + 32
+    pub token_amount: u64,       // 8
+    pub settlement_index: Option<u16>, // 1 + 2
+    pub settlement_plan_id: u32, // 4
 }
 
 impl RefundAccount {
     pub const SEED_PREFIX: &'static [u8; 6] = b"refund";
+    pub const VERSION: u8 = 1;
+}
+
+/**
+ * Refund layout with floating point amount, only used by migration.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct RefundAccountV0 {
+    pub session_id: u64,
+    pub refund_id: u16,
+    pub date: i64,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: f32,
+    pub amount_in_lamports: u64,
 }
 
 #[event]
@@ -27,3 +47,9 @@ pub struct RefundDeleted {
     pub session_id: u64,
     pub refund_id: u16,
 }
+#[event]
+pub struct RefundMigrated {
+    pub session_id: u64,
+    pub refund_id: u16,
+    pub version: u8,
+}
@@ -1,5 +1,7 @@
 use anchor_lang::prelude::*;
 
+use crate::errors::*;
+
 #[account]
 #[derive(InitSpace)]
 pub struct SessionAccount {
@@ -14,16 +16,86 @@ pub struct SessionAccount {
     pub refunds_count: u16, // 2
     pub status: SessionStatus, // 1
     pub invitation_hash: [u8; 32], // 32
+    pub decimals: u8, // 1
+    pub version: u8, // 1
+    pub mint: Option<Pubkey>, // 1 + 32
+    #[max_len(3)]
+    pub mint_currency: String, // 4 + 3
+    #[max_len(3)]
+    pub currency: String, // 4 + 3
+    pub price_feed_id: [u8; 32], // 32
+    pub members_count: u16, // 2
+    pub pending_admin: Option<Pubkey>, // 1 + 32
+    pub approval_threshold: u8, // 1
+    pub proposals_count: u16, // 2
+    pub expense_approval: ExpenseApproval, // 1 + 1
+    pub closed_at: i64, // 8
+    pub reopen_window: i64, // 8
+    pub invitations_count: u16, // 2
+    pub recurring_count: u16, // 2
+    pub budgeted: bool, // 1
 }
 
 impl SessionAccount {
     pub const SEED_PREFIX: &'static [u8; 7] = b"session";
+    pub const VERSION: u8 = 1;
+    pub const DEFAULT_CURRENCY: &'static str = "USD";
+    pub const DEFAULT_DECIMALS: u8 = 2;
+    pub const MAX_DECIMALS: u8 = 4;
+    /// Maximum absolute balance in minor units for a member to be considered settled
+    pub const SETTLED_BALANCE_TOLERANCE: u64 = 1;
+
+    /**
+     * Ensures currency is an ISO-4217 alphabetic code with a supported minor unit precision.
+     */
+    pub fn validate_currency(currency: &str, decimals: u8) -> Result<()> {
+        require!(
+            currency.len() == 3 && currency.bytes().all(|c| c.is_ascii_uppercase()),
+            SolidrError::InvalidCurrency
+        );
+        require!(
+            decimals <= SessionAccount::MAX_DECIMALS,
+            SolidrError::InvalidCurrencyDecimals
+        );
+        Ok(())
+    }
+}
+
+/**
+ * Session layout before amounts were stored in minor units, only used by migration.
+ */
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct SessionAccountV0 {
+    pub session_id: u64,
+    #[max_len(20)]
+    pub name: String,
+    #[max_len(80)]
+    pub description: String,
+    pub admin: Pubkey,
+    pub expenses_count: u16,
+    pub refunds_count: u16,
+    pub status: SessionStatus,
+    pub invitation_hash: [u8; 32],
 }
 
 #[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
 pub enum SessionStatus {
     Opened,
     Closed,
+    Settled,
+}
+
+/**
+ * Defines which participants approvals are required before an expense counts toward balances.
+ */
+#[derive(Clone, Copy, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub enum ExpenseApproval {
+    /// Expenses count toward balances as soon as added
+    Disabled,
+    /// Every participant must approve
+    AllParticipants,
+    /// Given number of participants must approve, or all of them if fewer
+    Quorum { approvals: u8 },
 }
 
 #[event]
@@ -31,6 +103,16 @@ pub struct SessionClosed {
     pub session_id: u64,
 }
 
+#[event]
+pub struct SessionSettled {
+    pub session_id: u64,
+}
+
+#[event]
+pub struct SessionReopened {
+    pub session_id: u64,
+}
+
 #[event]
 pub struct SessionUpdated {
     pub session_id: u64,
@@ -39,9 +121,48 @@ pub struct SessionUpdated {
 #[event]
 pub struct SessionOpened {
     pub session_id: u64,
+    pub currency: String,
+    pub decimals: u8,
 }
 
 #[event]
 pub struct SessionDeleted {
     pub session_id: u64,
 }
+
+#[event]
+pub struct SessionMintUpdated {
+    pub session_id: u64,
+    pub mint: Option<Pubkey>,
+}
+
+#[event]
+pub struct SessionPriceFeedUpdated {
+    pub session_id: u64,
+    pub price_feed_id: [u8; 32],
+}
+
+#[event]
+pub struct AdminProposed {
+    pub session_id: u64,
+    pub addr: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferred {
+    pub session_id: u64,
+    pub previous_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct ExpenseApprovalUpdated {
+    pub session_id: u64,
+    pub expense_approval: ExpenseApproval,
+}
+
+#[event]
+pub struct SessionMigrated {
+    pub session_id: u64,
+    pub version: u8,
+}
@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct SettlementAccount {
+    // 8 discriminator
+    pub session_id: u64, // 8
+    pub date: i64,       // 8
+    pub plan_id: u32,    // 4
+    #[max_len(30)]
+    pub transfers: Vec<SettlementTransfer>, // 4 + 30 * 80
+}
+
+impl SettlementAccount {
+    pub const SEED_PREFIX: &'static [u8; 10] = b"settlement";
+    pub const MAX_TRANSFERS: usize = 30;
+}
+
+#[derive(Clone, AnchorSerialize, AnchorDeserialize, InitSpace, PartialEq)]
+pub struct SettlementTransfer {
+    pub from: Pubkey, // 32
+    pub to: Pubkey,   // 32
+    pub amount: u64,  // 8
+    pub paid: u64,    // 8
+}
+
+impl SettlementTransfer {
+    pub fn is_fulfilled(&self) -> bool {
+        self.paid >= self.amount
+    }
+}
+
+#[event]
+pub struct SettlementComputed {
+    pub session_id: u64,
+    pub transfers_count: u16,
+}
+
+#[event]
+pub struct SettlementTransferFulfilled {
+    pub session_id: u64,
+    pub index: u16,
+    pub from: Pubkey,
+    pub to: Pubkey,
+    pub amount: u64,
+}
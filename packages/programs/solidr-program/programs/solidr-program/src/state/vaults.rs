@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+
+#[account]
+#[derive(InitSpace)]
+pub struct VaultAccount {
+    // 8 discriminator
+    pub session_id: u64,      // 8
+    pub epoch: u16,           // 2
+    pub total_deposited: u64, // 8
+    pub total_lamports: u64,  // 8
+}
+
+impl VaultAccount {
+    pub const SEED_PREFIX: &'static [u8; 5] = b"vault";
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct DepositAccount {
+    // 8 discriminator
+    pub session_id: u64, // 8
+    pub addr: Pubkey,    // 32
+    pub epoch: u16,      // 2
+    pub amount: u64,     // 8
+    pub lamports: u64,   // 8
+}
+
+impl DepositAccount {
+    pub const SEED_PREFIX: &'static [u8; 7] = b"deposit";
+}
+
+#[event]
+pub struct VaultDeposited {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    pub amount: u64,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct VaultWithdrawn {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    pub amount: u64,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct VaultPaidOut {
+    pub session_id: u64,
+    pub addr: Pubkey,
+    pub amount: u64,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct VaultSettled {
+    pub session_id: u64,
+    pub epoch: u16,
+    pub total_amount: u64,
+    pub total_lamports: u64,
+}
@@ -3,8 +3,37 @@ import * as anchor from '@coral-xyz/anchor';
 import { BN, Program, Wallet } from '@coral-xyz/anchor';
 import { assert } from 'chai';
 
-import { MemberBalance, MISSING_INVITATION_HASH, SessionMember, SessionStatus, Solidr, SolidrClient } from '../client';
-import { ACCOUNT_NOT_FOUND, ACCOUNT_NOT_FOUND as ACCOUNT_NOT_FOUND_ERROR, ACCOUNT_NOT_INITIALIZED, assertError } from './test.helpers';
+import {
+    DEFAULT_CURRENCY,
+    DEFAULT_DECIMALS,
+    ExpenseApprovalMode,
+    ExpenseCategory,
+    ExpenseStatus,
+    MemberBalance,
+    MemberRole,
+    MISSING_INVITATION_HASH,
+    ProposalActionType,
+    RecurringStatus,
+    SessionMember,
+    SessionStatus,
+    SOL_USD_PRICE_FEED_ID,
+    SOL_USD_PRICE_UPDATE_ACCOUNT,
+    Solidr,
+    SolidrClient,
+    SplitMode,
+} from '../client';
+import {
+    ACCOUNT_NOT_FOUND,
+    ACCOUNT_NOT_FOUND as ACCOUNT_NOT_FOUND_ERROR,
+    ACCOUNT_NOT_INITIALIZED,
+    assertError,
+    createMint,
+    createTokenAccount,
+    getTokenBalance,
+    LOW_CONFIDENCE_PRICE_UPDATE_ACCOUNT,
+    STALE_PRICE_UPDATE_ACCOUNT,
+    TOKEN_2022_PROGRAM_ID,
+} from './test.helpers';
 import { hashToken } from '../client/TokenHelpers';
 import { PublicKey } from '@solana/web3.js';
 
@@ -63,11 +92,13 @@ describe('solidr', () => {
             assert.deepEqual(session.status, SessionStatus.Opened);
             assert.equal(session.expensesCount, 0);
             assert.equal(session.invitationHash, MISSING_INVITATION_HASH);
+            assert.equal(session.decimals, DEFAULT_DECIMALS);
 
             const member = await client.getSessionMember(memberAccountAddress);
             assert.equal(member.name, 'Admin');
             assert.equal(member.addr.toString(), administrator.publicKey.toString());
             assert.isTrue(member.isAdmin);
+            assert.equal(member.role, MemberRole.Admin);
 
             const { sessionOpened } = events;
             assert.equal(sessionOpened[0].sessionId.toNumber(), expectedSessionId);
@@ -91,6 +122,7 @@ describe('solidr', () => {
             assert.deepEqual(session.status, SessionStatus.Opened);
             assert.equal(session.expensesCount, 0);
             assert.equal(session.invitationHash, MISSING_INVITATION_HASH);
+            assert.equal(session.decimals, DEFAULT_DECIMALS);
 
             const member = await client.getSessionMember(memberAccountAddress);
             assert.equal(member.name, 'Alice');
@@ -118,6 +150,128 @@ describe('solidr', () => {
         });
     });
 
+    describe('> session currency', () => {
+        it('> should store currency and decimals', async () => {
+            const {
+                events: { sessionOpened },
+                accounts: { sessionAccountPubkey },
+            } = await client.openSession(alice, 'Euro trip', '', 'Alice', 'EUR', 2);
+
+            const session = await client.getSession(sessionAccountPubkey);
+            assert.equal(session.currency, 'EUR');
+            assert.equal(session.decimals, 2);
+
+            assert.equal(sessionOpened[0].currency, 'EUR');
+            assert.equal(sessionOpened[0].decimals, 2);
+        });
+
+        it('> should store amounts with currency decimals', async () => {
+            const {
+                events: { sessionOpened },
+            } = await client.openSession(alice, 'Tokyo', '', 'Alice', 'JPY', 0);
+            const sessionId = new BN(sessionOpened[0].sessionId);
+
+            const {
+                accounts: { expenseAccountPubkey },
+            } = await client.addExpense(alice, sessionId, 'ramen', 1500);
+
+            const expense = await client.getExpense(expenseAccountPubkey);
+            assert.equal(expense.amount, 1500);
+            const internalExpense = await program.account.expenseAccount.fetch(expenseAccountPubkey);
+            assert.equal(internalExpense.amount.toNumber(), 1500);
+        });
+
+        it('> should reject amounts finer than currency decimals', async () => {
+            const {
+                events: { sessionOpened },
+            } = await client.openSession(alice, 'Tokyo', '', 'Alice', 'JPY', 0);
+            const sessionId = new BN(sessionOpened[0].sessionId);
+
+            await assertError(async () => client.addExpense(alice, sessionId, 'ramen', 1500.5), {
+                message: 'Amount exceeds session currency precision',
+            });
+        });
+
+        it('> should fail when called with invalid currency', async () => {
+            await assertError(async () => client.openSession(alice, 'name', '', 'Alice', 'usd', 2), {
+                code: 'InvalidCurrency',
+                message: 'Currency must be an ISO-4217 alphabetic code',
+            });
+        });
+
+        it('> should fail when called with too many decimals', async () => {
+            await assertError(async () => client.openSession(alice, 'name', '', 'Alice', 'USD', 5), {
+                code: 'InvalidCurrencyDecimals',
+                message: "Currency decimals can't exceed 4",
+            });
+        });
+
+        describe('> price feed', () => {
+            let sessionId: BN;
+
+            beforeEach(async () => {
+                const {
+                    events: { sessionOpened },
+                } = await client.openSession(alice, 'Euro trip', '', 'Alice', 'EUR', 2);
+                sessionId = new BN(sessionOpened[0].sessionId);
+                await client.addSessionMember(alice, sessionId, bob.publicKey, 'Bob');
+            });
+
+            it('> should fail to refund when price feed is not set', async () => {
+                await assertError(async () => client.sendRefunds(alice, sessionId, [{ amount: 10, to: bob.publicKey }]), {
+                    code: 'MissingPriceFeed',
+                    message: 'Session price feed is not configured',
+                });
+            });
+
+            it('> should refund once price feed is set', async () => {
+                await client.setSessionPriceFeed(alice, sessionId, SOL_USD_PRICE_FEED_ID);
+
+                const {
+                    accounts: { refundAccountPubkey },
+                } = await client.sendRefunds(alice, sessionId, [{ amount: 10, to: bob.publicKey }]);
+
+                const refund = await client.getRefund(refundAccountPubkey);
+                assert.isAtLeast(refund.amountInLamports.toNumber(), 1);
+            });
+
+            it('> should fail to set price feed when called by member', async () => {
+                await assertError(async () => client.setSessionPriceFeed(bob, sessionId, SOL_USD_PRICE_FEED_ID), {
+                    code: 'ForbiddenRole',
+                    message: "Member role doesn't allow this action",
+                });
+            });
+
+            it('> should only accept token pegged to session currency', async () => {
+                const mint = await createMint(provider, 6);
+
+                await assertError(async () => client.setSessionMint(alice, sessionId, mint), {
+                    code: 'MintCurrencyMismatch',
+                    message: "Token currency doesn't match session currency",
+                });
+
+                await client.setSessionMint(alice, sessionId, mint, 'EUR');
+                const session = await client.getSession(client.findSessionAccountAddress(sessionId));
+                assert.equal(session.mintCurrency, 'EUR');
+            });
+
+            it('> should change a pinned price feed once approved by majority', async () => {
+                await client.setSessionPriceFeed(alice, sessionId, SOL_USD_PRICE_FEED_ID);
+                const {
+                    events: { proposalCreated },
+                } = await client.createProposal(alice, sessionId, { type: ProposalActionType.SetPriceFeed, priceFeedId: SOL_USD_PRICE_FEED_ID });
+                await client.approveProposal(bob, sessionId, new BN(proposalCreated[0].proposalId));
+
+                const {
+                    events: { sessionPriceFeedUpdated, proposalExecuted },
+                } = await client.setSessionPriceFeed(alice, sessionId, SOL_USD_PRICE_FEED_ID);
+
+                assert.equal(proposalExecuted[0].proposalId, proposalCreated[0].proposalId);
+                assert.equal(sessionPriceFeedUpdated[0].sessionId.toNumber(), sessionId.toNumber());
+            });
+        });
+    });
+
     describe('> updateSession', () => {
         it('> should update session info when called by admin', async () => {
             // setup
@@ -156,6 +310,7 @@ describe('solidr', () => {
                 events: { sessionOpened },
             } = await client.openSession(administrator, 'name', 'description', 'Admin');
             const sessionId = sessionOpened[0].sessionId;
+            await client.addSessionMember(administrator, sessionId, alice.publicKey, 'Alice');
             await assertError(async () => client.updateSession(alice, sessionId, 'new name', 'new description'), {
                 code: 'ForbiddenAsNonAdmin',
                 message: 'Only session administrator is granted',
@@ -164,7 +319,7 @@ describe('solidr', () => {
     });
 
     describe('> closeSession', () => {
-        it('> should change session status and reset invitationHash', async () => {
+        it('> should change session status', async () => {
             const name = 'Session C';
             const description = 'New session C';
             // setup
@@ -173,16 +328,12 @@ describe('solidr', () => {
                 accounts: { sessionAccountPubkey },
             } = await client.openSession(administrator, name, description, 'Admin');
             const sid = openEvents.sessionOpened[0].sessionId;
-            const {
-                data: { token },
-            } = await client.generateSessionLink(administrator, sid);
             let session = await client.getSession(sessionAccountPubkey);
             assert.deepEqual(session.status, SessionStatus.Opened);
-            assert.equal(session.invitationHash, [...hashToken(token)].toString());
             // assert
             const { events: closeEvents } = await client.closeSession(administrator, sid);
             session = await client.getSession(sessionAccountPubkey);
-            assert.equal(session.invitationHash, MISSING_INVITATION_HASH);
+            assert.deepEqual(session.status, SessionStatus.Closed);
             assert.equal(closeEvents.sessionClosed[0].sessionId.toNumber(), sid);
         });
     });
@@ -203,6 +354,7 @@ describe('solidr', () => {
                 events: { sessionOpened },
             } = await client.openSession(administrator, 'name', 'description', 'Admin');
             const sessionId = sessionOpened[0].sessionId;
+            await client.addSessionMember(administrator, sessionId, bob.publicKey, 'Bob');
             await client.closeSession(administrator, sessionId);
             await assertError(async () => client.deleteSession(bob, sessionId), {
                 code: 'ForbiddenAsNonAdmin',
@@ -267,6 +419,9 @@ describe('solidr', () => {
             await assertError(async () => client.getSessionMember(charlieAccountPubkey), {
                 message: ACCOUNT_NOT_FOUND,
             });
+            await assertError(async () => client.getSessionMember(client.findSessionMemberAccountAddress(sessionId, administrator.publicKey)), {
+                message: ACCOUNT_NOT_FOUND,
+            });
         });
     });
 
@@ -292,6 +447,7 @@ describe('solidr', () => {
                 assert.equal(member.name, 'Bob');
                 assert.equal(member.addr.toString(), bob.publicKey.toString());
                 assert.isFalse(member.isAdmin);
+                assert.equal(member.role, MemberRole.Member);
 
                 assert.equal(memberAdded[0].sessionId.toNumber(), sessionId.toNumber());
                 assert.equal(memberAdded[0].name, 'Bob');
@@ -299,7 +455,8 @@ describe('solidr', () => {
             });
 
             it('> should fail when called non session administrator', async () => {
-                await assertError(async () => client.addSessionMember(bob, sessionId, bob.publicKey, 'Bob'), {
+                await client.addSessionMember(alice, sessionId, bob.publicKey, 'Bob');
+                await assertError(async () => client.addSessionMember(bob, sessionId, charlie.publicKey, 'Charlie'), {
                     code: 'ForbiddenAsNonAdmin',
                     message: `Only session administrator is granted`,
                 });
@@ -401,43 +558,169 @@ describe('solidr', () => {
                     message: ACCOUNT_NOT_FOUND,
                 });
             });
-        });
+            it('> should failed when member balance is not settled', async () => {
+                await client.addSessionMember(alice, sessionId, bob.publicKey, 'Bob');
+                await client.addExpense(alice, sessionId, 'exp', 20, [bob.publicKey]);
+                await client.closeSession(alice, sessionId);
+
+                await assertError(async () => client.deleteSessionMember(alice, sessionId, bob.publicKey), {
+                    code: 'MemberBalanceNotZero',
+                    message: 'Member balance must be zero',
+                });
+            });
+            it('> should failed when member is still involved in expenses', async () => {
+                await client.addSessionMember(alice, sessionId, bob.publicKey, 'Bob');
+                await client.addExpense(alice, sessionId, 'exp', 20, [bob.publicKey]);
+                await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey }]);
+                await client.closeSession(alice, sessionId);
+
+                await assertError(async () => client.deleteSessionMember(alice, sessionId, bob.publicKey), {
+                    code: 'MemberInvolvedInExpenses',
+                    message: 'Member is still involved in expenses',
+                });
+            });
+            it('> should failed when member is involved in pending expenses', async () => {
+                await client.addSessionMember(alice, sessionId, bob.publicKey, 'Bob');
+                await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.AllParticipants });
+                await client.addExpense(alice, sessionId, 'exp', 20, [bob.publicKey]);
+                await client.closeSession(alice, sessionId);
 
-        describe('> create invitation link', () => {
-            it('> should fail with invalid sessionId', async () => {
-                await assertError(async () => client.generateSessionLink(alice, new BN(100)), {
-                    code: 'AccountNotInitialized',
-                    message: `The program expected this account to be already initialized`,
+                await assertError(async () => client.deleteSessionMember(alice, sessionId, bob.publicKey), {
+                    code: 'MemberHasOutstandingExpenses',
+                    message: 'Member is involved in expenses pending approval or disputed',
                 });
             });
+        });
 
+        describe('> invitations', () => {
             it('> should fail when called with non administrator', async () => {
-                await assertError(async () => client.generateSessionLink(bob, sessionId), {
+                await client.addSessionMember(alice, sessionId, bob.publicKey, 'Bob');
+                await assertError(async () => client.createInvitation(bob, sessionId), {
                     code: 'ForbiddenAsNonAdmin',
                     message: `Only session administrator is granted`,
                 });
             });
 
-            it('> should prevent anybody to join session without generated token', async () => {
-                await assertError(async () => client.joinSessionAsMember(bob, sessionId, 'Bob', 'bad_token'), {
-                    code: 'MissingInvitationHash',
-                    message: `Missing invitation link hash`,
+            it('> should fail to create an already expired invitation', async () => {
+                await assertError(async () => client.createInvitation(alice, sessionId, { expiresAt: new Date(Date.now() - 3600 * 1000) }), {
+                    code: 'InvitationExpired',
+                    message: `Invitation has expired`,
+                });
+            });
+
+            it('> should fail to create an invitation without any use', async () => {
+                await assertError(async () => client.createInvitation(alice, sessionId, { maxUses: 0 }), {
+                    code: 'InvalidInvitation',
+                    message: `Invitation must have a token hash and at least one use`,
+                });
+            });
+
+            it('> should create invitation with expiry and usage limit', async () => {
+                const expiresAt = new Date(Math.floor(Date.now() / 1000 + 3600) * 1000);
+                const {
+                    data: { token },
+                    accounts: { invitationAccountPubkey },
+                } = await client.createInvitation(alice, sessionId, { expiresAt, maxUses: 2 });
+
+                const invitation = await client.getInvitation(invitationAccountPubkey);
+                assert.equal(invitation.invitationId, 0);
+                assert.equal(invitation.tokenHash, [...hashToken(token)].toString());
+                assert.equal(invitation.expiresAt?.getTime(), expiresAt.getTime());
+                assert.equal(invitation.maxUses, 2);
+                assert.equal(invitation.remainingUses, 2);
+                assert.isFalse(invitation.revoked);
+            });
+
+            it('> should fail to create invitation with too long label', async () => {
+                await assertError(async () => client.createInvitation(alice, sessionId, { label: 'x'.repeat(33) }), {
+                    code: 'InvitationLabelTooLong',
+                    message: `Invitation label can't exceed 32 characters`,
                 });
             });
 
-            it('> should set new invitation hash in session account', async () => {
+            it('> should create named invitations with label, creator and role', async () => {
+                await client.createInvitation(alice, sessionId, { label: 'Family', maxUses: 5 });
+                await client.createInvitation(alice, sessionId, { label: 'Accountants', role: MemberRole.Treasurer });
+
+                const invitations = await client.listSessionInvitations(sessionId);
+                assert.equal(invitations.length, 2);
+                assert.equal(invitations[0].invitationId, 0);
+                assert.equal(invitations[0].label, 'Family');
+                assert.equal(invitations[0].creator.toString(), alice.publicKey.toString());
+                assert.isNull(invitations[0].role);
+                assert.equal(invitations[1].invitationId, 1);
+                assert.equal(invitations[1].label, 'Accountants');
+                assert.equal(invitations[1].role, MemberRole.Treasurer);
+            });
+
+            it('> should allow joining through concurrent invitations and record the one used', async () => {
+                const {
+                    data: { token: familyToken },
+                } = await client.createInvitation(alice, sessionId, { label: 'Family' });
+                const {
+                    data: { token: accountantsToken },
+                } = await client.createInvitation(alice, sessionId, { label: 'Accountants', role: MemberRole.Treasurer });
+
+                const {
+                    accounts: { memberAccountAddress: bobAccountAddress },
+                    events: { memberAdded: bobAdded },
+                } = await client.joinSessionAsMember(bob, sessionId, 'Bob', familyToken, new BN(0));
+                const {
+                    accounts: { memberAccountAddress: charlieAccountAddress },
+                    events: { memberAdded: charlieAdded },
+                } = await client.joinSessionAsMember(charlie, sessionId, 'Charlie', accountantsToken, new BN(1));
+
+                assert.equal(bobAdded[0].invitationId, 0);
+                assert.equal(charlieAdded[0].invitationId, 1);
+                const bobMember = await client.getSessionMember(bobAccountAddress);
+                assert.equal(bobMember.role, MemberRole.Member);
+                const charlieMember = await client.getSessionMember(charlieAccountAddress);
+                assert.equal(charlieMember.role, MemberRole.Treasurer);
+            });
+
+            it('> should record the invitation looked up from the token when joining', async () => {
+                await client.createInvitation(alice, sessionId, { label: 'Family' });
+                const {
+                    data: { token },
+                } = await client.createInvitation(alice, sessionId, { label: 'Friends' });
+                const {
+                    events: { memberAdded },
+                } = await client.joinSessionAsMember(bob, sessionId, 'Bob', token);
+
+                assert.equal(memberAdded[0].invitationId, 1);
+            });
+
+            it('> should decrement remaining uses when a member joins', async () => {
+                const {
+                    data: { token },
+                    accounts: { invitationAccountPubkey },
+                } = await client.createInvitation(alice, sessionId, { maxUses: 2 });
+                const {
+                    accounts: { memberAccountAddress },
+                } = await client.joinSessionAsMember(bob, sessionId, 'Bob', token, new BN(0));
+
+                const member = await client.getSessionMember(memberAccountAddress);
+                assert.equal(member.addr.toString(), bob.publicKey.toString());
+                const invitation = await client.getInvitation(invitationAccountPubkey);
+                assert.equal(invitation.remainingUses, 1);
+            });
+
+            it('> should prevent anybody to join with an exhausted invitation', async () => {
                 const {
                     data: { token },
-                    accounts: { sessionAccountPubkey },
-                } = await client.generateSessionLink(alice, sessionId);
-                const session = await client.getSession(sessionAccountPubkey);
-                assert.equal(session.invitationHash, [...hashToken(token)].toString());
+                } = await client.createInvitation(alice, sessionId, { maxUses: 1 });
+                await client.joinSessionAsMember(bob, sessionId, 'Bob', token, new BN(0));
+
+                await assertError(async () => client.joinSessionAsMember(charlie, sessionId, 'Charlie', token, new BN(0)), {
+                    code: 'InvitationExhausted',
+                    message: `Invitation has no remaining use`,
+                });
             });
 
-            it('> should allow anybody to join session as member with correct token', async () => {
+            it('> should allow anybody to join session with correct token only', async () => {
                 const {
                     data: { token },
-                } = await client.generateSessionLink(alice, sessionId);
+                } = await client.createInvitation(alice, sessionId);
                 const {
                     accounts: { memberAccountAddress },
                 } = await client.joinSessionAsMember(bob, sessionId, 'Bob', token);
@@ -448,13 +731,45 @@ describe('solidr', () => {
                 assert.isFalse(member.isAdmin);
             });
 
-            it('> should prevent anybody to join session with wrong token', async () => {
-                await client.generateSessionLink(alice, sessionId);
+            it('> should prevent anybody to join session without invitation', async () => {
                 await assertError(async () => client.joinSessionAsMember(bob, sessionId, 'Bob', 'bad_token'), {
+                    message: `Invitation not found`,
+                });
+            });
+
+            it('> should prevent anybody to join with a wrong invitation token', async () => {
+                await client.createInvitation(alice, sessionId);
+
+                await assertError(async () => client.joinSessionAsMember(bob, sessionId, 'Bob', 'bad_token', new BN(0)), {
                     code: 'InvalidInvitationHash',
                     message: `Invalid invitation link hash`,
                 });
             });
+
+            it('> should prevent anybody to join with a revoked invitation', async () => {
+                const {
+                    data: { token },
+                    accounts: { invitationAccountPubkey },
+                } = await client.createInvitation(alice, sessionId);
+                await client.revokeInvitation(alice, sessionId, new BN(0));
+
+                const invitation = await client.getInvitation(invitationAccountPubkey);
+                assert.isTrue(invitation.revoked);
+                await assertError(async () => client.joinSessionAsMember(bob, sessionId, 'Bob', token, new BN(0)), {
+                    code: 'InvitationRevoked',
+                    message: `Invitation has been revoked`,
+                });
+            });
+
+            it('> should fail to revoke an already revoked invitation', async () => {
+                await client.createInvitation(alice, sessionId);
+                await client.revokeInvitation(alice, sessionId, new BN(0));
+
+                await assertError(async () => client.revokeInvitation(alice, sessionId, new BN(0)), {
+                    code: 'InvitationRevoked',
+                    message: `Invitation has been revoked`,
+                });
+            });
         });
 
         context('> two session members are registered', () => {
@@ -463,248 +778,422 @@ describe('solidr', () => {
                 await client.addSessionMember(alice, sessionId, charlie.publicKey, 'charlie');
             });
 
-            describe('> addExpense', () => {
-                it('> should fail when called with invalid session id', async () => {
-                    const invalidSessionId = new BN(666);
-                    await assertError(async () => client.addExpense(alice, invalidSessionId, 'name', 10), {
-                        message: ACCOUNT_NOT_FOUND_ERROR,
-                    });
+            describe('> roles', () => {
+                it('> should grant role when called by admin', async () => {
+                    const {
+                        accounts: { memberAccountPubkey },
+                        events: { roleGranted },
+                    } = await client.grantRole(alice, sessionId, bob.publicKey, MemberRole.Treasurer);
+
+                    const member = await client.getSessionMember(memberAccountPubkey);
+                    assert.equal(member.role, MemberRole.Treasurer);
+                    assert.equal(roleGranted[0].addr.toString(), bob.publicKey.toString());
                 });
 
-                it('> should fail when called with amount equals to 0', async () => {
-                    await assertError(async () => client.addExpense(alice, sessionId, 'name', 0), {
-                        code: 'ExpenseAmountMustBeGreaterThanZero',
-                        message: `Expense amount must be greater than zero`,
-                    });
+                it('> should reset role to member when revoked', async () => {
+                    await client.grantRole(alice, sessionId, bob.publicKey, MemberRole.Viewer);
+
+                    const {
+                        accounts: { memberAccountPubkey },
+                        events: { roleRevoked },
+                    } = await client.revokeRole(alice, sessionId, bob.publicKey);
+
+                    const member = await client.getSessionMember(memberAccountPubkey);
+                    assert.equal(member.role, MemberRole.Member);
+                    assert.equal(roleRevoked[0].addr.toString(), bob.publicKey.toString());
                 });
 
-                it('> should fail when called with to long name', async () => {
-                    const longName = _.times(21, () => 'X').join('');
-                    await assertError(async () => client.addExpense(alice, sessionId, longName, 10), {
-                        code: 'ExpenseNameTooLong',
-                        message: `Expense's name can't exceed 20 characters`,
+                it('> should fail to grant role when called by non admin', async () => {
+                    await assertError(async () => client.grantRole(bob, sessionId, charlie.publicKey, MemberRole.Treasurer), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
                     });
                 });
 
-                it('> should failed when called by a non member', async () => {
-                    await assertError(async () => client.addExpense(paul, sessionId, 'Paul', 10), {
-                        code: 'AccountNotInitialized',
-                        message: `The program expected this account to be already initialized`,
+                it('> should fail to change role of session admin', async () => {
+                    await client.grantRole(alice, sessionId, bob.publicKey, MemberRole.Admin);
+
+                    await assertError(async () => client.revokeRole(bob, sessionId, alice.publicKey), {
+                        code: 'CannotChangeSessionAdminRole',
+                        message: "Session administrator role can't be changed",
                     });
                 });
 
-                it('> should succeed when called by alice ', async () => {
-                    const expectedExpenseId = 0;
-                    const name = 'expense1';
-                    const amount = 10.3;
-                    const timestampBefore = Math.floor(Date.now()) - 10000; // sometimes, this timestamp is bigger than that set in expense !?
+                it('> should prevent viewer to add expense', async () => {
+                    await client.grantRole(alice, sessionId, bob.publicKey, MemberRole.Viewer);
+
+                    await assertError(async () => client.addExpense(bob, sessionId, 'exp', 10), {
+                        code: 'ForbiddenRole',
+                        message: "Member role doesn't allow this action",
+                    });
+                });
 
+                it('> should allow treasurer to delete refund', async () => {
+                    await client.grantRole(alice, sessionId, bob.publicKey, MemberRole.Treasurer);
                     const {
-                        events,
-                        accounts: { expenseAccountPubkey },
-                    } = await client.addExpense(alice, sessionId, name, amount);
+                        accounts: { refundAccountPubkey },
+                        events: { refundAdded },
+                    } = await client.sendRefunds(charlie, sessionId, [{ amount: 10, to: alice.publicKey }]);
+                    await client.closeSession(alice, sessionId);
 
-                    const expense = await client.getExpense(expenseAccountPubkey);
-                    assert.equal(expense.name, name);
-                    assert.equal(expense.amount, amount);
-                    assert.equal(expense.owner.toString(), alice.publicKey.toString());
-                    assert.isAtLeast(expense.date.getTime(), timestampBefore);
-                    assert.isAtMost(expense.date.getTime(), Math.floor(Date.now()));
-                    assert.lengthOf(expense.participants, 1);
-                    assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+                    await client.deleteRefund(bob, sessionId, new BN(refundAdded[0].refundId));
 
-                    const { expenseAdded } = events;
-                    assert.equal(expenseAdded[0].sessionId.toNumber(), sessionId);
-                    assert.equal(expenseAdded[0].expenseId, expectedExpenseId);
+                    await assertError(async () => client.getRefund(refundAccountPubkey), {
+                        message: ACCOUNT_NOT_FOUND,
+                    });
                 });
 
-                it('> should succeed when called by a member', async () => {
-                    const expectedExpenseId = 0;
-                    const name = 'expense1';
-                    const amount = 10.45;
-                    const timestampBefore = Math.floor(Date.now()) - 10000;
-
+                it('> should prevent member to delete refund', async () => {
                     const {
-                        events,
-                        accounts: { expenseAccountPubkey },
-                    } = await client.addExpense(bob, sessionId, name, amount);
-
-                    const expense = await client.getExpense(expenseAccountPubkey);
-                    assert.equal(expense.name, name);
-                    assert.equal(expense.amount, amount);
-                    assert.equal(expense.owner.toString(), bob.publicKey.toString());
-                    assert.isAtLeast(expense.date.getTime(), timestampBefore);
-                    assert.isAtMost(expense.date.getTime(), Math.floor(Date.now()));
-                    assert.lengthOf(expense.participants, 1);
-                    assert.equal(expense.participants[0].toString(), bob.publicKey.toString());
+                        events: { refundAdded },
+                    } = await client.sendRefunds(charlie, sessionId, [{ amount: 10, to: alice.publicKey }]);
+                    await client.closeSession(alice, sessionId);
 
-                    const { expenseAdded } = events;
-                    assert.equal(expenseAdded[0].sessionId.toNumber(), sessionId);
-                    assert.equal(expenseAdded[0].expenseId, expectedExpenseId);
+                    await assertError(async () => client.deleteRefund(bob, sessionId, new BN(refundAdded[0].refundId)), {
+                        code: 'ForbiddenRole',
+                        message: "Member role doesn't allow this action",
+                    });
                 });
+            });
 
-                describe('> add expense with participants', () => {
-                    const name = 'expense1';
-                    const amount = 10;
+            describe('> admin transfer', () => {
+                it('> should record pending admin when proposed by admin', async () => {
+                    const {
+                        accounts: { sessionAccountPubkey },
+                        events: { adminProposed },
+                    } = await client.proposeAdmin(alice, sessionId, bob.publicKey);
+
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.pendingAdmin.toString(), bob.publicKey.toString());
+                    assert.equal(session.admin.toString(), alice.publicKey.toString());
+                    assert.equal(adminProposed[0].addr.toString(), bob.publicKey.toString());
+                });
 
-                    it('> should fail when called with invalid session id', async () => {
-                        await assertError(async () => client.addExpense(alice, new BN(666), name, amount, [bob.publicKey]), {
-                            message: ACCOUNT_NOT_FOUND,
-                        });
+                it('> should fail to propose admin when called by non admin', async () => {
+                    await assertError(async () => client.proposeAdmin(bob, sessionId, bob.publicKey), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
                     });
+                });
 
-                    it('> should succeed with one member', async () => {
-                        const {
-                            accounts: { expenseAccountPubkey },
-                            events: { expenseParticipantAdded },
-                        } = await client.addExpense(alice, sessionId, name, amount, [bob.publicKey]);
+                it('> should fail to propose admin when called by another member with Admin role', async () => {
+                    await client.grantRole(alice, sessionId, charlie.publicKey, MemberRole.Admin);
 
-                        const expense = await client.getExpense(expenseAccountPubkey);
-                        assert.lengthOf(expense.participants, 2);
-                        assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
-                        assert.equal(expense.participants[1].toString(), bob.publicKey.toString());
+                    await assertError(async () => client.proposeAdmin(charlie, sessionId, bob.publicKey), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
+                    });
+                });
 
-                        assert.equal(expenseParticipantAdded[0].expenseId, 0);
-                        assert.equal(expenseParticipantAdded[0].memberPubkey.toString(), bob.publicKey.toString());
+                it('> should fail to propose current admin', async () => {
+                    await assertError(async () => client.proposeAdmin(alice, sessionId, alice.publicKey), {
+                        code: 'AlreadySessionAdmin',
+                        message: 'Member is already session administrator',
                     });
+                });
 
-                    it('> should succeed with two members', async () => {
-                        const {
-                            accounts: { expenseAccountPubkey },
-                            events: { expenseParticipantAdded },
-                        } = await client.addExpense(alice, sessionId, name, amount, [bob.publicKey, charlie.publicKey]);
+                it('> should fail to propose admin when session is closed', async () => {
+                    await client.closeSession(alice, sessionId);
 
-                        const expense = await client.getExpense(expenseAccountPubkey);
-                        assert.lengthOf(expense.participants, 3);
-                        assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
-                        assert.equal(expense.participants[1].toString(), bob.publicKey.toString());
-                        assert.equal(expense.participants[2].toString(), charlie.publicKey.toString());
+                    await assertError(async () => client.proposeAdmin(alice, sessionId, bob.publicKey), {
+                        code: 'SessionClosed',
+                        message: 'Session is closed',
+                    });
+                });
 
-                        assert.equal(expenseParticipantAdded[0].expenseId, 0);
-                        assert.equal(expenseParticipantAdded[0].memberPubkey.toString(), bob.publicKey.toString());
-                        assert.equal(expenseParticipantAdded[1].expenseId, 0);
-                        assert.equal(expenseParticipantAdded[1].memberPubkey.toString(), charlie.publicKey.toString());
-                    });
+                it('> should fail to accept admin when session is closed', async () => {
+                    await client.proposeAdmin(alice, sessionId, bob.publicKey);
+                    await client.closeSession(alice, sessionId);
 
-                    it('> should succeed but not add non session member', async () => {
-                        const {
-                            accounts: { expenseAccountPubkey },
-                            events: { expenseParticipantAdded },
-                        } = await client.addExpense(alice, sessionId, name, amount, [paul.publicKey]);
+                    await assertError(async () => client.acceptAdmin(bob, sessionId), {
+                        code: 'SessionClosed',
+                        message: 'Session is closed',
+                    });
+                });
 
-                        const expense = await client.getExpense(expenseAccountPubkey);
-                        assert.lengthOf(expense.participants, 1);
-                        assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+                it('> should fail to accept admin when signer is not the proposed one', async () => {
+                    await client.proposeAdmin(alice, sessionId, bob.publicKey);
 
-                        assert.isUndefined(expenseParticipantAdded);
+                    await assertError(async () => client.acceptAdmin(charlie, sessionId), {
+                        code: 'NotPendingAdmin',
+                        message: "Signer isn't the proposed session administrator",
                     });
                 });
+
+                it('> should transfer administration when accepted', async () => {
+                    await client.proposeAdmin(alice, sessionId, bob.publicKey);
+
+                    const {
+                        accounts: { sessionAccountPubkey },
+                        events: { adminTransferred },
+                    } = await client.acceptAdmin(bob, sessionId);
+
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.admin.toString(), bob.publicKey.toString());
+                    assert.isNull(session.pendingAdmin);
+                    assert.equal(adminTransferred[0].previousAdmin.toString(), alice.publicKey.toString());
+                    assert.equal(adminTransferred[0].newAdmin.toString(), bob.publicKey.toString());
+
+                    const previousAdmin = await client.getSessionMember(client.findSessionMemberAccountAddress(sessionId, alice.publicKey));
+                    assert.isFalse(previousAdmin.isAdmin);
+                    assert.equal(previousAdmin.role, MemberRole.Member);
+                    const newAdmin = await client.getSessionMember(client.findSessionMemberAccountAddress(sessionId, bob.publicKey));
+                    assert.isTrue(newAdmin.isAdmin);
+                    assert.equal(newAdmin.role, MemberRole.Admin);
+                });
             });
 
-            describe('> updateExpense', () => {
-                let currentExpenseId: BN;
-                let currentExpenseAccountPubkey: PublicKey;
+            describe('> approval threshold', () => {
+                it('> should set approval threshold when called by admin', async () => {
+                    const {
+                        accounts: { sessionAccountPubkey },
+                        events: { approvalThresholdUpdated },
+                    } = await client.setApprovalThreshold(alice, sessionId, 2);
 
-                beforeEach(async () => {
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.approvalThreshold, 2);
+                    assert.equal(approvalThresholdUpdated[0].threshold, 2);
+                });
+
+                it('> should fail when threshold exceeds members count', async () => {
+                    await assertError(async () => client.setApprovalThreshold(alice, sessionId, 4), {
+                        code: 'InvalidApprovalThreshold',
+                        message: "Approval threshold can't exceed members count nor 20",
+                    });
+                });
+
+                it('> should fail when called by non admin', async () => {
+                    await assertError(async () => client.setApprovalThreshold(bob, sessionId, 2), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
+                    });
+                });
+
+                it('> should require approved proposal to close session', async () => {
+                    await client.setApprovalThreshold(alice, sessionId, 2);
+
+                    await assertError(async () => client.closeSession(alice, sessionId), {
+                        code: 'ApprovalRequired',
+                        message: 'Action requires a proposal approved by enough members',
+                    });
+                });
+
+                it('> should fail when proposal has not enough approvals', async () => {
+                    await client.setApprovalThreshold(alice, sessionId, 3);
                     const {
-                        accounts: { expenseAccountPubkey },
-                        events: { expenseAdded },
-                    } = await client.addExpense(alice, sessionId, 'exp 3', 200, [charlie.publicKey]);
-                    currentExpenseId = new BN(expenseAdded[0].expenseId);
-                    currentExpenseAccountPubkey = expenseAccountPubkey;
+                        events: { proposalCreated },
+                    } = await client.createProposal(alice, sessionId, { type: ProposalActionType.CloseSession });
+                    await client.approveProposal(bob, sessionId, new BN(proposalCreated[0].proposalId));
+
+                    await assertError(async () => client.closeSession(alice, sessionId), {
+                        code: 'ApprovalRequired',
+                        message: 'Action requires a proposal approved by enough members',
+                    });
                 });
 
-                it("> should update expense when I'm the owner", async () => {
-                    const updatedName = 'exp 3 updated';
-                    const updatedAmount = 100.37;
+                it('> should close session once proposal reaches threshold', async () => {
+                    await client.setApprovalThreshold(alice, sessionId, 2);
                     const {
-                        events: { expenseUpdated },
-                    } = await client.updateExpense(alice, sessionId, currentExpenseId, updatedName, updatedAmount, [bob.publicKey]);
-                    const expense = await client.getExpense(currentExpenseAccountPubkey);
+                        accounts: { proposalAccountPubkey },
+                    } = await client.createProposal(alice, sessionId, { type: ProposalActionType.CloseSession });
+                    const proposal = await client.getProposal(proposalAccountPubkey);
+                    assert.sameMembers(
+                        proposal.approvals.map((approval) => approval.toString()),
+                        [alice.publicKey.toString()],
+                    );
 
-                    assert.equal(expense.name, updatedName);
-                    assert.equal(expense.amount, updatedAmount);
-                    assert.lengthOf(expense.participants, 2);
-                    assert.sameDeepMembers(expense.participants, [alice.publicKey, bob.publicKey]);
+                    const {
+                        events: { proposalApproved },
+                    } = await client.approveProposal(bob, sessionId, new BN(proposal.proposalId));
+                    assert.equal(proposalApproved[0].approvalsCount, 2);
 
-                    assert.equal(expenseUpdated[0].sessionId.toNumber(), sessionId);
-                    assert.equal(expenseUpdated[0].expenseId, currentExpenseId);
+                    const {
+                        accounts: { sessionAccountPubkey },
+                        events: { proposalExecuted },
+                    } = await client.closeSession(alice, sessionId);
+
+                    assert.equal(proposalExecuted[0].proposalId, proposal.proposalId);
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.status, SessionStatus.Closed);
+                    assert.isTrue((await client.getProposal(proposalAccountPubkey)).executed);
                 });
 
-                it('> should fail when called with non owner of expense', async () => {
-                    await assertError(async () => client.updateExpense(bob, sessionId, currentExpenseId, 'exp 3 updated', 100, [bob.publicKey]), {
-                        code: 'NotExpenseOwner',
-                        message: 'Only expense owner can update or delete expense',
+                it('> should fail when member approves twice', async () => {
+                    const {
+                        events: { proposalCreated },
+                    } = await client.createProposal(alice, sessionId, { type: ProposalActionType.CloseSession });
+
+                    await assertError(async () => client.approveProposal(alice, sessionId, new BN(proposalCreated[0].proposalId)), {
+                        code: 'AlreadyApproved',
+                        message: 'Member has already approved this proposal',
                     });
                 });
 
-                it('> should fail when called with invalid session id', async () => {
-                    await assertError(async () => client.updateExpense(alice, new BN(666), currentExpenseId, 'exp 3 updated', 100, [bob.publicKey]), {
-                        message: ACCOUNT_NOT_FOUND,
+                it('> should fail to create proposal when role is not allowed', async () => {
+                    await assertError(async () => client.createProposal(bob, sessionId, { type: ProposalActionType.DeleteSession }), {
+                        code: 'ForbiddenRole',
+                        message: "Member role doesn't allow this action",
                     });
                 });
-            });
 
-            describe('> deleteExpense', () => {
-                let currentExpenseId: BN;
-                let currentExpenseAccountPubkey: PublicKey;
+                it('> should require approval to lower threshold', async () => {
+                    await client.setApprovalThreshold(alice, sessionId, 2);
+                    await assertError(async () => client.setApprovalThreshold(alice, sessionId, 0), {
+                        code: 'ApprovalRequired',
+                        message: 'Action requires a proposal approved by enough members',
+                    });
 
-                beforeEach(async () => {
                     const {
-                        accounts: { expenseAccountPubkey },
-                        events: { expenseAdded },
-                    } = await client.addExpense(alice, sessionId, 'exp 3', 200, [charlie.publicKey]);
-                    currentExpenseId = new BN(expenseAdded[0].expenseId);
-                    currentExpenseAccountPubkey = expenseAccountPubkey;
+                        events: { proposalCreated },
+                    } = await client.createProposal(alice, sessionId, { type: ProposalActionType.SetApprovalThreshold, threshold: 0 });
+                    await client.approveProposal(charlie, sessionId, new BN(proposalCreated[0].proposalId));
+                    const {
+                        accounts: { sessionAccountPubkey },
+                    } = await client.setApprovalThreshold(alice, sessionId, 0);
+
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.approvalThreshold, 0);
                 });
 
-                it("> should delete expense when I'm the owner", async () => {
+                it('> should delete refund with approved proposal', async () => {
                     const {
-                        events: { expenseDeleted },
-                    } = await client.deleteExpense(alice, sessionId, currentExpenseId);
+                        accounts: { refundAccountPubkey },
+                        events: { refundAdded },
+                    } = await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey }]);
+                    await client.setApprovalThreshold(alice, sessionId, 2);
+                    const {
+                        events: { proposalCreated: closeProposalCreated },
+                    } = await client.createProposal(alice, sessionId, { type: ProposalActionType.CloseSession });
+                    await client.approveProposal(bob, sessionId, new BN(closeProposalCreated[0].proposalId));
+                    await client.closeSession(alice, sessionId);
 
-                    await assertError(async () => client.getExpense(currentExpenseAccountPubkey), {
+                    const refundId = refundAdded[0].refundId;
+                    await assertError(async () => client.deleteRefund(alice, sessionId, new BN(refundId)), {
+                        code: 'ApprovalRequired',
+                        message: 'Action requires a proposal approved by enough members',
+                    });
+
+                    const {
+                        events: { proposalCreated },
+                    } = await client.createProposal(alice, sessionId, { type: ProposalActionType.DeleteRefund, refundId });
+                    await client.approveProposal(bob, sessionId, new BN(proposalCreated[0].proposalId));
+                    await client.deleteRefund(alice, sessionId, new BN(refundId));
+
+                    await assertError(async () => client.getRefund(refundAccountPubkey), {
                         message: ACCOUNT_NOT_FOUND,
                     });
+                });
+            });
+
+            describe('> addExpense', () => {
+                it('> should fail when called with invalid session id', async () => {
+                    const invalidSessionId = new BN(666);
+                    await assertError(async () => client.addExpense(alice, invalidSessionId, 'name', 10), {
+                        message: ACCOUNT_NOT_FOUND_ERROR,
+                    });
+                });
 
-                    assert.equal(expenseDeleted[0].sessionId.toNumber(), sessionId);
-                    assert.equal(expenseDeleted[0].expenseId, currentExpenseId);
+                it('> should fail when called with amount equals to 0', async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'name', 0), {
+                        code: 'ExpenseAmountMustBeGreaterThanZero',
+                        message: `Expense amount must be greater than zero`,
+                    });
                 });
 
-                it('> should fail when called with non owner of expense', async () => {
-                    await assertError(async () => client.deleteExpense(bob, sessionId, currentExpenseId), {
-                        code: 'NotExpenseOwner',
-                        message: 'Only expense owner can update or delete expense',
+                it('> should fail when called with to long name', async () => {
+                    const longName = _.times(21, () => 'X').join('');
+                    await assertError(async () => client.addExpense(alice, sessionId, longName, 10), {
+                        code: 'ExpenseNameTooLong',
+                        message: `Expense's name can't exceed 20 characters`,
                     });
                 });
 
-                it('> should fail when called with invalid session id', async () => {
-                    await assertError(async () => client.deleteExpense(alice, new BN(666), currentExpenseId), {
+                it('> should failed when called by a non member', async () => {
+                    await assertError(async () => client.addExpense(paul, sessionId, 'Paul', 10), {
                         code: 'AccountNotInitialized',
-                        message: 'The program expected this account to be already initialized',
+                        message: `The program expected this account to be already initialized`,
                     });
                 });
-            });
 
-            context('> an expense is created by alice. charlie and bob are members of the session', () => {
-                let expenseId: BN;
+                it('> should succeed when called by alice ', async () => {
+                    const expectedExpenseId = 0;
+                    const name = 'expense1';
+                    const amount = 10.3;
+                    const timestampBefore = Math.floor(Date.now()) - 10000; // sometimes, this timestamp is bigger than that set in expense !?
 
-                beforeEach(async () => {
-                    const { events } = await client.addExpense(alice, sessionId, 'name', 20);
-                    expenseId = new BN(events.expenseAdded[0].expenseId);
+                    const {
+                        events,
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, name, amount);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.name, name);
+                    assert.equal(expense.amount, amount);
+                    assert.equal(expense.owner.toString(), alice.publicKey.toString());
+                    assert.isAtLeast(expense.date.getTime(), timestampBefore);
+                    assert.isAtMost(expense.date.getTime(), Math.floor(Date.now()));
+                    assert.lengthOf(expense.participants, 1);
+                    assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+
+                    const { expenseAdded } = events;
+                    assert.equal(expenseAdded[0].sessionId.toNumber(), sessionId);
+                    assert.equal(expenseAdded[0].expenseId, expectedExpenseId);
                 });
 
-                describe('> addExpenseParticipants', () => {
-                    it('> should fail when called with invalid session id', async () => {
-                        await assertError(async () => client.addExpenseParticipants(alice, new BN(666), expenseId, [bob.publicKey]), {
-                            message: ACCOUNT_NOT_INITIALIZED,
-                        });
-                    });
+                it('> should succeed when called by a member', async () => {
+                    const expectedExpenseId = 0;
+                    const name = 'expense1';
+                    const amount = 10.45;
+                    const timestampBefore = Math.floor(Date.now()) - 10000;
 
-                    it('> should fail when called with non expense owner', async () => {
-                        await assertError(async () => client.addExpenseParticipants(bob, sessionId, expenseId, [charlie.publicKey]), {
-                            code: 'NotExpenseOwner',
-                            message: 'Only expense owner can update or delete expense',
+                    const {
+                        events,
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(bob, sessionId, name, amount);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.name, name);
+                    assert.equal(expense.amount, amount);
+                    assert.equal(expense.owner.toString(), bob.publicKey.toString());
+                    assert.isAtLeast(expense.date.getTime(), timestampBefore);
+                    assert.isAtMost(expense.date.getTime(), Math.floor(Date.now()));
+                    assert.lengthOf(expense.participants, 1);
+                    assert.equal(expense.participants[0].toString(), bob.publicKey.toString());
+
+                    const { expenseAdded } = events;
+                    assert.equal(expenseAdded[0].sessionId.toNumber(), sessionId);
+                    assert.equal(expenseAdded[0].expenseId, expectedExpenseId);
+                });
+
+                it('> should store amount in minor units of session currency', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'expense1', 10.3);
+
+                    const internalExpense = await program.account.expenseAccount.fetch(expenseAccountPubkey);
+                    assert.equal(internalExpense.amount.toNumber(), 1030);
+                });
+
+                it('> should not drift when adding fractional amounts', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey: exp1AccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'expense1', 0.1);
+                    const {
+                        accounts: { expenseAccountPubkey: exp2AccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'expense2', 0.2);
+
+                    const exp1 = await program.account.expenseAccount.fetch(exp1AccountPubkey);
+                    const exp2 = await program.account.expenseAccount.fetch(exp2AccountPubkey);
+                    assert.equal(exp1.amount.add(exp2.amount).toNumber(), 30);
+                });
+
+                describe('> add expense with participants', () => {
+                    const name = 'expense1';
+                    const amount = 10;
+
+                    it('> should fail when called with invalid session id', async () => {
+                        await assertError(async () => client.addExpense(alice, new BN(666), name, amount, [bob.publicKey]), {
+                            message: ACCOUNT_NOT_FOUND,
                         });
                     });
 
@@ -712,7 +1201,7 @@ describe('solidr', () => {
                         const {
                             accounts: { expenseAccountPubkey },
                             events: { expenseParticipantAdded },
-                        } = await client.addExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey]);
+                        } = await client.addExpense(alice, sessionId, name, amount, [bob.publicKey]);
 
                         const expense = await client.getExpense(expenseAccountPubkey);
                         assert.lengthOf(expense.participants, 2);
@@ -727,7 +1216,7 @@ describe('solidr', () => {
                         const {
                             accounts: { expenseAccountPubkey },
                             events: { expenseParticipantAdded },
-                        } = await client.addExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey, charlie.publicKey]);
+                        } = await client.addExpense(alice, sessionId, name, amount, [bob.publicKey, charlie.publicKey]);
 
                         const expense = await client.getExpense(expenseAccountPubkey);
                         assert.lengthOf(expense.participants, 3);
@@ -741,249 +1230,1983 @@ describe('solidr', () => {
                         assert.equal(expenseParticipantAdded[1].memberPubkey.toString(), charlie.publicKey.toString());
                     });
 
-                    it('> should succeed but not add non session member', async () => {
-                        const {
-                            accounts: { expenseAccountPubkey },
-                            events: { expenseParticipantAdded },
-                        } = await client.addExpenseParticipants(alice, sessionId, expenseId, [paul.publicKey]);
+                    it('> should fail when called with non session member', async () => {
+                        await assertError(async () => client.addExpense(alice, sessionId, name, amount, [paul.publicKey]), {
+                            code: 'ParticipantNotMember',
+                            message: 'Only members can be added as participants',
+                        });
+                    });
+                });
+            });
 
-                        const expense = await client.getExpense(expenseAccountPubkey);
-                        assert.lengthOf(expense.participants, 1);
-                        assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+            describe('> original amount', () => {
+                const original = { currency: 'EUR', amount: 100, decimals: 2, exchangeRate: 1.0856 };
+
+                it('> should store original amount and exchange rate', async () => {
+                    const {
+                        events: { expenseAdded },
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'dinner', 108.56, [bob.publicKey], { original });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.amount, 108.56);
+                    assert.deepEqual(expense.original, original);
+
+                    assert.equal(expenseAdded[0].original.currency, 'EUR');
+                    assert.equal(expenseAdded[0].original.amount.toNumber(), 10000);
+                    assert.equal(expenseAdded[0].original.exchangeRate.toNumber(), 1_085_600_000);
+                });
 
-                        assert.isUndefined(expenseParticipantAdded);
+                it("> should fail when amount doesn't match converted original amount", async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'dinner', 100, [bob.publicKey], { original }), {
+                        code: 'ExchangeRateMismatch',
+                        message: "Expense amount doesn't match original amount converted with exchange rate",
                     });
                 });
 
-                context('> participants are added to the expense', () => {
-                    beforeEach(async () => {
-                        await client.addSessionMember(alice, sessionId, paul.publicKey, 'paul');
-                        await client.addExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey, charlie.publicKey, paul.publicKey]);
+                it('> should keep original amount when updating expense', async () => {
+                    const {
+                        events: { expenseAdded },
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'dinner', 108.56, [bob.publicKey], { original });
+                    const expenseId = new BN(expenseAdded[0].expenseId);
+
+                    await client.updateExpense(alice, sessionId, expenseId, 'team dinner', 108.56, [bob.publicKey]);
+                    assert.deepEqual((await client.getExpense(expenseAccountPubkey)).original, original);
+
+                    await client.updateExpense(alice, sessionId, expenseId, 'team dinner', 120, [bob.publicKey], undefined, null);
+                    assert.isNull((await client.getExpense(expenseAccountPubkey)).original);
+                });
+            });
+
+            describe('> split modes', () => {
+                it('> should split amount according to weights', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'weighted', 40, [bob.publicKey, charlie.publicKey], { splitMode: SplitMode.Weighted, shares: [2, 1] });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.splitMode, SplitMode.Weighted);
+                    assert.deepEqual(expense.shares, [1, 2, 1]);
+
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 30);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -20);
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, -10);
+                });
+
+                it('> should split amount according to percentages', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'percentage', 100, [alice.publicKey, bob.publicKey], {
+                        splitMode: SplitMode.Percentage,
+                        shares: [25, 75],
                     });
 
-                    describe('> removeExpenseParticipants', () => {
-                        it('> should fail when called with invalid session id', async () => {
-                            await assertError(async () => client.removeExpenseParticipants(alice, new BN(666), expenseId, [bob.publicKey]), {
-                                message: ACCOUNT_NOT_INITIALIZED,
-                            });
-                        });
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.splitMode, SplitMode.Percentage);
+                    assert.deepEqual(expense.shares, [25, 75]);
+
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 75);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -75);
+                });
+
+                it('> should split amount according to exact amounts', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'exact', 50.5, [bob.publicKey, charlie.publicKey], { splitMode: SplitMode.Exact, shares: [20.25, 30.25] });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.splitMode, SplitMode.Exact);
+                    assert.deepEqual(expense.shares, [0, 20.25, 30.25]);
+
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 50.5);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -20.25);
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, -30.25);
+                });
+
+                it('> should fail when weights are all zero', async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'weighted', 40, [alice.publicKey], { splitMode: SplitMode.Weighted, shares: [0] }), {
+                        code: 'WeightsMustBeGreaterThanZero',
+                        message: 'Participants total weight must be greater than zero',
+                    });
+                });
+
+                it("> should fail when percentages don't sum to 100%", async () => {
+                    await assertError(
+                        async () => client.addExpense(alice, sessionId, 'percentage', 100, [alice.publicKey, bob.publicKey], { splitMode: SplitMode.Percentage, shares: [25, 70] }),
+                        {
+                            code: 'PercentagesMustSumToHundred',
+                            message: 'Participants percentages must sum to 100%',
+                        },
+                    );
+                });
+
+                it("> should fail when exact amounts don't sum to expense amount", async () => {
+                    await assertError(
+                        async () => client.addExpense(alice, sessionId, 'exact', 50, [bob.publicKey, charlie.publicKey], { splitMode: SplitMode.Exact, shares: [20, 20] }),
+                        {
+                            code: 'ExactAmountsMustSumToTotal',
+                            message: 'Participants exact amounts must sum to expense amount',
+                        },
+                    );
+                });
+
+                it('> should add participant with given weight', async () => {
+                    const {
+                        events,
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'weighted', 40, [bob.publicKey], { splitMode: SplitMode.Weighted, shares: [1] });
+                    await client.addExpenseParticipants(alice, sessionId, new BN(events.expenseAdded[0].expenseId), [charlie.publicKey], [2]);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.deepEqual(expense.shares, [1, 1, 2]);
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, -20);
+                });
+
+                it('> should update shares of existing participants', async () => {
+                    const {
+                        events,
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'exact', 50, [bob.publicKey, charlie.publicKey], { splitMode: SplitMode.Exact, shares: [20, 30] });
+                    await client.updateExpense(alice, sessionId, new BN(events.expenseAdded[0].expenseId), 'exact', 60, [bob.publicKey, charlie.publicKey], [30, 30]);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.deepEqual(expense.shares, [0, 30, 30]);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -30);
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, -30);
+                });
+            });
+
+            describe('> expense approval', () => {
+                const addExpense = async (participants: PublicKey[]) => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.addExpense(alice, sessionId, 'exp', 30, participants);
+                    return { expenseAccountPubkey, expenseId: new BN(expenseAdded[0].expenseId) };
+                };
+
+                it('> should set expense approval mode when called by admin', async () => {
+                    const {
+                        accounts: { sessionAccountPubkey },
+                    } = await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.Quorum, approvals: 2 });
+
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.deepEqual(session.expenseApproval, { mode: ExpenseApprovalMode.Quorum, approvals: 2 });
+                });
+
+                it('> should fail when quorum is zero', async () => {
+                    await assertError(async () => client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.Quorum, approvals: 0 }), {
+                        code: 'InvalidExpenseQuorum',
+                        message: 'Expense quorum must be between 1 and 20',
+                    });
+                });
+
+                it('> should fail when called by non admin', async () => {
+                    await assertError(async () => client.setExpenseApproval(bob, sessionId, { mode: ExpenseApprovalMode.AllParticipants }), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
+                    });
+                });
+
+                it('> should keep pending expense out of balances until every participant approves', async () => {
+                    await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.AllParticipants });
+                    const { expenseAccountPubkey, expenseId } = await addExpense([bob.publicKey, charlie.publicKey]);
+
+                    let expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.status, ExpenseStatus.Pending);
+                    assert.sameMembers(
+                        expense.approvals.map((approval) => approval.toString()),
+                        [alice.publicKey.toString()],
+                    );
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 0);
+
+                    await client.approveExpense(bob, sessionId, expenseId);
+                    assert.equal((await client.getExpense(expenseAccountPubkey)).status, ExpenseStatus.Pending);
+
+                    const {
+                        events: { expenseApproved },
+                    } = await client.approveExpense(charlie, sessionId, expenseId);
+
+                    expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.status, ExpenseStatus.Approved);
+                    assert.equal(expenseApproved[0].addr.toString(), charlie.publicKey.toString());
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 20);
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, -10);
+                });
+
+                it('> should approve expense once quorum is reached', async () => {
+                    await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.Quorum, approvals: 2 });
+                    const { expenseAccountPubkey, expenseId } = await addExpense([bob.publicKey, charlie.publicKey]);
+
+                    await client.approveExpense(bob, sessionId, expenseId);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.status, ExpenseStatus.Approved);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -10);
+                });
+
+                it('> should fail when participant approves twice', async () => {
+                    await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.AllParticipants });
+                    const { expenseId } = await addExpense([bob.publicKey, charlie.publicKey]);
+                    await client.approveExpense(bob, sessionId, expenseId);
+
+                    await assertError(async () => client.approveExpense(bob, sessionId, expenseId), {
+                        code: 'ExpenseAlreadyApproved',
+                        message: 'Participant has already approved this expense',
+                    });
+                });
+
+                it('> should fail when called by non participant', async () => {
+                    await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.AllParticipants });
+                    const { expenseId } = await addExpense([bob.publicKey]);
+
+                    await assertError(async () => client.approveExpense(charlie, sessionId, expenseId), {
+                        code: 'NotExpenseParticipant',
+                        message: "Signer isn't an expense participant",
+                    });
+                });
+
+                it('> should reject expense and notify owner', async () => {
+                    await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.AllParticipants });
+                    const { expenseAccountPubkey, expenseId } = await addExpense([bob.publicKey, charlie.publicKey]);
+
+                    const {
+                        events: { expenseRejected },
+                    } = await client.rejectExpense(bob, sessionId, expenseId);
+
+                    assert.equal(expenseRejected[0].owner.toString(), alice.publicKey.toString());
+                    assert.equal(expenseRejected[0].addr.toString(), bob.publicKey.toString());
+                    assert.equal((await client.getExpense(expenseAccountPubkey)).status, ExpenseStatus.Rejected);
+                    await assertError(async () => client.approveExpense(charlie, sessionId, expenseId), {
+                        code: 'ExpenseNotPending',
+                        message: "Expense isn't pending approval",
+                    });
+                });
+            });
+
+            describe('> disputes', () => {
+                let expenseId: BN;
+                let expenseAccountPubkey: PublicKey;
+
+                beforeEach(async () => {
+                    const { accounts, events } = await client.addExpense(charlie, sessionId, 'exp', 30, [alice.publicKey, bob.publicKey]);
+                    expenseId = new BN(events.expenseAdded[0].expenseId);
+                    expenseAccountPubkey = accounts.expenseAccountPubkey;
+                });
+
+                it('> should flag expense when disputed by participant', async () => {
+                    const {
+                        events: { expenseDisputed },
+                    } = await client.disputeExpense(bob, sessionId, expenseId, 'I was not there');
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.dispute.by.toString(), bob.publicKey.toString());
+                    assert.equal(expense.dispute.reason, 'I was not there');
+                    assert.equal(expenseDisputed[0].reason, 'I was not there');
+                });
+
+                it('> should fail when called by non participant', async () => {
+                    const { events } = await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey]);
+
+                    await assertError(async () => client.disputeExpense(charlie, sessionId, new BN(events.expenseAdded[0].expenseId), 'Not mine'), {
+                        code: 'NotExpenseParticipant',
+                        message: "Signer isn't an expense participant",
+                    });
+                });
+
+                it('> should fail when reason is too long', async () => {
+                    await assertError(async () => client.disputeExpense(bob, sessionId, expenseId, 'x'.repeat(65)), {
+                        code: 'DisputeReasonTooLong',
+                        message: "Dispute reason can't exceed 64 characters",
+                    });
+                });
+
+                it('> should freeze expense while disputed', async () => {
+                    await client.disputeExpense(bob, sessionId, expenseId, 'Too expensive');
+
+                    await assertError(async () => client.updateExpense(charlie, sessionId, expenseId, 'exp', 60, [alice.publicKey, bob.publicKey]), {
+                        code: 'ExpenseDisputed',
+                        message: 'Expense is frozen until its dispute is resolved',
+                    });
+                    await assertError(async () => client.deleteExpense(charlie, sessionId, expenseId), {
+                        code: 'ExpenseDisputed',
+                        message: 'Expense is frozen until its dispute is resolved',
+                    });
+                    await assertError(async () => client.disputeExpense(alice, sessionId, expenseId, 'Me too'), {
+                        code: 'ExpenseDisputed',
+                        message: 'Expense is frozen until its dispute is resolved',
+                    });
+                });
+
+                it('> should fail to resolve when called by non owner and non admin', async () => {
+                    await client.disputeExpense(alice, sessionId, expenseId, 'Too expensive');
+
+                    await assertError(async () => client.resolveDispute(bob, sessionId, expenseId), {
+                        code: 'NotExpenseOwner',
+                        message: 'Only expense owner can update or delete expense',
+                    });
+                });
+
+                it('> should fail to resolve when expense is not disputed', async () => {
+                    await assertError(async () => client.resolveDispute(charlie, sessionId, expenseId), {
+                        code: 'ExpenseNotDisputed',
+                        message: "Expense isn't disputed",
+                    });
+                });
+
+                it('> should clear dispute when resolved by owner', async () => {
+                    await client.disputeExpense(bob, sessionId, expenseId, 'Too expensive');
+
+                    const {
+                        events: { expenseDisputeResolved },
+                    } = await client.resolveDispute(charlie, sessionId, expenseId);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.isNull(expense.dispute);
+                    assert.equal(expense.amount, 30);
+                    assert.isFalse(expenseDisputeResolved[0].amended);
+                    await client.deleteExpense(charlie, sessionId, expenseId);
+                });
+
+                it('> should amend expense when resolved by admin', async () => {
+                    await client.disputeExpense(bob, sessionId, expenseId, 'It was 60');
+
+                    const {
+                        events: { expenseDisputeResolved },
+                    } = await client.resolveDispute(alice, sessionId, expenseId, { amount: 60 });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.isNull(expense.dispute);
+                    assert.equal(expense.amount, 60);
+                    assert.isTrue(expenseDisputeResolved[0].amended);
+                    const balance = await client.getMemberBalance(sessionId, charlie.publicKey);
+                    assert.equal(balance.totalPaid, 60);
+                    assert.equal(balance.balance, 40);
+                });
+            });
+
+            describe('> recurring expenses', () => {
+                const DAY = 86400;
+
+                const chainTime = async (): Promise<number> => {
+                    const blockTime = await client.connection.getBlockTime(await client.connection.getSlot());
+                    return blockTime ?? Math.floor(Date.now() / 1000);
+                };
+
+                const waitUntil = async (timestamp: number) => {
+                    while ((await chainTime()) <= timestamp) {
+                        await new Promise((resolve) => setTimeout(resolve, 500));
+                    }
+                };
+
+                it('> should create recurring expense template', async () => {
+                    const firstDue = new Date(((await chainTime()) + DAY) * 1000);
+                    const {
+                        accounts: { recurringAccountPubkey },
+                        events: { recurringExpenseCreated },
+                    } = await client.createRecurringExpense(alice, sessionId, 'Rent', 900, 30 * DAY, firstDue, [bob.publicKey, charlie.publicKey]);
+
+                    const recurring = await client.getRecurringExpense(recurringAccountPubkey);
+                    assert.equal(recurring.recurringId, 0);
+                    assert.equal(recurring.name, 'Rent');
+                    assert.equal(recurring.amount, 900);
+                    assert.equal(recurring.owner.toString(), alice.publicKey.toString());
+                    assert.deepEqual(
+                        recurring.participants.map((p) => p.toString()),
+                        [alice.publicKey.toString(), bob.publicKey.toString(), charlie.publicKey.toString()],
+                    );
+                    assert.equal(recurring.interval, 30 * DAY);
+                    assert.equal(recurring.nextDue.getTime(), firstDue.getTime());
+                    assert.equal(recurring.status, RecurringStatus.Active);
+                    assert.equal(recurring.category, ExpenseCategory.Other);
+                    assert.deepEqual(recurring.tags, []);
+                    assert.equal(recurringExpenseCreated[0].recurringId, 0);
+                });
+
+                it('> should fail with an interval shorter than a day', async () => {
+                    const firstDue = new Date(((await chainTime()) + DAY) * 1000);
+                    await assertError(async () => client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY - 1, firstDue, [bob.publicKey]), {
+                        code: 'InvalidRecurringInterval',
+                        message: 'Recurring expense interval must be at least one day',
+                    });
+                });
+
+                it('> should fail with a first occurrence in the past', async () => {
+                    const firstDue = new Date(((await chainTime()) - DAY) * 1000);
+                    await assertError(async () => client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY, firstDue, [bob.publicKey]), {
+                        code: 'RecurringFirstDueInPast',
+                        message: "Recurring expense first occurrence can't be in the past",
+                    });
+                });
+
+                it('> should fail to materialize before due date', async () => {
+                    const firstDue = new Date(((await chainTime()) + DAY) * 1000);
+                    await client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY, firstDue, [bob.publicKey]);
+
+                    await assertError(async () => client.materializeRecurringExpense(paul, sessionId, new BN(0)), {
+                        code: 'RecurringExpenseNotDue',
+                        message: "Recurring expense isn't due yet",
+                    });
+                });
+
+                it('> should allow anybody to materialize a due occurrence', async () => {
+                    const firstDue = (await chainTime()) + 2;
+                    await client.createRecurringExpense(alice, sessionId, 'Internet', 30, DAY, new Date(firstDue * 1000), [bob.publicKey, charlie.publicKey]);
+                    await waitUntil(firstDue);
+
+                    const {
+                        accounts: { expenseAccountPubkey, recurringAccountPubkey },
+                        events: { recurringExpenseMaterialized },
+                    } = await client.materializeRecurringExpense(paul, sessionId, new BN(0));
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.name, 'Internet');
+                    assert.equal(expense.amount, 30);
+                    assert.equal(expense.owner.toString(), alice.publicKey.toString());
+                    assert.equal(expense.participants.length, 3);
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 20);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -10);
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, -10);
+
+                    const recurring = await client.getRecurringExpense(recurringAccountPubkey);
+                    assert.equal(recurring.nextDue.getTime(), (firstDue + DAY) * 1000);
+                    assert.equal(recurringExpenseMaterialized[0].expenseId, expense.expenseId);
+
+                    await assertError(async () => client.materializeRecurringExpense(paul, sessionId, new BN(0)), {
+                        code: 'RecurringExpenseNotDue',
+                        message: "Recurring expense isn't due yet",
+                    });
+                });
+
+                it('> should fail with too many tags', async () => {
+                    const firstDue = new Date(((await chainTime()) + DAY) * 1000);
+                    await assertError(
+                        async () => client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY, firstDue, [bob.publicKey], { tags: ['a', 'b', 'c', 'd', 'e', 'f'] }),
+                        {
+                            code: 'TooManyExpenseTags',
+                            message: 'Too many expense tags',
+                        },
+                    );
+                });
+
+                it('> should copy category and tags to materialized expenses', async () => {
+                    const firstDue = (await chainTime()) + 2;
+                    const {
+                        accounts: { recurringAccountPubkey },
+                    } = await client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY, new Date(firstDue * 1000), [bob.publicKey], {
+                        category: ExpenseCategory.Accommodation,
+                        tags: ['flat'],
+                    });
+                    const recurring = await client.getRecurringExpense(recurringAccountPubkey);
+                    assert.equal(recurring.category, ExpenseCategory.Accommodation);
+                    assert.deepEqual(recurring.tags, ['flat']);
+                    await waitUntil(firstDue);
+
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.materializeRecurringExpense(paul, sessionId, new BN(0));
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.category, ExpenseCategory.Accommodation);
+                    assert.deepEqual(expense.tags, ['flat']);
+                    assert.deepEqual(expenseAdded[0].tags, ['flat']);
+                });
+
+                it('> should pause and resume recurring expense', async () => {
+                    const firstDue = (await chainTime()) + 2;
+                    const {
+                        accounts: { recurringAccountPubkey },
+                    } = await client.createRecurringExpense(bob, sessionId, 'Electricity', 60, DAY, new Date(firstDue * 1000), [alice.publicKey]);
+
+                    await client.pauseRecurringExpense(bob, sessionId, new BN(0));
+                    assert.equal((await client.getRecurringExpense(recurringAccountPubkey)).status, RecurringStatus.Paused);
+                    await waitUntil(firstDue);
+                    await assertError(async () => client.materializeRecurringExpense(paul, sessionId, new BN(0)), {
+                        code: 'RecurringExpensePaused',
+                        message: 'Recurring expense is paused',
+                    });
+
+                    // admin can resume other members templates, missed occurrences are skipped
+                    await client.resumeRecurringExpense(alice, sessionId, new BN(0));
+                    const recurring = await client.getRecurringExpense(recurringAccountPubkey);
+                    assert.equal(recurring.status, RecurringStatus.Active);
+                    assert.equal(recurring.nextDue.getTime(), (firstDue + DAY) * 1000);
+                });
+
+                it('> should fail to resume an active recurring expense', async () => {
+                    const firstDue = new Date(((await chainTime()) + DAY) * 1000);
+                    await client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY, firstDue, [bob.publicKey]);
+
+                    await assertError(async () => client.resumeRecurringExpense(alice, sessionId, new BN(0)), {
+                        code: 'RecurringExpenseNotPaused',
+                        message: "Recurring expense isn't paused",
+                    });
+                });
+
+                it('> should fail to pause when called by another member', async () => {
+                    const firstDue = new Date(((await chainTime()) + DAY) * 1000);
+                    await client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY, firstDue, [bob.publicKey]);
+
+                    await assertError(async () => client.pauseRecurringExpense(bob, sessionId, new BN(0)), {
+                        code: 'NotExpenseOwner',
+                        message: 'Only expense owner can update or delete expense',
+                    });
+                });
+
+                it('> should cancel recurring expense', async () => {
+                    const firstDue = new Date(((await chainTime()) + DAY) * 1000);
+                    const {
+                        accounts: { recurringAccountPubkey },
+                    } = await client.createRecurringExpense(alice, sessionId, 'Rent', 900, DAY, firstDue, [bob.publicKey]);
+
+                    const {
+                        events: { recurringExpenseCancelled },
+                    } = await client.cancelRecurringExpense(alice, sessionId, new BN(0));
+
+                    assert.equal(recurringExpenseCancelled[0].recurringId, 0);
+                    await assertError(async () => client.getRecurringExpense(recurringAccountPubkey), {
+                        message: ACCOUNT_NOT_FOUND,
+                    });
+                });
+            });
+
+            describe('> categories and tags', () => {
+                it('> should default to other category without tags', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey]);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.category, ExpenseCategory.Other);
+                    assert.deepEqual(expense.tags, []);
+                });
+
+                it('> should store category and tags on added expense', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.addExpense(alice, sessionId, 'Dinner', 60, [bob.publicKey], {
+                        category: ExpenseCategory.Food,
+                        tags: ['restaurant', 'birthday'],
+                    });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.category, ExpenseCategory.Food);
+                    assert.deepEqual(expense.tags, ['restaurant', 'birthday']);
+                    assert.deepEqual(expenseAdded[0].category, { food: {} });
+                    assert.deepEqual(expenseAdded[0].tags, ['restaurant', 'birthday']);
+                });
+
+                it('> should update category and tags, and keep them when not given', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.addExpense(alice, sessionId, 'Taxi', 20, [bob.publicKey], { category: ExpenseCategory.Food, tags: ['night'] });
+                    const expenseId = new BN(expenseAdded[0].expenseId);
+
+                    const {
+                        events: { expenseUpdated },
+                    } = await client.updateExpense(alice, sessionId, expenseId, 'Taxi', 20, [bob.publicKey], undefined, undefined, {
+                        category: ExpenseCategory.Transport,
+                    });
+                    let expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.category, ExpenseCategory.Transport);
+                    assert.deepEqual(expense.tags, ['night']);
+                    assert.deepEqual(expenseUpdated[0].category, { transport: {} });
+                    assert.deepEqual(expenseUpdated[0].tags, ['night']);
+
+                    await client.updateExpense(alice, sessionId, expenseId, 'Taxi', 25, [bob.publicKey], undefined, undefined, { tags: [] });
+                    expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.category, ExpenseCategory.Transport);
+                    assert.deepEqual(expense.tags, []);
+                });
+
+                it('> should fail with too many tags', async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey], { tags: ['a', 'b', 'c', 'd', 'e', 'f'] }), {
+                        code: 'TooManyExpenseTags',
+                        message: 'Too many expense tags',
+                    });
+                });
+
+                it('> should fail with empty or too long tag', async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey], { tags: [''] }), {
+                        code: 'InvalidExpenseTag',
+                        message: 'Expense tags must be non empty and 16 characters max',
+                    });
+                    await assertError(async () => client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey], { tags: ['x'.repeat(17)] }), {
+                        code: 'InvalidExpenseTag',
+                        message: 'Expense tags must be non empty and 16 characters max',
+                    });
+                });
+            });
+
+            describe('> budgets', () => {
+                it('> should fail when called with non administrator', async () => {
+                    await assertError(async () => client.setBudget(bob, sessionId, 100), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: `Only session administrator is granted`,
+                    });
+                });
+
+                it('> should track running totals for session and categories', async () => {
+                    const {
+                        accounts: { budgetAccountPubkey },
+                        events: { budgetUpdated },
+                    } = await client.setBudget(alice, sessionId, 500);
+                    await client.setBudget(alice, sessionId, 100, { category: ExpenseCategory.Food });
+                    await client.addExpense(alice, sessionId, 'Dinner', 60, [bob.publicKey], { category: ExpenseCategory.Food });
+                    await client.addExpense(bob, sessionId, 'Taxi', 20, [alice.publicKey], { category: ExpenseCategory.Transport });
+
+                    const budget = await client.getBudget(budgetAccountPubkey);
+                    assert.equal(budget.total.limit, 500);
+                    assert.equal(budget.total.spent, 80);
+                    assert.isFalse(budget.total.hardCap);
+                    assert.equal(budget.categories[ExpenseCategory.Food].limit, 100);
+                    assert.equal(budget.categories[ExpenseCategory.Food].spent, 60);
+                    assert.isNull(budget.categories[ExpenseCategory.Transport].limit);
+                    assert.equal(budget.categories[ExpenseCategory.Transport].spent, 20);
+                    assert.isNull(budgetUpdated[0].category);
+                    assert.equal(budgetUpdated[0].limit.toNumber(), 50000);
+                });
+
+                it('> should emit BudgetExceeded once limit is crossed', async () => {
+                    await client.setBudget(alice, sessionId, 100);
+                    const {
+                        events: { budgetExceeded: notExceeded },
+                    } = await client.addExpense(alice, sessionId, 'exp 1', 60, [bob.publicKey]);
+                    const {
+                        events: { budgetExceeded },
+                    } = await client.addExpense(alice, sessionId, 'exp 2', 50, [bob.publicKey]);
+
+                    assert.isUndefined(notExceeded);
+                    assert.isNull(budgetExceeded[0].category);
+                    assert.equal(budgetExceeded[0].limit.toNumber(), 10000);
+                    assert.equal(budgetExceeded[0].spent.toNumber(), 11000);
+                });
+
+                it('> should fail when expense crosses a hard capped limit', async () => {
+                    await client.setBudget(alice, sessionId, 50, { category: ExpenseCategory.Food, hardCap: true });
+
+                    await assertError(async () => client.addExpense(alice, sessionId, 'Dinner', 60, [bob.publicKey], { category: ExpenseCategory.Food }), {
+                        code: 'BudgetHardCapExceeded',
+                        message: 'Expense would exceed session budget',
+                    });
+                    // other categories aren't capped
+                    await client.addExpense(alice, sessionId, 'Taxi', 60, [bob.publicKey], { category: ExpenseCategory.Transport });
+                });
+
+                it('> should release amounts when expenses are updated or deleted', async () => {
+                    const {
+                        accounts: { budgetAccountPubkey },
+                    } = await client.setBudget(alice, sessionId, 100);
+                    const { events } = await client.addExpense(alice, sessionId, 'Dinner', 60, [bob.publicKey], { category: ExpenseCategory.Food });
+                    const expenseId = new BN(events.expenseAdded[0].expenseId);
+
+                    await client.updateExpense(alice, sessionId, expenseId, 'Groceries', 30, [bob.publicKey], undefined, undefined, {
+                        category: ExpenseCategory.Shopping,
+                    });
+                    let budget = await client.getBudget(budgetAccountPubkey);
+                    assert.equal(budget.total.spent, 30);
+                    assert.equal(budget.categories[ExpenseCategory.Food].spent, 0);
+                    assert.equal(budget.categories[ExpenseCategory.Shopping].spent, 30);
+
+                    await client.deleteExpense(alice, sessionId, expenseId);
+                    budget = await client.getBudget(budgetAccountPubkey);
+                    assert.equal(budget.total.spent, 0);
+                    assert.equal(budget.categories[ExpenseCategory.Shopping].spent, 0);
+                });
+
+                it('> should not release amounts of expenses recorded before budgeting', async () => {
+                    const { events } = await client.addExpense(alice, sessionId, 'exp 1', 40, [bob.publicKey]);
+                    const {
+                        accounts: { budgetAccountPubkey },
+                    } = await client.setBudget(alice, sessionId, 100);
+                    await client.addExpense(alice, sessionId, 'exp 2', 30, [bob.publicKey]);
+
+                    await client.deleteExpense(alice, sessionId, new BN(events.expenseAdded[0].expenseId));
+
+                    const budget = await client.getBudget(budgetAccountPubkey);
+                    assert.equal(budget.total.spent, 30);
+                });
+            });
+
+            describe('> receipts', () => {
+                const receipt = {
+                    hash: Buffer.from(hashToken('receipt content')).toString('hex'),
+                    uri: 'ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3e',
+                };
+
+                it('> should store receipt set at expense creation', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey], { receipt });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.deepEqual(expense.receipt, receipt);
+                    assert.equal(expense.receipt?.hash, Buffer.from(hashToken('receipt content')).toString('hex'));
+                });
+
+                it('> should attach, replace and remove receipt later', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events,
+                    } = await client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey]);
+                    const expenseId = new BN(events.expenseAdded[0].expenseId);
+                    assert.isNull((await client.getExpense(expenseAccountPubkey)).receipt);
+
+                    const {
+                        events: { expenseReceiptUpdated },
+                    } = await client.setExpenseReceipt(alice, sessionId, expenseId, receipt);
+                    assert.deepEqual((await client.getExpense(expenseAccountPubkey)).receipt, receipt);
+                    assert.equal(expenseReceiptUpdated[0].expenseId, expenseId.toNumber());
+                    assert.equal(expenseReceiptUpdated[0].receipt.uri, receipt.uri);
+
+                    const newReceipt = { hash: Buffer.from(hashToken('new content')).toString('hex'), uri: 'https://example.com/r.pdf' };
+                    await client.setExpenseReceipt(alice, sessionId, expenseId, newReceipt);
+                    assert.deepEqual((await client.getExpense(expenseAccountPubkey)).receipt, newReceipt);
+
+                    const {
+                        events: { expenseReceiptUpdated: removed },
+                    } = await client.setExpenseReceipt(alice, sessionId, expenseId, null);
+                    assert.isNull((await client.getExpense(expenseAccountPubkey)).receipt);
+                    assert.isNull(removed[0].receipt);
+                });
+
+                it('> should fail when called with non owner of expense', async () => {
+                    const { events } = await client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey]);
+
+                    await assertError(async () => client.setExpenseReceipt(bob, sessionId, new BN(events.expenseAdded[0].expenseId), receipt), {
+                        code: 'NotExpenseOwner',
+                        message: 'Only expense owner can update or delete expense',
+                    });
+                });
+
+                it('> should fail with empty or too long receipt uri', async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey], { receipt: { ...receipt, uri: '' } }), {
+                        code: 'InvalidReceiptUri',
+                        message: 'Receipt URI must be non empty and 64 characters max',
+                    });
+                    await assertError(async () => client.addExpense(alice, sessionId, 'exp', 10, [bob.publicKey], { receipt: { ...receipt, uri: 'https://' + 'x'.repeat(57) } }), {
+                        code: 'InvalidReceiptUri',
+                        message: 'Receipt URI must be non empty and 64 characters max',
+                    });
+                });
+            });
+
+            describe('> payer', () => {
+                it('> should default payer to signer, taking part in the expense', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.addExpense(bob, sessionId, 'exp', 30, [charlie.publicKey]);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.owner.toString(), bob.publicKey.toString());
+                    assert.equal(expense.payer.toString(), bob.publicKey.toString());
+                    assert.equal(expenseAdded[0].payer.toString(), bob.publicKey.toString());
+                    assert.sameMembers(
+                        expense.participants.map((p) => p.toString()),
+                        [bob, charlie].map((m) => m.publicKey.toString()),
+                    );
+                });
+
+                it('> should allow treasurer to record an expense paid by another member', async () => {
+                    await client.grantRole(alice, sessionId, bob.publicKey, MemberRole.Treasurer);
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.addExpense(bob, sessionId, 'exp', 30, [alice.publicKey], { payer: charlie.publicKey });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.owner.toString(), bob.publicKey.toString());
+                    assert.equal(expense.payer.toString(), charlie.publicKey.toString());
+                    assert.equal(expenseAdded[0].payer.toString(), charlie.publicKey.toString());
+                    assert.sameMembers(
+                        expense.participants.map((p) => p.toString()),
+                        [charlie, alice].map((m) => m.publicKey.toString()),
+                    );
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, 15);
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, -15);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, 0);
+                });
+
+                it('> should exclude payer from participants', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'Gift', 30, [bob.publicKey, charlie.publicKey], { excludePayer: true });
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.sameMembers(
+                        expense.participants.map((p) => p.toString()),
+                        [bob, charlie].map((m) => m.publicKey.toString()),
+                    );
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 30);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -15);
+                    assert.equal((await client.getMemberBalance(sessionId, charlie.publicKey)).balance, -15);
+                });
+
+                it('> should keep an excluded payer out of participants on update', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events,
+                    } = await client.addExpense(alice, sessionId, 'Gift', 30, [bob.publicKey, charlie.publicKey], { excludePayer: true });
+
+                    await client.updateExpense(alice, sessionId, new BN(events.expenseAdded[0].expenseId), 'Gift', 20, [bob.publicKey]);
+
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.deepEqual(
+                        expense.participants.map((p) => p.toString()),
+                        [bob.publicKey.toString()],
+                    );
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 20);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -20);
+                });
+
+                it('> should fail when a member records an expense paid by another member', async () => {
+                    await assertError(async () => client.addExpense(bob, sessionId, 'exp', 30, [alice.publicKey], { payer: charlie.publicKey }), {
+                        code: 'ForbiddenRole',
+                        message: "Member role doesn't allow this action",
+                    });
+                });
+
+                it('> should fail when payer is not a session member', async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey], { payer: paul.publicKey }), {
+                        code: 'PayerNotMember',
+                        message: 'Expense payer must be a session member',
+                    });
+                });
+
+                it('> should fail when payer is excluded and no participant is given', async () => {
+                    await assertError(async () => client.addExpense(alice, sessionId, 'exp', 30, [], { excludePayer: true }), {
+                        code: 'ExpenseWithoutParticipants',
+                        message: 'Expense must have at least one participant',
+                    });
+                });
+            });
+
+            describe('> updateExpense', () => {
+                let currentExpenseId: BN;
+                let currentExpenseAccountPubkey: PublicKey;
+
+                beforeEach(async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.addExpense(alice, sessionId, 'exp 3', 200, [charlie.publicKey]);
+                    currentExpenseId = new BN(expenseAdded[0].expenseId);
+                    currentExpenseAccountPubkey = expenseAccountPubkey;
+                });
+
+                it("> should update expense when I'm the owner", async () => {
+                    const updatedName = 'exp 3 updated';
+                    const updatedAmount = 100.37;
+                    const {
+                        events: { expenseUpdated },
+                    } = await client.updateExpense(alice, sessionId, currentExpenseId, updatedName, updatedAmount, [bob.publicKey]);
+                    const expense = await client.getExpense(currentExpenseAccountPubkey);
+
+                    assert.equal(expense.name, updatedName);
+                    assert.equal(expense.amount, updatedAmount);
+                    assert.lengthOf(expense.participants, 2);
+                    assert.sameDeepMembers(expense.participants, [alice.publicKey, bob.publicKey]);
+
+                    const internalExpense = await program.account.expenseAccount.fetch(currentExpenseAccountPubkey);
+                    assert.equal(internalExpense.amount.toNumber(), 10037);
+
+                    assert.equal(expenseUpdated[0].sessionId.toNumber(), sessionId);
+                    assert.equal(expenseUpdated[0].expenseId, currentExpenseId);
+                });
+
+                it('> should fail when called with non owner of expense', async () => {
+                    await assertError(async () => client.updateExpense(bob, sessionId, currentExpenseId, 'exp 3 updated', 100, [bob.publicKey]), {
+                        code: 'NotExpenseOwner',
+                        message: 'Only expense owner can update or delete expense',
+                    });
+                });
+
+                it('> should fail when called with invalid session id', async () => {
+                    await assertError(async () => client.updateExpense(alice, new BN(666), currentExpenseId, 'exp 3 updated', 100, [bob.publicKey]), {
+                        message: ACCOUNT_NOT_FOUND,
+                    });
+                });
+            });
+
+            describe('> deleteExpense', () => {
+                let currentExpenseId: BN;
+                let currentExpenseAccountPubkey: PublicKey;
+
+                beforeEach(async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                        events: { expenseAdded },
+                    } = await client.addExpense(alice, sessionId, 'exp 3', 200, [charlie.publicKey]);
+                    currentExpenseId = new BN(expenseAdded[0].expenseId);
+                    currentExpenseAccountPubkey = expenseAccountPubkey;
+                });
+
+                it("> should delete expense when I'm the owner", async () => {
+                    const {
+                        events: { expenseDeleted },
+                    } = await client.deleteExpense(alice, sessionId, currentExpenseId);
+
+                    await assertError(async () => client.getExpense(currentExpenseAccountPubkey), {
+                        message: ACCOUNT_NOT_FOUND,
+                    });
+
+                    assert.equal(expenseDeleted[0].sessionId.toNumber(), sessionId);
+                    assert.equal(expenseDeleted[0].expenseId, currentExpenseId);
+                });
+
+                it('> should fail when called with non owner of expense', async () => {
+                    await assertError(async () => client.deleteExpense(bob, sessionId, currentExpenseId), {
+                        code: 'NotExpenseOwner',
+                        message: 'Only expense owner can update or delete expense',
+                    });
+                });
+
+                it('> should fail when called with invalid session id', async () => {
+                    await assertError(async () => client.deleteExpense(alice, new BN(666), currentExpenseId), {
+                        code: 'AccountNotInitialized',
+                        message: 'The program expected this account to be already initialized',
+                    });
+                });
+            });
+
+            context('> an expense is created by alice. charlie and bob are members of the session', () => {
+                let expenseId: BN;
+
+                beforeEach(async () => {
+                    const { events } = await client.addExpense(alice, sessionId, 'name', 20);
+                    expenseId = new BN(events.expenseAdded[0].expenseId);
+                });
+
+                describe('> addExpenseParticipants', () => {
+                    it('> should fail when called with invalid session id', async () => {
+                        await assertError(async () => client.addExpenseParticipants(alice, new BN(666), expenseId, [bob.publicKey]), {
+                            message: ACCOUNT_NOT_INITIALIZED,
+                        });
+                    });
+
+                    it('> should fail when called with non expense owner', async () => {
+                        await assertError(async () => client.addExpenseParticipants(bob, sessionId, expenseId, [charlie.publicKey]), {
+                            code: 'NotExpenseOwner',
+                            message: 'Only expense owner can update or delete expense',
+                        });
+                    });
+
+                    it('> should succeed with one member', async () => {
+                        const {
+                            accounts: { expenseAccountPubkey },
+                            events: { expenseParticipantAdded },
+                        } = await client.addExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey]);
+
+                        const expense = await client.getExpense(expenseAccountPubkey);
+                        assert.lengthOf(expense.participants, 2);
+                        assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+                        assert.equal(expense.participants[1].toString(), bob.publicKey.toString());
+
+                        assert.equal(expenseParticipantAdded[0].expenseId, 0);
+                        assert.equal(expenseParticipantAdded[0].memberPubkey.toString(), bob.publicKey.toString());
+                    });
+
+                    it('> should succeed with two members', async () => {
+                        const {
+                            accounts: { expenseAccountPubkey },
+                            events: { expenseParticipantAdded },
+                        } = await client.addExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey, charlie.publicKey]);
+
+                        const expense = await client.getExpense(expenseAccountPubkey);
+                        assert.lengthOf(expense.participants, 3);
+                        assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+                        assert.equal(expense.participants[1].toString(), bob.publicKey.toString());
+                        assert.equal(expense.participants[2].toString(), charlie.publicKey.toString());
+
+                        assert.equal(expenseParticipantAdded[0].expenseId, 0);
+                        assert.equal(expenseParticipantAdded[0].memberPubkey.toString(), bob.publicKey.toString());
+                        assert.equal(expenseParticipantAdded[1].expenseId, 0);
+                        assert.equal(expenseParticipantAdded[1].memberPubkey.toString(), charlie.publicKey.toString());
+                    });
+
+                    it('> should fail when called with non session member', async () => {
+                        await assertError(async () => client.addExpenseParticipants(alice, sessionId, expenseId, [paul.publicKey]), {
+                            code: 'ParticipantNotMember',
+                            message: 'Only members can be added as participants',
+                        });
+                    });
+                });
+
+                context('> participants are added to the expense', () => {
+                    beforeEach(async () => {
+                        await client.addSessionMember(alice, sessionId, paul.publicKey, 'paul');
+                        await client.addExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey, charlie.publicKey, paul.publicKey]);
+                    });
+
+                    describe('> removeExpenseParticipants', () => {
+                        it('> should fail when called with invalid session id', async () => {
+                            await assertError(async () => client.removeExpenseParticipants(alice, new BN(666), expenseId, [bob.publicKey]), {
+                                message: ACCOUNT_NOT_INITIALIZED,
+                            });
+                        });
+
+                        it('> should fail when called with non expense owner', async () => {
+                            await assertError(async () => client.removeExpenseParticipants(bob, sessionId, expenseId, [charlie.publicKey]), {
+                                code: 'NotExpenseOwner',
+                                message: 'Only expense owner can update or delete expense',
+                            });
+                        });
+
+                        it('> should fail when called to remove expense owner', async () => {
+                            await assertError(async () => client.removeExpenseParticipants(alice, sessionId, expenseId, [alice.publicKey]), {
+                                code: 'CannotRemoveExpenseOwner',
+                                message: 'Expense owner cannot be removed from participants',
+                            });
+                        });
+
+                        it('> should succeed with one member', async () => {
+                            const {
+                                accounts: { expenseAccountPubkey },
+                                events: { expenseParticipantRemoved },
+                            } = await client.removeExpenseParticipants(alice, sessionId, expenseId, [charlie.publicKey]);
+
+                            const expense = await client.getExpense(expenseAccountPubkey);
+                            assert.lengthOf(expense.participants, 3);
+                            assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+                            assert.equal(expense.participants[1].toString(), bob.publicKey.toString());
+                            assert.equal(expense.participants[2].toString(), paul.publicKey.toString());
+
+                            assert.equal(expenseParticipantRemoved[0].expenseId, 0);
+                            assert.equal(expenseParticipantRemoved[0].memberPubkey.toString(), charlie.publicKey.toString());
+                        });
+
+                        it('> should succeed with two members', async () => {
+                            const {
+                                accounts: { expenseAccountPubkey },
+                                events: { expenseParticipantRemoved },
+                            } = await client.removeExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey, charlie.publicKey]);
+
+                            const expense = await client.getExpense(expenseAccountPubkey);
+                            assert.lengthOf(expense.participants, 2);
+                            assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
+                            assert.equal(expense.participants[1].toString(), paul.publicKey.toString());
+
+                            assert.equal(expenseParticipantRemoved[0].expenseId, 0);
+                            assert.equal(expenseParticipantRemoved[0].memberPubkey.toString(), bob.publicKey.toString());
+                            assert.equal(expenseParticipantRemoved[1].expenseId, 0);
+                            assert.equal(expenseParticipantRemoved[1].memberPubkey.toString(), charlie.publicKey.toString());
+                        });
+                    });
+                });
+            });
+
+            describe('> sendRefunds', () => {
+                it('> should fail when called with invalid session id', async () => {
+                    const invalidSessionId = new BN(666);
+                    await assertError(
+                        async () =>
+                            client.sendRefunds(alice, invalidSessionId, [
+                                {
+                                    amount: 10,
+                                    to: bob.publicKey,
+                                },
+                            ]),
+                        {
+                            message: ACCOUNT_NOT_FOUND_ERROR,
+                        },
+                    );
+                });
+
+                it('> should fail when called with amount equals to 0', async () => {
+                    await assertError(
+                        async () =>
+                            client.sendRefunds(alice, sessionId, [
+                                {
+                                    amount: 0,
+                                    to: bob.publicKey,
+                                },
+                            ]),
+                        {
+                            code: 'RefundAmountMustBeGreaterThanZero',
+                            message: `Refund amount must be greater than zero`,
+                        },
+                    );
+                });
+
+                it('> should failed when called by a non member', async () => {
+                    await assertError(
+                        async () =>
+                            client.sendRefunds(paul, sessionId, [
+                                {
+                                    amount: 10,
+                                    to: bob.publicKey,
+                                },
+                            ]),
+                        {
+                            code: 'AccountNotInitialized',
+                            message: `The program expected this account to be already initialized`,
+                        },
+                    );
+                });
+
+                it('> should failed when receiver is not a member', async () => {
+                    await assertError(
+                        async () =>
+                            client.sendRefunds(alice, sessionId, [
+                                {
+                                    amount: 10,
+                                    to: paul.publicKey,
+                                },
+                            ]),
+                        {
+                            code: 'AccountNotInitialized',
+                            message: `The program expected this account to be already initialized`,
+                        },
+                    );
+                });
+
+                it('> should succeed when called by session administrator ', async () => {
+                    const expectedRefundId = 0;
+                    const timestampBefore = Math.floor(Date.now()) - 10000; // sometimes, this timestamp is bigger than that set in expense !?
+                    const senderBalanceBefore = await provider.connection.getBalance(alice.publicKey);
+                    const receiverBalanceBefore = await provider.connection.getBalance(bob.publicKey);
+
+                    const {
+                        fees,
+                        events,
+                        accounts: { refundAccountPubkey },
+                    } = await client.sendRefunds(alice, sessionId, [{ amount: 10.2, to: bob.publicKey }]);
+
+                    const refund = await client.getRefund(refundAccountPubkey);
+                    const transferedLamports = refund.amountInLamports.toNumber();
+
+                    assert.equal(refund.sessionId.toString(), sessionId.toString());
+                    assert.equal(refund.from.toString(), alice.publicKey.toString());
+                    assert.equal(refund.to.toString(), bob.publicKey.toString());
+                    assert.isAtLeast(refund.date.getTime(), timestampBefore);
+                    assert.isAtMost(refund.date.getTime(), Math.floor(Date.now()));
+                    assert.equal(refund.amount.toPrecision(4), '10.20');
+                    assert.isAtLeast(transferedLamports, 1);
+
+                    const internalRefund = await program.account.refundAccount.fetch(refundAccountPubkey);
+                    assert.equal(internalRefund.amount.toNumber(), 1020);
+
+                    const { refundAdded } = events;
+                    assert.equal(refundAdded[0].sessionId.toNumber(), sessionId);
+                    assert.equal(refundAdded[0].refundId, expectedRefundId);
+
+                    const senderBalanceAfter = await provider.connection.getBalance(alice.publicKey);
+                    const receiverBalanceAfter = await provider.connection.getBalance(bob.publicKey);
+                    const refundAccountBalance = await provider.connection.getBalance(refundAccountPubkey);
+
+                    assert.equal(receiverBalanceAfter, receiverBalanceBefore + transferedLamports);
+                    assert.equal(senderBalanceAfter, senderBalanceBefore - fees - refundAccountBalance - transferedLamports);
+                });
+
+                it('> should succeed when called by a member', async () => {
+                    const expectedRefundId = 0;
+                    const timestampBefore = Math.floor(Date.now()) - 10000; // sometimes, this timestamp is bigger than that set in expense !?
+                    const senderBalanceBefore = await provider.connection.getBalance(bob.publicKey);
+                    const receiverBalanceBefore = await provider.connection.getBalance(charlie.publicKey);
+
+                    const {
+                        fees,
+                        events,
+                        accounts: { refundAccountPubkey },
+                    } = await client.sendRefunds(bob, sessionId, [{ amount: 10.8, to: charlie.publicKey }]);
+
+                    const refund = await client.getRefund(refundAccountPubkey);
+                    const transferedLamports = refund.amountInLamports.toNumber();
+
+                    assert.equal(refund.sessionId.toString(), sessionId.toString());
+                    assert.equal(refund.from.toString(), bob.publicKey.toString());
+                    assert.equal(refund.to.toString(), charlie.publicKey.toString());
+                    assert.isAtLeast(refund.date.getTime(), timestampBefore);
+                    assert.isAtMost(refund.date.getTime(), Math.floor(Date.now()));
+                    assert.equal(refund.amount.toPrecision(4), '10.80');
+                    assert.isAtLeast(transferedLamports, 1);
+
+                    const { refundAdded } = events;
+                    assert.equal(refundAdded[0].sessionId.toNumber(), sessionId);
+                    assert.equal(refundAdded[0].refundId, expectedRefundId);
+
+                    const senderBalanceAfter = await provider.connection.getBalance(bob.publicKey);
+                    const receiverBalanceAfter = await provider.connection.getBalance(charlie.publicKey);
+                    const refundAccountBalance = await provider.connection.getBalance(refundAccountPubkey);
+
+                    assert.equal(receiverBalanceAfter, receiverBalanceBefore + transferedLamports);
+                    assert.equal(senderBalanceAfter, senderBalanceBefore - fees - refundAccountBalance - transferedLamports);
+                });
+
+                it('> should transfer lamports computed from price update', async () => {
+                    const {
+                        accounts: { refundAccountPubkey },
+                    } = await client.sendRefunds(alice, sessionId, [{ amount: 10.2, to: bob.publicKey }]);
+
+                    // 10.20$ at 150$ per SOL
+                    const refund = await client.getRefund(refundAccountPubkey);
+                    assert.equal(refund.amountInLamports.toNumber(), 68_000_000);
+                });
+
+                it('> should fail when price update is stale', async () => {
+                    await assertError(async () => client.sendRefunds(alice, sessionId, [{ amount: 10, to: bob.publicKey }], STALE_PRICE_UPDATE_ACCOUNT), {
+                        code: 'PriceTooOld',
+                        message: "This price feed update's age exceeds the requested maximum age",
+                    });
+                });
+
+                it('> should fail when price confidence is too low', async () => {
+                    await assertError(async () => client.sendRefunds(alice, sessionId, [{ amount: 10, to: bob.publicKey }], LOW_CONFIDENCE_PRICE_UPDATE_ACCOUNT), {
+                        code: 'PriceConfidenceTooLow',
+                        message: 'Price confidence interval is too wide',
+                    });
+                });
+
+                describe('> slippage', () => {
+                    const addRefund = (expectedLamports: number, maxSlippageBps: number) =>
+                        program.methods
+                            .addRefund(new BN(1020), new BN(expectedLamports), maxSlippageBps, null)
+                            .accountsPartial({
+                                fromAddr: alice.publicKey,
+                                sender: client.findSessionMemberAccountAddress(sessionId, alice.publicKey),
+                                toAddr: bob.publicKey,
+                                receiver: client.findSessionMemberAccountAddress(sessionId, bob.publicKey),
+                                session: client.findSessionAccountAddress(sessionId),
+                                senderBalance: client.findBalanceAccountAddress(sessionId, alice.publicKey),
+                                receiverBalance: client.findBalanceAccountAddress(sessionId, bob.publicKey),
+                                priceUpdate: SOL_USD_PRICE_UPDATE_ACCOUNT,
+                                settlement: null,
+                                refund: client.findRefundAccountAddress(sessionId, new BN(0)),
+                            })
+                            .signers([alice.payer])
+                            .rpc();
+
+                    it('> should succeed when expected lamports are within slippage', async () => {
+                        await addRefund(68_500_000, 100);
+
+                        const refund = await client.getRefund(client.findRefundAccountAddress(sessionId, new BN(0)));
+                        assert.equal(refund.amountInLamports.toNumber(), 68_000_000);
+                    });
+
+                    it('> should fail when expected lamports exceed slippage', async () => {
+                        await assertError(async () => addRefund(70_000_000, 100), {
+                            code: 'SlippageExceeded',
+                            message: 'Refund lamports differ from expected amount more than allowed slippage',
+                        });
+                    });
+                });
+            });
+
+            describe('> sendTokenRefunds', () => {
+                let mint: PublicKey;
+                let aliceTokenAccount: PublicKey;
+                let bobTokenAccount: PublicKey;
+
+                beforeEach(async () => {
+                    mint = await createMint(provider, 6);
+                    aliceTokenAccount = await createTokenAccount(provider, mint, alice.publicKey, 100_000_000);
+                    bobTokenAccount = await createTokenAccount(provider, mint, bob.publicKey);
+                });
+
+                it('> should set session mint when called by admin', async () => {
+                    const {
+                        accounts: { sessionAccountPubkey },
+                    } = await client.setSessionMint(alice, sessionId, mint);
+
+                    let session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.mint.toString(), mint.toString());
+                    assert.equal(session.mintCurrency, DEFAULT_CURRENCY);
+
+                    await client.setSessionMint(alice, sessionId, null);
+                    session = await client.getSession(sessionAccountPubkey);
+                    assert.isNull(session.mint);
+                    assert.equal(session.mintCurrency, '');
+                });
+
+                it('> should fail to set session mint pegged to another currency', async () => {
+                    await assertError(async () => client.setSessionMint(alice, sessionId, mint, 'EUR'), {
+                        code: 'MintCurrencyMismatch',
+                        message: "Token currency doesn't match session currency",
+                    });
+                });
+
+                it('> should fail to set session mint when called by member', async () => {
+                    await assertError(async () => client.setSessionMint(bob, sessionId, mint), {
+                        code: 'ForbiddenRole',
+                        message: "Member role doesn't allow this action",
+                    });
+                });
+
+                it('> should fail when session mint is not set', async () => {
+                    await assertError(async () => client.sendTokenRefunds(alice, sessionId, [{ amount: 10, to: bob.publicKey }]), {
+                        message: 'Session mint is not set',
+                    });
+                });
+
+                it('> should transfer tokens and record refund', async () => {
+                    await client.setSessionMint(alice, sessionId, mint);
+
+                    const {
+                        accounts: { refundAccountPubkey },
+                    } = await client.sendTokenRefunds(alice, sessionId, [{ amount: 10.25, to: bob.publicKey }]);
+
+                    const refund = await client.getRefund(refundAccountPubkey);
+                    assert.equal(refund.amount, 10.25);
+                    assert.equal(refund.mint.toString(), mint.toString());
+                    assert.equal(refund.tokenAmount.toNumber(), 10_250_000);
+                    assert.equal(refund.amountInLamports.toNumber(), 0);
+
+                    assert.equal(await getTokenBalance(provider, aliceTokenAccount), 89_750_000);
+                    assert.equal(await getTokenBalance(provider, bobTokenAccount), 10_250_000);
+
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 10.25);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -10.25);
+                });
+
+                it('> should transfer Token-2022 tokens', async () => {
+                    const mint2022 = await createMint(provider, 6, TOKEN_2022_PROGRAM_ID);
+                    await createTokenAccount(provider, mint2022, alice.publicKey, 100_000_000, TOKEN_2022_PROGRAM_ID);
+                    const bobTokenAccount2022 = await createTokenAccount(provider, mint2022, bob.publicKey, 0, TOKEN_2022_PROGRAM_ID);
+                    await client.setSessionMint(alice, sessionId, mint2022);
+
+                    const {
+                        accounts: { refundAccountPubkey },
+                    } = await client.sendTokenRefunds(alice, sessionId, [{ amount: 5, to: bob.publicKey }]);
+
+                    const refund = await client.getRefund(refundAccountPubkey);
+                    assert.equal(refund.mint.toString(), mint2022.toString());
+                    assert.equal(refund.tokenAmount.toNumber(), 5_000_000);
+                    assert.equal(await getTokenBalance(provider, bobTokenAccount2022), 5_000_000);
+                });
+
+                it("> should fail when amount can't be represented with mint decimals", async () => {
+                    const mintWithoutDecimals = await createMint(provider, 0);
+                    await createTokenAccount(provider, mintWithoutDecimals, alice.publicKey, 100);
+                    await createTokenAccount(provider, mintWithoutDecimals, bob.publicKey);
+                    await client.setSessionMint(alice, sessionId, mintWithoutDecimals);
+
+                    await assertError(async () => client.sendTokenRefunds(alice, sessionId, [{ amount: 10.5, to: bob.publicKey }]), {
+                        code: 'RefundAmountNotTransferable',
+                        message: "Refund amount can't be represented with token decimals",
+                    });
+                });
+            });
+
+            describe('> delete Refund', () => {
+                it('> should failed when called with on open session', async () => {
+                    const {
+                        events: { refundAdded },
+                    } = await client.sendRefunds(alice, sessionId, [{ amount: 20, to: bob.publicKey }]);
+
+                    await assertError(async () => client.deleteRefund(alice, sessionId, new BN(refundAdded[0].refundId)), {
+                        code: 'SessionNotClosed',
+                        message: 'Session is not closed',
+                    });
+                });
+                it('> should failed when called by non admin', async () => {
+                    await client.sendRefunds(alice, sessionId, [{ amount: 20, to: bob.publicKey }]);
+                    await client.closeSession(alice, sessionId);
+                    await assertError(async () => client.deleteSessionMember(bob, sessionId, bob.publicKey), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
+                    });
+                });
+                it('> should close refund pda', async () => {
+                    const {
+                        accounts: { refundAccountPubkey },
+                    } = await client.sendRefunds(alice, sessionId, [{ amount: 20, to: bob.publicKey }]);
+
+                    await client.closeSession(alice, sessionId);
+                    await client.deleteSession(alice, sessionId);
+                    await assertError(async () => client.getRefund(refundAccountPubkey), {
+                        message: ACCOUNT_NOT_FOUND,
+                    });
+                });
+            });
+
+            describe('> reopenSession', () => {
+                it('> should fail when session is opened', async () => {
+                    await assertError(async () => client.reopenSession(alice, sessionId), {
+                        code: 'SessionNotClosed',
+                        message: 'Session is not closed',
+                    });
+                });
+
+                it('> should fail when called by non admin', async () => {
+                    await client.closeSession(alice, sessionId);
+
+                    await assertError(async () => client.reopenSession(bob, sessionId), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
+                    });
+                });
+
+                it('> should fail when reopen window is negative', async () => {
+                    await assertError(async () => client.setReopenWindow(alice, sessionId, -1), {
+                        code: 'InvalidReopenWindow',
+                        message: "Reopen window can't be negative",
+                    });
+                });
+
+                it('> should reopen session within reopen window', async () => {
+                    await client.setReopenWindow(alice, sessionId, 3600);
+                    await client.closeSession(alice, sessionId);
+
+                    const {
+                        accounts: { sessionAccountPubkey },
+                        events: { sessionReopened },
+                    } = await client.reopenSession(alice, sessionId);
+
+                    assert.equal(sessionReopened[0].sessionId.toNumber(), sessionId.toNumber());
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.status, SessionStatus.Opened);
+                    assert.isNull(session.closedAt);
+                    assert.equal(session.reopenWindow, 3600);
+                    await client.addExpense(bob, sessionId, 'forgotten', 10);
+                });
+
+                it('> should require majority approval out of reopen window', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await assertError(async () => client.reopenSession(alice, sessionId), {
+                        code: 'ApprovalRequired',
+                        message: 'Action requires a proposal approved by enough members',
+                    });
+
+                    const {
+                        events: { proposalCreated },
+                    } = await client.createProposal(alice, sessionId, { type: ProposalActionType.ReopenSession });
+                    await client.approveProposal(charlie, sessionId, new BN(proposalCreated[0].proposalId));
+                    const {
+                        accounts: { sessionAccountPubkey },
+                    } = await client.reopenSession(alice, sessionId);
+
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.status, SessionStatus.Opened);
+                });
+            });
+
+            describe('> removeSessionMember', () => {
+                it('> should strip member from expenses and re-split them', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+
+                    const {
+                        accounts: { memberAccountPubkey },
+                        events: { memberRemoved, expenseParticipantRemoved },
+                    } = await client.removeSessionMember(alice, sessionId, charlie.publicKey);
+
+                    assert.equal(memberRemoved[0].expensesCount, 1);
+                    assert.equal(expenseParticipantRemoved[0].memberPubkey.toString(), charlie.publicKey.toString());
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.sameMembers(
+                        expense.participants.map((p) => p.toString()),
+                        [alice, bob].map((m) => m.publicKey.toString()),
+                    );
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 15);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -15);
+                    await assertError(async () => client.getSessionMember(memberAccountPubkey), {
+                        message: ACCOUNT_NOT_FOUND,
+                    });
+                });
+
+                it('> should fail when member owns expenses and no new owner is given', async () => {
+                    await client.addExpense(bob, sessionId, 'exp', 30, [alice.publicKey]);
+
+                    await assertError(async () => client.removeSessionMember(alice, sessionId, bob.publicKey), {
+                        code: 'MemberOwnsExpenses',
+                        message: 'Member paid expenses must be reassigned to another member',
+                    });
+                });
+
+                it('> should reassign expenses paid by member to new owner', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey], { payer: charlie.publicKey, excludePayer: true });
+
+                    await assertError(async () => client.removeSessionMember(alice, sessionId, charlie.publicKey), {
+                        code: 'MemberOwnsExpenses',
+                        message: 'Member paid expenses must be reassigned to another member',
+                    });
+                    const {
+                        events: { expensePayerReassigned },
+                    } = await client.removeSessionMember(alice, sessionId, charlie.publicKey, alice.publicKey);
+
+                    assert.equal(expensePayerReassigned[0].previousPayer.toString(), charlie.publicKey.toString());
+                    assert.equal(expensePayerReassigned[0].newPayer.toString(), alice.publicKey.toString());
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.owner.toString(), alice.publicKey.toString());
+                    assert.equal(expense.payer.toString(), alice.publicKey.toString());
+                    assert.equal((await client.getMemberBalance(sessionId, alice.publicKey)).balance, 30);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -30);
+                });
+
+                it('> should reassign member expenses to new owner', async () => {
+                    const {
+                        accounts: { expenseAccountPubkey },
+                    } = await client.addExpense(bob, sessionId, 'exp', 30, [alice.publicKey]);
+
+                    const {
+                        events: { expenseOwnerReassigned },
+                    } = await client.removeSessionMember(alice, sessionId, bob.publicKey, alice.publicKey);
+
+                    assert.equal(expenseOwnerReassigned[0].newOwner.toString(), alice.publicKey.toString());
+                    const expense = await client.getExpense(expenseAccountPubkey);
+                    assert.equal(expense.owner.toString(), alice.publicKey.toString());
+                    assert.sameMembers(
+                        expense.participants.map((p) => p.toString()),
+                        [alice.publicKey.toString()],
+                    );
+                    const balance = await client.getMemberBalance(sessionId, alice.publicKey);
+                    assert.equal(balance.totalPaid, 30);
+                    assert.equal(balance.balance, 0);
+                });
+
+                it('> should fail when called by non admin', async () => {
+                    await assertError(async () => client.removeSessionMember(bob, sessionId, charlie.publicKey), {
+                        code: 'ForbiddenAsNonAdmin',
+                        message: 'Only session administrator is granted',
+                    });
+                });
+
+                it('> should fail to remove session admin', async () => {
+                    await assertError(async () => client.removeSessionMember(alice, sessionId, alice.publicKey), {
+                        code: 'AdminCannotLeave',
+                        message: "Session administrator can't leave the session",
+                    });
+                });
+            });
+
+            describe('> leaveSession', () => {
+                it('> should close member and balance accounts', async () => {
+                    const {
+                        accounts: { memberAccountPubkey },
+                        events: { memberLeft },
+                    } = await client.leaveSession(charlie, sessionId);
+
+                    assert.equal(memberLeft[0].addr.toString(), charlie.publicKey.toString());
+                    await assertError(async () => client.getSessionMember(memberAccountPubkey), {
+                        message: ACCOUNT_NOT_FOUND,
+                    });
+                    const balances = await client.listSessionBalances(sessionId);
+                    assert.lengthOf(balances, 2);
+                });
+
+                it('> should fail when called by session admin', async () => {
+                    await assertError(async () => client.leaveSession(alice, sessionId), {
+                        code: 'AdminCannotLeave',
+                        message: "Session administrator can't leave the session",
+                    });
+                });
+
+                it('> should fail when member balance is not zero', async () => {
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+
+                    await assertError(async () => client.leaveSession(charlie, sessionId), {
+                        code: 'MemberBalanceNotZero',
+                        message: 'Member balance must be zero',
+                    });
+                });
+
+                it('> should fail when member is involved in pending expenses', async () => {
+                    await client.setExpenseApproval(alice, sessionId, { mode: ExpenseApprovalMode.AllParticipants });
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+
+                    await assertError(async () => client.leaveSession(charlie, sessionId), {
+                        code: 'MemberHasOutstandingExpenses',
+                        message: 'Member is involved in expenses pending approval or disputed',
+                    });
+                });
+
+                it('> should fail when member is still involved in expenses', async () => {
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+                    await client.sendRefunds(charlie, sessionId, [{ amount: 10, to: alice.publicKey }]);
+
+                    await assertError(async () => client.leaveSession(charlie, sessionId), {
+                        code: 'MemberInvolvedInExpenses',
+                        message: 'Member is still involved in expenses',
+                    });
+                });
+
+                it('> should succeed once member is no longer involved in expenses', async () => {
+                    const {
+                        events: { expenseAdded },
+                    } = await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+                    await client.removeExpenseParticipants(alice, sessionId, new BN(expenseAdded[0].expenseId), [charlie.publicKey]);
+
+                    await client.leaveSession(charlie, sessionId);
+
+                    const members = await client.listSessionMembers(sessionId);
+                    assert.notInclude(
+                        members.map((member) => member.addr.toString()),
+                        charlie.publicKey.toString(),
+                    );
+                });
+            });
+
+            describe('> settlement vault', () => {
+                beforeEach(async () => {
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+                });
+
+                it('> should fail to deposit when session is opened', async () => {
+                    await assertError(async () => client.depositToVault(bob, sessionId), {
+                        code: 'SessionNotClosed',
+                        message: 'Session is not closed',
+                    });
+                });
+
+                it('> should fail to deposit when member is not a debtor', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await assertError(async () => client.depositToVault(alice, sessionId), {
+                        code: 'NothingToDeposit',
+                        message: 'Only members with a negative balance can deposit',
+                    });
+                });
 
-                        it('> should fail when called with non expense owner', async () => {
-                            await assertError(async () => client.removeExpenseParticipants(bob, sessionId, expenseId, [charlie.publicKey]), {
-                                code: 'NotExpenseOwner',
-                                message: 'Only expense owner can update or delete expense',
-                            });
-                        });
+                it('> should deposit debt of member', async () => {
+                    await client.closeSession(alice, sessionId);
+                    const vaultBalanceBefore = await provider.connection.getBalance(client.findVaultAccountAddress(sessionId));
 
-                        it('> should fail when called to remove expense owner', async () => {
-                            await assertError(async () => client.removeExpenseParticipants(alice, sessionId, expenseId, [alice.publicKey]), {
-                                code: 'CannotRemoveExpenseOwner',
-                                message: 'Expense owner cannot be removed from participants',
-                            });
-                        });
+                    const {
+                        events: { vaultDeposited },
+                    } = await client.depositToVault(bob, sessionId);
+
+                    // 10$ at 150$ per SOL
+                    const vault = await client.getVault(sessionId);
+                    assert.equal(vault.totalDeposited, 10);
+                    assert.equal(vault.totalLamports.toNumber(), 66_666_666);
+                    assert.equal(vaultDeposited[0].lamports.toNumber(), 66_666_666);
+
+                    const vaultBalanceAfter = await provider.connection.getBalance(client.findVaultAccountAddress(sessionId));
+                    assert.isAtLeast(vaultBalanceAfter - vaultBalanceBefore, 66_666_666);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, 0);
+                });
 
-                        it('> should succeed with one member', async () => {
-                            const {
-                                accounts: { expenseAccountPubkey },
-                                events: { expenseParticipantRemoved },
-                            } = await client.removeExpenseParticipants(alice, sessionId, expenseId, [charlie.publicKey]);
+                it('> should fail to settle when vault does not cover all debts', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await client.depositToVault(bob, sessionId);
 
-                            const expense = await client.getExpense(expenseAccountPubkey);
-                            assert.lengthOf(expense.participants, 3);
-                            assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
-                            assert.equal(expense.participants[1].toString(), bob.publicKey.toString());
-                            assert.equal(expense.participants[2].toString(), paul.publicKey.toString());
+                    await assertError(async () => client.settleSession(alice, sessionId), {
+                        code: 'VaultNotCovered',
+                        message: "Vault doesn't cover all debts",
+                    });
+                });
 
-                            assert.equal(expenseParticipantRemoved[0].expenseId, 0);
-                            assert.equal(expenseParticipantRemoved[0].memberPubkey.toString(), charlie.publicKey.toString());
-                        });
+                it('> should pay out creditors and settle session', async () => {
+                    const {
+                        accounts: { sessionAccountPubkey },
+                    } = await client.closeSession(alice, sessionId);
+                    await client.depositToVault(bob, sessionId);
+                    await client.depositToVault(charlie, sessionId);
+                    const aliceBalanceBefore = await provider.connection.getBalance(alice.publicKey);
 
-                        it('> should succeed with two members', async () => {
-                            const {
-                                accounts: { expenseAccountPubkey },
-                                events: { expenseParticipantRemoved },
-                            } = await client.removeExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey, charlie.publicKey]);
+                    const {
+                        events: { vaultPaidOut, sessionSettled },
+                    } = await client.settleSession(bob, sessionId);
+
+                    const aliceBalanceAfter = await provider.connection.getBalance(alice.publicKey);
+                    assert.equal(aliceBalanceAfter - aliceBalanceBefore, 133_333_332);
+                    assert.equal(vaultPaidOut[0].addr.toString(), alice.publicKey.toString());
+                    assert.equal(sessionSettled[0].sessionId.toNumber(), sessionId.toNumber());
+
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.status, SessionStatus.Settled);
+                    for (const balance of await client.listSessionBalances(sessionId)) {
+                        assert.equal(balance.balance, 0);
+                    }
+
+                    const vault = await client.getVault(sessionId);
+                    assert.equal(vault.epoch, 1);
+                    assert.equal(vault.totalDeposited, 0);
+                });
 
-                            const expense = await client.getExpense(expenseAccountPubkey);
-                            assert.lengthOf(expense.participants, 2);
-                            assert.equal(expense.participants[0].toString(), alice.publicKey.toString());
-                            assert.equal(expense.participants[1].toString(), paul.publicKey.toString());
+                it('> should fail to withdraw when session is closed', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await client.depositToVault(bob, sessionId);
 
-                            assert.equal(expenseParticipantRemoved[0].expenseId, 0);
-                            assert.equal(expenseParticipantRemoved[0].memberPubkey.toString(), bob.publicKey.toString());
-                            assert.equal(expenseParticipantRemoved[1].expenseId, 0);
-                            assert.equal(expenseParticipantRemoved[1].memberPubkey.toString(), charlie.publicKey.toString());
-                        });
+                    await assertError(async () => client.withdrawFromVault(bob, sessionId), {
+                        code: 'SessionClosed',
+                        message: 'Session is closed',
                     });
                 });
+
+                it('> should withdraw deposit once session is reopened', async () => {
+                    await client.setReopenWindow(alice, sessionId, 3600);
+                    await client.closeSession(alice, sessionId);
+                    await client.depositToVault(bob, sessionId);
+                    await client.reopenSession(alice, sessionId);
+                    const bobBalanceBefore = await provider.connection.getBalance(bob.publicKey);
+
+                    const {
+                        events: { vaultWithdrawn },
+                    } = await client.withdrawFromVault(bob, sessionId);
+
+                    assert.equal(vaultWithdrawn[0].lamports.toNumber(), 66_666_666);
+                    const bobBalanceAfter = await provider.connection.getBalance(bob.publicKey);
+                    assert.isAtLeast(bobBalanceAfter - bobBalanceBefore, 66_666_666);
+                    const vault = await client.getVault(sessionId);
+                    assert.equal(vault.totalDeposited, 0);
+                    assert.equal((await client.getMemberBalance(sessionId, bob.publicKey)).balance, -10);
+                });
             });
 
-            describe('> sendRefunds', () => {
-                it('> should fail when called with invalid session id', async () => {
-                    const invalidSessionId = new BN(666);
-                    await assertError(
-                        async () =>
-                            client.sendRefunds(alice, invalidSessionId, [
-                                {
-                                    amount: 10,
-                                    to: bob.publicKey,
-                                },
-                            ]),
-                        {
-                            message: ACCOUNT_NOT_FOUND_ERROR,
-                        },
-                    );
+            describe('> settlement plan', () => {
+                beforeEach(async () => {
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
                 });
 
-                it('> should fail when called with amount equals to 0', async () => {
-                    await assertError(
-                        async () =>
-                            client.sendRefunds(alice, sessionId, [
-                                {
-                                    amount: 0,
-                                    to: bob.publicKey,
-                                },
-                            ]),
-                        {
-                            code: 'RefundAmountMustBeGreaterThanZero',
-                            message: `Refund amount must be greater than zero`,
-                        },
-                    );
+                const findTransferIndex = async (from: Wallet) => {
+                    const settlement = await client.getSettlement(sessionId);
+                    return settlement.transfers.findIndex((transfer) => transfer.from.equals(from.publicKey));
+                };
+
+                it('> should fail to compute settlement when session is opened', async () => {
+                    await assertError(async () => client.computeSettlement(alice, sessionId), {
+                        code: 'SessionNotClosed',
+                        message: 'Session is not closed',
+                    });
                 });
 
-                it('> should failed when called by a non member', async () => {
-                    await assertError(
-                        async () =>
-                            client.sendRefunds(paul, sessionId, [
-                                {
-                                    amount: 10,
-                                    to: bob.publicKey,
-                                },
-                            ]),
-                        {
-                            code: 'AccountNotInitialized',
-                            message: `The program expected this account to be already initialized`,
-                        },
+                it('> should compute minimal transfers from member balances', async () => {
+                    await client.closeSession(alice, sessionId);
+
+                    const {
+                        events: { settlementComputed },
+                    } = await client.computeSettlement(alice, sessionId);
+
+                    assert.equal(settlementComputed[0].transfersCount, 2);
+                    const settlement = await client.getSettlement(sessionId);
+                    assert.equal(settlement.sessionId.toNumber(), sessionId.toNumber());
+                    assert.equal(settlement.planId, 1);
+                    assert.sameMembers(
+                        settlement.transfers.map((transfer) => transfer.from.toString()),
+                        [bob, charlie].map((m) => m.publicKey.toString()),
                     );
+                    for (const transfer of settlement.transfers) {
+                        assert.equal(transfer.to.toString(), alice.publicKey.toString());
+                        assert.equal(transfer.amount, 10);
+                        assert.equal(transfer.paid, 0);
+                    }
                 });
 
-                it('> should failed when receiver is not a member', async () => {
-                    await assertError(
-                        async () =>
-                            client.sendRefunds(alice, sessionId, [
-                                {
-                                    amount: 10,
-                                    to: paul.publicKey,
-                                },
-                            ]),
-                        {
-                            code: 'AccountNotInitialized',
-                            message: `The program expected this account to be already initialized`,
-                        },
-                    );
+                it('> should compute settlement when called by treasurer', async () => {
+                    await client.grantRole(alice, sessionId, bob.publicKey, MemberRole.Treasurer);
+                    await client.closeSession(alice, sessionId);
+                    const {
+                        events: { settlementComputed },
+                    } = await client.computeSettlement(bob, sessionId);
+                    assert.equal(settlementComputed[0].sessionId.toNumber(), sessionId.toNumber());
                 });
 
-                it('> should succeed when called by session administrator ', async () => {
-                    const expectedRefundId = 0;
-                    const timestampBefore = Math.floor(Date.now()) - 10000; // sometimes, this timestamp is bigger than that set in expense !?
-                    const senderBalanceBefore = await provider.connection.getBalance(alice.publicKey);
-                    const receiverBalanceBefore = await provider.connection.getBalance(bob.publicKey);
+                it('> should fail to compute settlement when called by member', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await assertError(async () => client.computeSettlement(bob, sessionId), {
+                        code: 'ForbiddenRole',
+                        message: "Member role doesn't allow this action",
+                    });
+                });
+
+                it('> should mark settlement transfer fulfilled by refund', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await client.computeSettlement(alice, sessionId);
+                    const settlementIndex = await findTransferIndex(bob);
 
                     const {
-                        fees,
-                        events,
                         accounts: { refundAccountPubkey },
-                    } = await client.sendRefunds(alice, sessionId, [{ amount: 10.2, to: bob.publicKey }]);
+                        events: { settlementTransferFulfilled },
+                    } = await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey, settlementIndex }]);
 
+                    assert.equal(settlementTransferFulfilled[0].index, settlementIndex);
+                    assert.equal(settlementTransferFulfilled[0].from.toString(), bob.publicKey.toString());
+                    const settlement = await client.getSettlement(sessionId);
+                    assert.equal(settlement.transfers[settlementIndex].paid, 10);
                     const refund = await client.getRefund(refundAccountPubkey);
-                    const transferedLamports = refund.amountInLamports.toNumber();
+                    assert.equal(refund.settlementIndex, settlementIndex);
+                });
 
-                    assert.equal(refund.sessionId.toString(), sessionId.toString());
-                    assert.equal(refund.from.toString(), alice.publicKey.toString());
-                    assert.equal(refund.to.toString(), bob.publicKey.toString());
-                    assert.isAtLeast(refund.date.getTime(), timestampBefore);
-                    assert.isAtMost(refund.date.getTime(), Math.floor(Date.now()));
-                    assert.equal(refund.amount.toPrecision(4), '10.20');
-                    assert.isAtLeast(transferedLamports, 1);
+                it("> should fail when refund doesn't match settlement transfer", async () => {
+                    await client.closeSession(alice, sessionId);
+                    await client.computeSettlement(alice, sessionId);
+                    const settlementIndex = await findTransferIndex(bob);
 
-                    const { refundAdded } = events;
-                    assert.equal(refundAdded[0].sessionId.toNumber(), sessionId);
-                    assert.equal(refundAdded[0].refundId, expectedRefundId);
+                    await assertError(async () => client.sendRefunds(charlie, sessionId, [{ amount: 10, to: alice.publicKey, settlementIndex }]), {
+                        code: 'InvalidSettlementTransfer',
+                        message: "Refund doesn't match settlement plan transfer",
+                    });
+                });
 
-                    const senderBalanceAfter = await provider.connection.getBalance(alice.publicKey);
-                    const receiverBalanceAfter = await provider.connection.getBalance(bob.publicKey);
-                    const refundAccountBalance = await provider.connection.getBalance(refundAccountPubkey);
+                it('> should fail when refund exceeds remaining amount of settlement transfer', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await client.computeSettlement(alice, sessionId);
+                    const settlementIndex = await findTransferIndex(bob);
 
-                    assert.equal(receiverBalanceAfter, receiverBalanceBefore + transferedLamports);
-                    assert.equal(senderBalanceAfter, senderBalanceBefore - fees - refundAccountBalance - transferedLamports);
+                    await client.sendRefunds(bob, sessionId, [{ amount: 6, to: alice.publicKey, settlementIndex }]);
+                    await assertError(async () => client.sendRefunds(bob, sessionId, [{ amount: 6, to: alice.publicKey, settlementIndex }]), {
+                        code: 'SettlementTransferExceeded',
+                        message: 'Refund amount exceeds remaining amount of settlement plan transfer',
+                    });
                 });
 
-                it('> should succeed when called by a member', async () => {
-                    const expectedRefundId = 0;
-                    const timestampBefore = Math.floor(Date.now()) - 10000; // sometimes, this timestamp is bigger than that set in expense !?
-                    const senderBalanceBefore = await provider.connection.getBalance(bob.publicKey);
-                    const receiverBalanceBefore = await provider.connection.getBalance(charlie.publicKey);
-
+                it('> should revert settlement transfer when refund is deleted', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await client.computeSettlement(alice, sessionId);
+                    const settlementIndex = await findTransferIndex(bob);
                     const {
-                        fees,
-                        events,
-                        accounts: { refundAccountPubkey },
-                    } = await client.sendRefunds(bob, sessionId, [{ amount: 10.8, to: charlie.publicKey }]);
+                        events: { refundAdded },
+                    } = await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey, settlementIndex }]);
 
-                    const refund = await client.getRefund(refundAccountPubkey);
-                    const transferedLamports = refund.amountInLamports.toNumber();
+                    await client.deleteRefund(alice, sessionId, new BN(refundAdded[0].refundId));
 
-                    assert.equal(refund.sessionId.toString(), sessionId.toString());
-                    assert.equal(refund.from.toString(), bob.publicKey.toString());
-                    assert.equal(refund.to.toString(), charlie.publicKey.toString());
-                    assert.isAtLeast(refund.date.getTime(), timestampBefore);
-                    assert.isAtMost(refund.date.getTime(), Math.floor(Date.now()));
-                    assert.equal(refund.amount.toPrecision(4), '10.80');
-                    assert.isAtLeast(transferedLamports, 1);
+                    const settlement = await client.getSettlement(sessionId);
+                    assert.equal(settlement.transfers[settlementIndex].paid, 0);
+                });
 
-                    const { refundAdded } = events;
-                    assert.equal(refundAdded[0].sessionId.toNumber(), sessionId);
-                    assert.equal(refundAdded[0].refundId, expectedRefundId);
+                it('> should not revert recomputed settlement plan when refund is deleted', async () => {
+                    await client.closeSession(alice, sessionId);
+                    await client.computeSettlement(alice, sessionId);
+                    const settlementIndex = await findTransferIndex(bob);
+                    const {
+                        events: { refundAdded },
+                    } = await client.sendRefunds(bob, sessionId, [{ amount: 4, to: alice.publicKey, settlementIndex }]);
+                    await client.computeSettlement(alice, sessionId);
+                    const recomputedIndex = await findTransferIndex(bob);
+                    await client.sendRefunds(bob, sessionId, [{ amount: 6, to: alice.publicKey, settlementIndex: recomputedIndex }]);
 
-                    const senderBalanceAfter = await provider.connection.getBalance(bob.publicKey);
-                    const receiverBalanceAfter = await provider.connection.getBalance(charlie.publicKey);
-                    const refundAccountBalance = await provider.connection.getBalance(refundAccountPubkey);
+                    await client.deleteRefund(alice, sessionId, new BN(refundAdded[0].refundId));
 
-                    assert.equal(receiverBalanceAfter, receiverBalanceBefore + transferedLamports);
-                    assert.equal(senderBalanceAfter, senderBalanceBefore - fees - refundAccountBalance - transferedLamports);
+                    const settlement = await client.getSettlement(sessionId);
+                    assert.equal(settlement.planId, 2);
+                    assert.equal(settlement.transfers[recomputedIndex].paid, 6);
                 });
             });
 
-            describe('> delete Refund', () => {
-                it('> should failed when called with on open session', async () => {
-                    const {
-                        events: { refundAdded },
-                    } = await client.sendRefunds(alice, sessionId, [{ amount: 20, to: bob.publicKey }]);
+            describe('> finalizeSession', () => {
+                beforeEach(async () => {
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+                });
 
-                    await assertError(async () => client.deleteRefund(alice, sessionId, new BN(refundAdded[0].refundId)), {
+                it('> should fail when session is opened', async () => {
+                    await assertError(async () => client.finalizeSession(bob, sessionId), {
                         code: 'SessionNotClosed',
                         message: 'Session is not closed',
                     });
                 });
-                it('> should failed when called by non admin', async () => {
-                    await client.sendRefunds(alice, sessionId, [{ amount: 20, to: bob.publicKey }]);
+
+                it('> should fail when a member balance is not settled', async () => {
                     await client.closeSession(alice, sessionId);
-                    await assertError(async () => client.deleteSessionMember(bob, sessionId, bob.publicKey), {
-                        code: 'ForbiddenAsNonAdmin',
-                        message: 'Only session administrator is granted',
+                    await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey }]);
+
+                    await assertError(async () => client.finalizeSession(bob, sessionId), {
+                        code: 'SessionNotSettled',
+                        message: 'Every member balance must be settled',
                     });
                 });
-                it('> should close refund pda', async () => {
-                    const {
-                        accounts: { refundAccountPubkey },
-                    } = await client.sendRefunds(alice, sessionId, [{ amount: 20, to: bob.publicKey }]);
 
+                it('> should fail when called by a non member', async () => {
                     await client.closeSession(alice, sessionId);
-                    await client.deleteSession(alice, sessionId);
-                    await assertError(async () => client.getRefund(refundAccountPubkey), {
-                        message: ACCOUNT_NOT_FOUND,
+                    await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey }]);
+                    await client.sendRefunds(charlie, sessionId, [{ amount: 10, to: alice.publicKey }]);
+
+                    await assertError(async () => client.finalizeSession(paul, sessionId), {
+                        message: ACCOUNT_NOT_INITIALIZED,
                     });
                 });
+
+                it('> should settle session when every member balance is settled', async () => {
+                    const {
+                        accounts: { sessionAccountPubkey },
+                    } = await client.closeSession(alice, sessionId);
+                    await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey }]);
+                    await client.sendRefunds(charlie, sessionId, [{ amount: 10, to: alice.publicKey }]);
+
+                    const {
+                        events: { sessionSettled },
+                    } = await client.finalizeSession(charlie, sessionId);
+
+                    assert.equal(sessionSettled[0].sessionId.toNumber(), sessionId.toNumber());
+                    const session = await client.getSession(sessionAccountPubkey);
+                    assert.equal(session.status, SessionStatus.Settled);
+                });
+            });
+
+            describe('> member balances', () => {
+                const assertBalance = async (member: Wallet, totalPaid: number, totalOwed: number, balance: number) => {
+                    const memberBalance = await client.getMemberBalance(sessionId, member.publicKey);
+                    assert.equal(memberBalance.totalPaid, totalPaid);
+                    assert.equal(memberBalance.totalOwed, totalOwed);
+                    assert.equal(memberBalance.balance, balance);
+                };
+
+                it('> should create zero balance for each member', async () => {
+                    const balances = await client.listSessionBalances(sessionId);
+                    assert.lengthOf(balances, 3);
+                    assert.sameMembers(
+                        balances.map((b) => b.addr.toString()),
+                        [alice, bob, charlie].map((m) => m.publicKey.toString()),
+                    );
+                    for (const balance of balances) {
+                        assert.equal(balance.totalPaid, 0);
+                        assert.equal(balance.totalOwed, 0);
+                        assert.equal(balance.balance, 0);
+                    }
+                });
+
+                it('> should update balances when an expense is added', async () => {
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+
+                    await assertBalance(alice, 30, 10, 20);
+                    await assertBalance(bob, 0, 10, -10);
+                    await assertBalance(charlie, 0, 10, -10);
+                });
+
+                it('> should update balances when an expense is updated', async () => {
+                    const { events } = await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+                    await client.updateExpense(alice, sessionId, new BN(events.expenseAdded[0].expenseId), 'exp', 60, [bob.publicKey]);
+
+                    await assertBalance(alice, 60, 30, 30);
+                    await assertBalance(bob, 0, 30, -30);
+                    await assertBalance(charlie, 0, 0, 0);
+                });
+
+                it('> should update balances when participants are added or removed', async () => {
+                    const { events } = await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey]);
+                    const expenseId = new BN(events.expenseAdded[0].expenseId);
+
+                    await client.addExpenseParticipants(alice, sessionId, expenseId, [charlie.publicKey]);
+                    await assertBalance(alice, 30, 10, 20);
+                    await assertBalance(charlie, 0, 10, -10);
+
+                    await client.removeExpenseParticipants(alice, sessionId, expenseId, [bob.publicKey]);
+                    await assertBalance(alice, 30, 15, 15);
+                    await assertBalance(bob, 0, 0, 0);
+                    await assertBalance(charlie, 0, 15, -15);
+                });
+
+                it('> should revert balances when an expense is deleted', async () => {
+                    const { events } = await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+                    await client.deleteExpense(alice, sessionId, new BN(events.expenseAdded[0].expenseId));
+
+                    await assertBalance(alice, 0, 0, 0);
+                    await assertBalance(bob, 0, 0, 0);
+                    await assertBalance(charlie, 0, 0, 0);
+                });
+
+                it('> should settle balances with refunds and revert them when refund is deleted', async () => {
+                    await client.addExpense(alice, sessionId, 'exp', 30, [bob.publicKey, charlie.publicKey]);
+                    const {
+                        events: { refundAdded },
+                    } = await client.sendRefunds(bob, sessionId, [{ amount: 10, to: alice.publicKey }]);
+
+                    await assertBalance(alice, 30, 20, 10);
+                    await assertBalance(bob, 10, 10, 0);
+
+                    await client.closeSession(alice, sessionId);
+                    await client.deleteRefund(alice, sessionId, new BN(refundAdded[0].refundId));
+
+                    await assertBalance(alice, 30, 10, 20);
+                    await assertBalance(bob, 0, 10, -10);
+                });
             });
         });
 
@@ -1002,8 +3225,8 @@ describe('solidr', () => {
             });
 
             describe('> create invitation link', () => {
-                it('> should fail because session is closed', async () => {
-                    await assertError(async () => client.generateSessionLink(alice, sessionId), {
+                it('> should fail to create invitation because session is closed', async () => {
+                    await assertError(async () => client.createInvitation(alice, sessionId), {
                         code: 'SessionClosed',
                         message: `Session is closed`,
                     });
@@ -1061,6 +3284,15 @@ describe('solidr', () => {
                     refundsCount: 0,
                     status: SessionStatus.Opened,
                     invitationHash: MISSING_INVITATION_HASH,
+                    currency: DEFAULT_CURRENCY,
+                    decimals: DEFAULT_DECIMALS,
+                    mint: null,
+                    mintCurrency: '',
+                    pendingAdmin: null,
+                    approvalThreshold: 0,
+                    expenseApproval: { mode: ExpenseApprovalMode.Disabled },
+                    closedAt: null,
+                    reopenWindow: 0,
                 },
                 {
                     sessionId: zoeSessionIds[0],
@@ -1071,6 +3303,15 @@ describe('solidr', () => {
                     refundsCount: 0,
                     status: SessionStatus.Opened,
                     invitationHash: MISSING_INVITATION_HASH,
+                    currency: DEFAULT_CURRENCY,
+                    decimals: DEFAULT_DECIMALS,
+                    mint: null,
+                    mintCurrency: '',
+                    pendingAdmin: null,
+                    approvalThreshold: 0,
+                    expenseApproval: { mode: ExpenseApprovalMode.Disabled },
+                    closedAt: null,
+                    reopenWindow: 0,
                 },
                 {
                     sessionId: zoeSessionIds[1],
@@ -1081,6 +3322,15 @@ describe('solidr', () => {
                     refundsCount: 0,
                     status: SessionStatus.Opened,
                     invitationHash: MISSING_INVITATION_HASH,
+                    currency: DEFAULT_CURRENCY,
+                    decimals: DEFAULT_DECIMALS,
+                    mint: null,
+                    mintCurrency: '',
+                    pendingAdmin: null,
+                    approvalThreshold: 0,
+                    expenseApproval: { mode: ExpenseApprovalMode.Disabled },
+                    closedAt: null,
+                    reopenWindow: 0,
                 },
                 {
                     sessionId: zoeSessionIds[2],
@@ -1091,6 +3341,15 @@ describe('solidr', () => {
                     refundsCount: 0,
                     status: SessionStatus.Opened,
                     invitationHash: MISSING_INVITATION_HASH,
+                    currency: DEFAULT_CURRENCY,
+                    decimals: DEFAULT_DECIMALS,
+                    mint: null,
+                    mintCurrency: '',
+                    pendingAdmin: null,
+                    approvalThreshold: 0,
+                    expenseApproval: { mode: ExpenseApprovalMode.Disabled },
+                    closedAt: null,
+                    reopenWindow: 0,
                 },
                 {
                     sessionId: zoeSessionIds[3],
@@ -1101,6 +3360,15 @@ describe('solidr', () => {
                     refundsCount: 0,
                     status: SessionStatus.Opened,
                     invitationHash: MISSING_INVITATION_HASH,
+                    currency: DEFAULT_CURRENCY,
+                    decimals: DEFAULT_DECIMALS,
+                    mint: null,
+                    mintCurrency: '',
+                    pendingAdmin: null,
+                    approvalThreshold: 0,
+                    expenseApproval: { mode: ExpenseApprovalMode.Disabled },
+                    closedAt: null,
+                    reopenWindow: 0,
                 },
             ]);
 
@@ -1115,6 +3383,15 @@ describe('solidr', () => {
                     refundsCount: 0,
                     status: SessionStatus.Opened,
                     invitationHash: MISSING_INVITATION_HASH,
+                    currency: DEFAULT_CURRENCY,
+                    decimals: DEFAULT_DECIMALS,
+                    mint: null,
+                    mintCurrency: '',
+                    pendingAdmin: null,
+                    approvalThreshold: 0,
+                    expenseApproval: { mode: ExpenseApprovalMode.Disabled },
+                    closedAt: null,
+                    reopenWindow: 0,
                 },
             ]);
         });
@@ -1128,6 +3405,7 @@ describe('solidr', () => {
                     {
                         addr: alice.publicKey,
                         isAdmin: true,
+                        role: MemberRole.Admin,
                         name: 'Alice',
                         sessionId,
                     },
@@ -1148,18 +3426,21 @@ describe('solidr', () => {
                     {
                         addr: alice.publicKey,
                         isAdmin: true,
+                        role: MemberRole.Admin,
                         name: 'Alice',
                         sessionId,
                     },
                     {
                         addr: bob.publicKey,
                         isAdmin: false,
+                        role: MemberRole.Member,
                         name: 'Bob',
                         sessionId,
                     },
                     {
                         addr: charlie.publicKey,
                         isAdmin: false,
+                        role: MemberRole.Member,
                         name: 'Charlie',
                         sessionId,
                     },
@@ -1170,6 +3451,7 @@ describe('solidr', () => {
                     {
                         addr: zoe.publicKey,
                         isAdmin: false,
+                        role: MemberRole.Member,
                         name: 'Zoé',
                         sessionId,
                     },
@@ -1418,5 +3700,26 @@ describe('solidr', () => {
             //transfers
             assert.lengthOf(transfers, 0);
         });
+
+        it('> Should handle weighted and exact splits', async () => {
+            await client.addExpense(alice, sessionId, 'exp 1', 40, [bob.publicKey, charlie.publicKey], { splitMode: SplitMode.Weighted, shares: [2, 1] });
+            await client.addExpense(bob, sessionId, 'exp 2', 30, [alice.publicKey, charlie.publicKey], { splitMode: SplitMode.Exact, shares: [10, 20] });
+            const expenses = await client.listSessionExpenses(sessionId);
+            const refunds = await client.listSessionRefunds(sessionId);
+            const { totalExpenses, balances, transfers } = await client.computeBalance(sessionMembers, expenses, refunds);
+
+            //balances
+            assertBalance(balances, alice, { owner: alice.publicKey, balance: 20, totalCost: 20 });
+            assertBalance(balances, bob, { owner: bob.publicKey, balance: 10, totalCost: 20 });
+            assertBalance(balances, charlie, { owner: charlie.publicKey, balance: -30, totalCost: 30 });
+            assertBalance(balances, zoe, { owner: zoe.publicKey, balance: 0, totalCost: 0 });
+            assert.equal(totalExpenses, 70);
+            //transfers
+            assert.lengthOf(transfers, 2);
+            assert.includeDeepMembers(transfers, [
+                { from: charlie.publicKey, to: alice.publicKey, amount: 20 },
+                { from: charlie.publicKey, to: bob.publicKey, amount: 10 },
+            ]);
+        });
     });
 });
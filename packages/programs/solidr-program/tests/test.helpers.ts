@@ -1,9 +1,14 @@
-import { AnchorError } from '@coral-xyz/anchor';
+import { AnchorError, AnchorProvider, BN, utils } from '@coral-xyz/anchor';
+import { Keypair, PublicKey, SystemProgram, Transaction, TransactionInstruction } from '@solana/web3.js';
 import { assert } from 'chai';
 
 export const ACCOUNT_NOT_FOUND = `Error: Account does not exist or has no data`;
 export const ACCOUNT_NOT_INITIALIZED = `The program expected this account to be already initialized`;
 
+// mock price updates loaded by local validator from tests/fixtures, SOL/USD being priced 150$
+export const STALE_PRICE_UPDATE_ACCOUNT = new PublicKey('4wMTGYZEKZ8rf9UsmrZJxdmSi3bjXwocsTFd7NJRL6sP');
+export const LOW_CONFIDENCE_PRICE_UPDATE_ACCOUNT = new PublicKey('Eez8DE6LVimnMt9N5afc16Fwdqk2B8vL1C3XnLpbqVGg');
+
 export interface IExpectedError {
     code?: string;
     message: string;
@@ -28,3 +33,80 @@ export async function assertError(fn: () => Promise<any>, expected: IExpectedErr
         }
     }
 }
+
+export const TOKEN_2022_PROGRAM_ID = new PublicKey('TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb');
+const MINT_SIZE = 82;
+
+/**
+ * Creates a mint without extensions, whose authority is provider wallet.
+ */
+export async function createMint(provider: AnchorProvider, decimals: number, tokenProgramId: PublicKey = utils.token.TOKEN_PROGRAM_ID): Promise<PublicKey> {
+    const mint = Keypair.generate();
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(MINT_SIZE);
+    const tx = new Transaction().add(
+        SystemProgram.createAccount({
+            fromPubkey: provider.wallet.publicKey,
+            newAccountPubkey: mint.publicKey,
+            lamports,
+            space: MINT_SIZE,
+            programId: tokenProgramId,
+        }),
+        new TransactionInstruction({
+            // InitializeMint2 without freeze authority
+            programId: tokenProgramId,
+            keys: [{ pubkey: mint.publicKey, isSigner: false, isWritable: true }],
+            data: Buffer.concat([Buffer.from([20, decimals]), provider.wallet.publicKey.toBuffer(), Buffer.from([0])]),
+        }),
+    );
+    await provider.sendAndConfirm(tx, [mint]);
+    return mint.publicKey;
+}
+
+/**
+ * Creates associated token account of owner and mints given raw amount of tokens to it.
+ */
+export async function createTokenAccount(
+    provider: AnchorProvider,
+    mint: PublicKey,
+    owner: PublicKey,
+    amount: number = 0,
+    tokenProgramId: PublicKey = utils.token.TOKEN_PROGRAM_ID,
+): Promise<PublicKey> {
+    const [tokenAccount] = PublicKey.findProgramAddressSync([owner.toBuffer(), tokenProgramId.toBuffer(), mint.toBuffer()], utils.token.ASSOCIATED_PROGRAM_ID);
+    const tx = new Transaction().add(
+        new TransactionInstruction({
+            programId: utils.token.ASSOCIATED_PROGRAM_ID,
+            keys: [
+                { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: true },
+                { pubkey: tokenAccount, isSigner: false, isWritable: true },
+                { pubkey: owner, isSigner: false, isWritable: false },
+                { pubkey: mint, isSigner: false, isWritable: false },
+                { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+                { pubkey: tokenProgramId, isSigner: false, isWritable: false },
+            ],
+            data: Buffer.from([0]),
+        }),
+    );
+    if (amount > 0) {
+        // MintTo
+        const data = Buffer.concat([Buffer.from([7]), new BN(amount).toArrayLike(Buffer, 'le', 8)]);
+        tx.add(
+            new TransactionInstruction({
+                programId: tokenProgramId,
+                keys: [
+                    { pubkey: mint, isSigner: false, isWritable: true },
+                    { pubkey: tokenAccount, isSigner: false, isWritable: true },
+                    { pubkey: provider.wallet.publicKey, isSigner: true, isWritable: false },
+                ],
+                data,
+            }),
+        );
+    }
+    await provider.sendAndConfirm(tx);
+    return tokenAccount;
+}
+
+export async function getTokenBalance(provider: AnchorProvider, tokenAccount: PublicKey): Promise<number> {
+    const { value } = await provider.connection.getTokenAccountBalance(tokenAccount);
+    return Number(value.amount);
+}